@@ -0,0 +1,98 @@
+use core::time::Duration;
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use orderbooklib::{ActorEngine, MatchingEngine, OrderRequest, OrderType, Side, TradingPair};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal};
+use rust_decimal::Decimal;
+
+const ORDERS_PER_MARKET: usize = 20_000;
+
+fn markets(n: usize) -> Vec<TradingPair> {
+    (0..n)
+        .map(|i| TradingPair::new(format!("BASE{i}"), "QUOTE".to_string()))
+        .collect()
+}
+
+fn random_flow(seed: u64) -> Vec<OrderRequest> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let normal = Normal::new(5000.0, 50.0).unwrap();
+    (0..ORDERS_PER_MARKET)
+        .map(|_| {
+            let side = if rng.random_bool(0.5) {
+                Side::Bid
+            } else {
+                Side::Ask
+            };
+            let price = Decimal::from(normal.sample(&mut rng) as u64);
+            OrderRequest::new(side, rng.random_range(1..=500), OrderType::Limit(price))
+        })
+        .collect()
+}
+
+/// The same flow through the single-threaded engine and through one actor
+/// thread per market, with one producer per market pipelining its orders.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("engine-benchmark");
+    group.sample_size(10);
+    group.measurement_time(Duration::new(10, 0));
+
+    for n in [1, 2, 4, 8] {
+        let pairs = markets(n);
+        let flows: Vec<_> = (0..n as u64).map(random_flow).collect();
+        group.throughput(Throughput::Elements((n * ORDERS_PER_MARKET) as u64));
+
+        group.bench_with_input(BenchmarkId::new("MatchingEngine", n), &n, |b, _| {
+            b.iter_batched(
+                || {
+                    let mut engine = MatchingEngine::new();
+                    for pair in &pairs {
+                        engine.add_market(pair.clone()).unwrap();
+                    }
+                    (engine, flows.clone())
+                },
+                |(mut engine, flows)| {
+                    for (pair, flow) in pairs.iter().zip(flows) {
+                        for order in flow {
+                            engine.place_order(pair, order).unwrap();
+                        }
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+
+        group.bench_with_input(BenchmarkId::new("ActorEngine", n), &n, |b, _| {
+            b.iter_batched(
+                || {
+                    let engine = ActorEngine::new();
+                    for pair in &pairs {
+                        engine.handle().add_market(pair.clone()).unwrap();
+                    }
+                    (engine, flows.clone())
+                },
+                |(engine, flows)| {
+                    std::thread::scope(|s| {
+                        for (pair, flow) in pairs.iter().zip(flows) {
+                            let handle = engine.handle();
+                            s.spawn(move || {
+                                let replies: Vec<_> = flow
+                                    .into_iter()
+                                    .map(|order| handle.place_order(pair, order).unwrap())
+                                    .collect();
+                                for reply in replies {
+                                    reply.recv().unwrap();
+                                }
+                            });
+                        }
+                    });
+                    engine
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use orderbooklib::{
+    ItchEncoder, ItchMessage, MatchingEngine, MessageRef, OrderRequest, OrderType, Side,
+    TradingPair,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal};
+use rust_decimal::Decimal;
+
+const ORDERS: usize = 20_000;
+
+/// The feed produced by a random limit order flow against one market.
+fn feed() -> Vec<ItchMessage> {
+    let pair = TradingPair::new("BTC".to_string(), "USDT".to_string());
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair.clone()).unwrap();
+    let changes = engine.subscribe_book_changes(&pair).unwrap();
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let normal = Normal::new(5000.0, 50.0).unwrap();
+    for _ in 0..ORDERS {
+        let side = if rng.random_bool(0.5) {
+            Side::Bid
+        } else {
+            Side::Ask
+        };
+        let price = Decimal::from(normal.sample(&mut rng) as u64);
+        let order = OrderRequest::new(side, rng.random_range(1..=500), OrderType::Limit(price));
+        engine.place_order(&pair, order).unwrap();
+    }
+
+    let mut encoder = ItchEncoder::new();
+    encoder.add_market(pair.clone()).unwrap();
+    changes
+        .try_iter()
+        .flat_map(|change| encoder.book_change(&pair, &change).unwrap())
+        .collect()
+}
+
+/// Encoding and decoding the same messages as ITCH and as JSON lines.
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let messages = feed();
+    let mut itch = Vec::new();
+    let mut json = Vec::new();
+    for message in &messages {
+        message.encode(&mut itch).unwrap();
+        serde_json::to_writer(&mut json, message).unwrap();
+        json.push(b'\n');
+    }
+    println!(
+        "{} messages: {} bytes as ITCH, {} bytes as JSON",
+        messages.len(),
+        itch.len(),
+        json.len()
+    );
+
+    let mut group = c.benchmark_group("itch-benchmark");
+    group.throughput(Throughput::Elements(messages.len() as u64));
+
+    group.bench_function("encode/itch", |b| {
+        let mut out = Vec::with_capacity(itch.len());
+        b.iter(|| {
+            out.clear();
+            for message in &messages {
+                message.encode(&mut out).unwrap();
+            }
+        });
+    });
+    group.bench_function("encode/json", |b| {
+        let mut out = Vec::with_capacity(json.len());
+        b.iter(|| {
+            out.clear();
+            for message in &messages {
+                serde_json::to_writer(&mut out, message).unwrap();
+                out.push(b'\n');
+            }
+        });
+    });
+
+    group.bench_function("decode/itch-zero-copy", |b| {
+        b.iter(|| {
+            let mut buf = itch.as_slice();
+            let mut seen = 0u64;
+            while let Some((message, used)) = MessageRef::decode(buf).unwrap() {
+                seen ^= message.timestamp();
+                buf = &buf[used..];
+            }
+            seen
+        });
+    });
+    group.bench_function("decode/itch-owned", |b| {
+        b.iter(|| {
+            MessageRef::decode_all(&itch)
+                .unwrap()
+                .iter()
+                .map(MessageRef::to_message)
+                .collect::<Vec<_>>()
+        });
+    });
+    group.bench_function("decode/json", |b| {
+        b.iter(|| {
+            json.split(|&b| b == b'\n')
+                .filter(|line| !line.is_empty())
+                .map(|line| serde_json::from_slice::<ItchMessage>(line).unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,297 @@
+use core::time::Duration;
+use std::time::Instant;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use orderbooklib::{
+    MatchingEngine, OrderBook, OrderId, OrderRequest, OrderType, Side, TradingPair,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use rand_distr::{Distribution, LogNormal, Normal};
+use rust_decimal::Decimal;
+
+const BOOK_ORDERS: usize = 10_000;
+const MID: f64 = 5000.0;
+
+/// Resting limit orders around `MID`: distances from the mid are normally
+/// distributed, so liquidity thins out away from the touch, and sizes are
+/// log-normal, so most orders are small with the odd large one. Bids and
+/// asks never cross.
+struct Flow {
+    rng: StdRng,
+    distance: Normal<f64>,
+    size: LogNormal<f64>,
+}
+
+impl Flow {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            distance: Normal::new(0.0, 20.0).unwrap(),
+            size: LogNormal::new(2.0, 1.0).unwrap(),
+        }
+    }
+
+    fn side(&mut self) -> Side {
+        if self.rng.random_bool(0.5) {
+            Side::Bid
+        } else {
+            Side::Ask
+        }
+    }
+
+    fn qty(&mut self) -> Decimal {
+        Decimal::from(self.size.sample(&mut self.rng).ceil() as u64)
+    }
+
+    fn passive(&mut self) -> OrderRequest {
+        let side = self.side();
+        let ticks = 1 + self.distance.sample(&mut self.rng).abs() as u64;
+        let price = match side {
+            Side::Bid => MID as u64 - ticks,
+            Side::Ask => MID as u64 + ticks,
+        };
+        OrderRequest::new(side, self.qty(), OrderType::limit(price))
+    }
+
+    fn passive_orders(&mut self, n: usize) -> Vec<OrderRequest> {
+        (0..n).map(|_| self.passive()).collect()
+    }
+}
+
+fn book_with(orders: &[OrderRequest]) -> OrderBook {
+    let mut book = OrderBook::default();
+    for order in orders {
+        book.add_order(*order);
+    }
+    book
+}
+
+/// Quantity resting on the best `levels` asks.
+fn depth_qty(book: &OrderBook, levels: usize) -> Decimal {
+    book.asks.levels().take(levels).map(|(_, q)| q).sum()
+}
+
+fn insert_and_cancel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("order-benchmark");
+    let orders = Flow::new(1).passive_orders(BOOK_ORDERS);
+    let mut ids: Vec<OrderId> = orders.iter().map(OrderRequest::id).collect();
+    ids.shuffle(&mut StdRng::seed_from_u64(2));
+    group.throughput(Throughput::Elements(BOOK_ORDERS as u64));
+
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || orders.clone(),
+            |orders| book_with(&orders),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("cancel", |b| {
+        b.iter_batched(
+            || book_with(&orders),
+            |mut book| {
+                for id in &ids {
+                    book.delete_order(*id);
+                }
+                book
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// A single aggressive order sweeping the best `levels` levels, and FOK
+/// orders that pass or fail the pre-check at the same depth.
+fn matching(c: &mut Criterion) {
+    let mut group = c.benchmark_group("order-benchmark/match");
+    let orders = Flow::new(3).passive_orders(BOOK_ORDERS);
+    let book = book_with(&orders);
+    group.throughput(Throughput::Elements(1));
+
+    for levels in [1, 10, 50] {
+        let qty = depth_qty(&book, levels);
+        let limit = book.asks.levels().nth(levels - 1).unwrap().0;
+        group.bench_with_input(BenchmarkId::new("sweep", levels), &qty, |b, qty| {
+            b.iter_batched(
+                || book_with(&orders),
+                |mut book| book.add_order(OrderRequest::new(Side::Bid, *qty, OrderType::Market)),
+                BatchSize::LargeInput,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("fok-fill", levels), &qty, |b, qty| {
+            b.iter_batched(
+                || book_with(&orders),
+                |mut book| {
+                    book.add_order(OrderRequest::new(Side::Bid, *qty, OrderType::fok(limit)))
+                },
+                BatchSize::LargeInput,
+            );
+        });
+        // A rejected FOK leaves the book as it was, so one book serves
+        // every iteration.
+        let mut untouched = book_with(&orders);
+        group.bench_with_input(BenchmarkId::new("fok-reject", levels), &qty, |b, qty| {
+            b.iter(|| {
+                let qty = qty + Decimal::ONE;
+                untouched.add_order(OrderRequest::new(Side::Bid, qty, OrderType::fok(limit)))
+            });
+        });
+    }
+    group.finish();
+}
+
+fn snapshots(c: &mut Criterion) {
+    let mut group = c.benchmark_group("order-benchmark/snapshot");
+    let mut book = book_with(&Flow::new(4).passive_orders(BOOK_ORDERS));
+
+    for depth in [1, 20, 100] {
+        book.set_snapshot_depth(depth);
+        group.bench_with_input(BenchmarkId::new("publish", depth), &depth, |b, _| {
+            b.iter(|| book.publish());
+        });
+    }
+    group.bench_function("full-state", |b| b.iter(|| book.get_order_book_state()));
+    group.finish();
+}
+
+enum Action {
+    Place(usize, OrderRequest),
+    Cancel(usize, usize),
+}
+
+/// A mixed flow spread over `markets` markets: mostly passive orders, some
+/// cancels of earlier orders and the occasional marketable order.
+fn engine_flow(markets: usize, n: usize) -> Vec<Action> {
+    let mut flow = Flow::new(5);
+    let mut placed = vec![0; markets];
+    (0..n)
+        .map(|_| {
+            let market = flow.rng.random_range(0..markets);
+            let roll = flow.rng.random_range(0..10);
+            if roll < 2 && placed[market] > 0 {
+                let index = flow.rng.random_range(0..placed[market]);
+                return Action::Cancel(market, index);
+            }
+            let order = if roll == 9 {
+                let side = flow.side();
+                OrderRequest::new(side, flow.qty(), OrderType::Market)
+            } else {
+                flow.passive()
+            };
+            placed[market] += 1;
+            Action::Place(market, order)
+        })
+        .collect()
+}
+
+fn run_engine(engine: &mut MatchingEngine, pairs: &[TradingPair], flow: &[Action]) {
+    let mut placed: Vec<Vec<OrderId>> = vec![Vec::new(); pairs.len()];
+    for action in flow {
+        match action {
+            Action::Place(market, order) => {
+                engine.place_order(&pairs[*market], *order).unwrap();
+                placed[*market].push(order.id());
+            }
+            Action::Cancel(market, index) => {
+                let id = placed[*market][*index];
+                engine.cancel_order(&pairs[*market], id).unwrap();
+            }
+        }
+    }
+}
+
+fn engine(c: &mut Criterion) {
+    let mut group = c.benchmark_group("order-benchmark/engine");
+    group.sample_size(10);
+    group.measurement_time(Duration::new(10, 0));
+    const ACTIONS: usize = 50_000;
+
+    for markets in [1, 4, 16] {
+        let pairs: Vec<_> = (0..markets)
+            .map(|i| TradingPair::new(format!("BASE{i}"), "QUOTE".to_string()))
+            .collect();
+        let flow = engine_flow(markets, ACTIONS);
+        group.throughput(Throughput::Elements(ACTIONS as u64));
+        group.bench_with_input(BenchmarkId::new("mixed", markets), &markets, |b, _| {
+            b.iter_batched(
+                || {
+                    let mut engine = MatchingEngine::new();
+                    for pair in &pairs {
+                        engine.add_market(pair.clone()).unwrap();
+                    }
+                    engine
+                },
+                |mut engine| {
+                    run_engine(&mut engine, &pairs, &flow);
+                    engine
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Criterion reports means; a regression that only hits the slowest
+/// operations shows up here instead. Each operation is timed on its own
+/// and the percentiles printed per kind.
+fn tail_latency(_: &mut Criterion) {
+    let mut flow = Flow::new(6);
+    let mut book = book_with(&flow.passive_orders(BOOK_ORDERS));
+    let mut resting: Vec<OrderId> = book.order_loc.keys().copied().collect();
+    let mut samples: [(&str, Vec<Duration>); 3] = [
+        ("insert", Vec::new()),
+        ("cancel", Vec::new()),
+        ("match", Vec::new()),
+    ];
+
+    for _ in 0..200_000 {
+        let roll = flow.rng.random_range(0..10);
+        let (kind, elapsed) = if roll < 5 || resting.is_empty() {
+            let order = flow.passive();
+            let start = Instant::now();
+            book.add_order(order);
+            let elapsed = start.elapsed();
+            resting.push(order.id());
+            (0, elapsed)
+        } else if roll < 9 {
+            let id = resting.swap_remove(flow.rng.random_range(0..resting.len()));
+            let start = Instant::now();
+            book.delete_order(id);
+            (1, start.elapsed())
+        } else {
+            let side = flow.side();
+            let order = OrderRequest::new(side, flow.qty(), OrderType::Market);
+            let start = Instant::now();
+            book.add_order(order);
+            (2, start.elapsed())
+        };
+        samples[kind].1.push(elapsed);
+    }
+
+    println!("order-benchmark/latency");
+    for (kind, mut times) in samples {
+        times.sort();
+        let at = |q: f64| times[((times.len() - 1) as f64 * q) as usize];
+        println!(
+            "  {:<6} n={:<7} p50={:>9.2?} p90={:>9.2?} p99={:>9.2?} p99.9={:>9.2?} max={:>9.2?}",
+            kind,
+            times.len(),
+            at(0.5),
+            at(0.9),
+            at(0.99),
+            at(0.999),
+            times[times.len() - 1]
+        );
+    }
+}
+
+criterion_group!(
+    benches,
+    insert_and_cancel,
+    matching,
+    snapshots,
+    engine,
+    tail_latency
+);
+criterion_main!(benches);
@@ -0,0 +1,167 @@
+#![no_main]
+
+//! Drives an order book with arbitrary operations and checks its invariants
+//! after every step.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use orderbooklib::{OrderBook, OrderId, OrderRequest, OrderType, Side, TradeOrder};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Add {
+        bid: bool,
+        qty: u8,
+        kind: u8,
+        price: u8,
+    },
+    System {
+        bid: bool,
+        qty: u8,
+        price: u8,
+    },
+    Cancel {
+        pick: u16,
+        qty: u8,
+    },
+    Delete {
+        pick: u16,
+    },
+    Clear,
+}
+
+fn side(bid: bool) -> Side {
+    if bid { Side::Bid } else { Side::Ask }
+}
+
+/// Prices and quantities are kept small so orders meet and levels fill up.
+fn price(p: u8) -> Decimal {
+    Decimal::from(90 + p % 20)
+}
+
+fn qty(q: u8) -> Decimal {
+    Decimal::from(1 + q % 50)
+}
+
+fn order_type(kind: u8, p: u8) -> OrderType {
+    match kind % 5 {
+        0 => OrderType::Market,
+        1 => OrderType::Limit(price(p)),
+        2 => OrderType::IOC(price(p)),
+        3 => OrderType::FOK(price(p)),
+        _ => OrderType::SystemLevel(price(p)),
+    }
+}
+
+fn queue(book: &OrderBook, side: Side, price: Decimal) -> Vec<OrderId> {
+    book.get_orders_at_price(side, price)
+        .unwrap_or_default()
+        .iter()
+        .map(|o| o.id)
+        .collect()
+}
+
+fn add(book: &mut OrderBook, request: OrderRequest) {
+    let opposite = request.side.opposite();
+    let before = book.get_total_volume();
+    let queues: Vec<_> = match opposite {
+        Side::Bid => book.bids.iter_prices().collect::<Vec<_>>(),
+        Side::Ask => book.asks.iter_prices().collect::<Vec<_>>(),
+    }
+    .into_iter()
+    .map(|p| (p, queue(book, opposite, p)))
+    .collect();
+
+    let (result, executions) = book.add_order(request);
+
+    let filled: Decimal = executions.iter().map(|e| e.qty).sum();
+    assert_eq!(result.filled_qty(), filled);
+    assert_eq!(result.initial_qty(), filled + result.remaining_qty);
+    let rested = match request.order_type {
+        OrderType::Limit(_) | OrderType::SystemLevel(_) => result.remaining_qty,
+        _ => Decimal::ZERO,
+    };
+    assert_eq!(book.get_total_volume(), before - filled + rested);
+
+    // Levels are taken best first, and within a level the makers are the
+    // front of the queue as it stood.
+    let mut levels = queues.iter();
+    let mut current: Option<&(Decimal, Vec<OrderId>)> = None;
+    let mut taken = 0;
+    for execution in &executions {
+        assert!(execution.qty > Decimal::ZERO);
+        if current.is_none_or(|(p, _)| *p != execution.price) {
+            current = levels.next();
+            taken = 0;
+        }
+        let (p, makers) = current.expect("execution outside the book");
+        assert_eq!(*p, execution.price);
+        if makers.get(taken) != Some(&execution.maker_order_id) {
+            // A partly filled maker stays at the front for the next fill.
+            assert_eq!(makers.get(taken + 1), Some(&execution.maker_order_id));
+            taken += 1;
+        }
+    }
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut book = OrderBook::default();
+    let mut ids: Vec<OrderId> = Vec::new();
+    for op in ops {
+        let pick = |i: u16| (!ids.is_empty()).then(|| ids[usize::from(i) % ids.len()]);
+        match op {
+            Op::Add {
+                bid,
+                qty: q,
+                kind,
+                price: p,
+            } => {
+                let request = OrderRequest::new(side(bid), qty(q), order_type(kind, p));
+                ids.push(request.id());
+                add(&mut book, request);
+            }
+            Op::System {
+                bid,
+                qty: q,
+                price: p,
+            } => {
+                // Inserted without matching, so only where it cannot cross.
+                let (side, price) = (side(bid), price(p));
+                let crosses = match side {
+                    Side::Bid => book.best_ask().is_some_and(|ask| price >= ask),
+                    Side::Ask => book.best_bid().is_some_and(|bid| price <= bid),
+                };
+                if !crosses {
+                    let request = OrderRequest::new(side, qty(q), OrderType::SystemLevel(price));
+                    ids.push(request.id());
+                    book.add_system_order(side, price, TradeOrder::from(request));
+                }
+            }
+            Op::Cancel { pick: i, qty: q } => {
+                if let Some(id) = pick(i) {
+                    let before = book.get_order(id).map(|o| o.remaining_qty);
+                    let result = book.cancel_order(id, qty(q));
+                    assert_eq!(result.is_some(), before.is_some());
+                    if let (Some(before), Some(result)) = (before, result) {
+                        assert_eq!(result.remaining_qty, before - qty(q).min(before));
+                    }
+                }
+            }
+            Op::Delete { pick: i } => {
+                if let Some(id) = pick(i) {
+                    let resting = book.get_order(id).is_some();
+                    assert_eq!(book.delete_order(id).is_some(), resting);
+                    assert!(book.get_order(id).is_none());
+                }
+            }
+            Op::Clear => {
+                book.clear();
+                assert!(book.is_empty());
+            }
+        }
+        if let Err(e) = book.check_invariants() {
+            panic!("{}", e);
+        }
+    }
+});
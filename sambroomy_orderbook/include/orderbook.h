@@ -0,0 +1,226 @@
+/* C ABI of the sambroomy order book, implemented in src/ffi.rs. */
+
+#ifndef SAMBROOMY_ORDERBOOK_H
+#define SAMBROOMY_ORDERBOOK_H
+
+/* Regenerate with `cbindgen --config cbindgen.toml --output include/orderbook.h`. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum ObOrderStatus {
+  OB_ORDER_STATUS_OPEN = 0,
+  OB_ORDER_STATUS_FILLED = 1,
+  OB_ORDER_STATUS_PARTIALLY_FILLED = 2,
+  OB_ORDER_STATUS_CANCELLED = 3,
+} ObOrderStatus;
+
+typedef enum ObOrderType {
+  OB_ORDER_TYPE_LIMIT = 0,
+  OB_ORDER_TYPE_MARKET = 1,
+  OB_ORDER_TYPE_IOC = 2,
+  OB_ORDER_TYPE_FOK = 3,
+} ObOrderType;
+
+typedef enum ObSide {
+  OB_SIDE_BID = 0,
+  OB_SIDE_ASK = 1,
+} ObSide;
+
+typedef enum ObStatus {
+  OB_STATUS_OK = 0,
+  OB_STATUS_NULL_ARGUMENT = 1,
+  OB_STATUS_INVALID_ARGUMENT = 2,
+  OB_STATUS_UNKNOWN_MARKET = 3,
+  OB_STATUS_MARKET_EXISTS = 4,
+  /**
+   * The engine refused the request, e.g. a risk check or market state.
+   */
+  OB_STATUS_REJECTED = 5,
+  OB_STATUS_NOT_FOUND = 6,
+  /**
+   * A Rust panic was caught; the handle should be freed.
+   */
+  OB_STATUS_INTERNAL = 7,
+} ObStatus;
+
+typedef struct ObEngine ObEngine;
+
+/**
+ * The 16 bytes of an order's UUID.
+ */
+typedef struct ObOrderId {
+  uint8_t bytes[16];
+} ObOrderId;
+
+/**
+ * `mantissa / 10^scale`, with `scale` at most 28.
+ */
+typedef struct ObDecimal {
+  int64_t mantissa;
+  uint32_t scale;
+} ObDecimal;
+
+typedef struct ObExecution {
+  struct ObOrderId taker_order_id;
+  struct ObOrderId maker_order_id;
+  enum ObSide take_side;
+  struct ObDecimal price;
+  struct ObDecimal qty;
+  /**
+   * Nanoseconds since the Unix epoch.
+   */
+  uint64_t timestamp_ns;
+} ObExecution;
+
+/**
+ * Called once per execution, on the calling thread, before the call that
+ * caused it returns. `market` and `execution` are only valid during the
+ * call.
+ */
+typedef void (*ObExecutionCallback)(const char *market,
+                                    const struct ObExecution *execution,
+                                    void *user_data);
+
+typedef struct ObOrderResult {
+  struct ObOrderId id;
+  enum ObOrderStatus status;
+  struct ObDecimal filled_qty;
+  struct ObDecimal remaining_qty;
+} ObOrderResult;
+
+typedef struct ObLevel {
+  struct ObDecimal price;
+  struct ObDecimal qty;
+} ObLevel;
+
+/**
+ * Top of book; a missing side has `has_bid`/`has_ask` false and a zero
+ * level.
+ */
+typedef struct ObBbo {
+  bool has_bid;
+  struct ObLevel bid;
+  bool has_ask;
+  struct ObLevel ask;
+} ObBbo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an engine without markets. Free it with [`ob_engine_free`].
+ */
+struct ObEngine *ob_engine_new(void);
+
+/**
+ * Frees `engine` and every market in it. Null is ignored.
+ *
+ * # Safety
+ *
+ * `engine` must come from [`ob_engine_new`] and not be used afterwards.
+ */
+void ob_engine_free(struct ObEngine *engine);
+
+/**
+ * Describes the last failed call on `engine`; empty after a successful one.
+ * The string lives until the next call on `engine`.
+ *
+ * # Safety
+ *
+ * `engine` must be null or a live handle.
+ */
+const char *ob_last_error(const struct ObEngine *engine);
+
+/**
+ * Calls `callback` with `user_data` for every execution from now on; a null
+ * `callback` stops the calls.
+ *
+ * # Safety
+ *
+ * `engine` must be null or a live handle, and `callback` must be safe to
+ * call with `user_data` for as long as it is registered.
+ */
+enum ObStatus ob_set_execution_callback(struct ObEngine *engine,
+                                        ObExecutionCallback callback,
+                                        void *user_data);
+
+/**
+ * # Safety
+ *
+ * `engine` must be null or a live handle and `market` null or a
+ * NUL-terminated string.
+ */
+enum ObStatus ob_add_market(struct ObEngine *engine, const char *market);
+
+/**
+ * Removes `market` and drops its resting orders.
+ *
+ * # Safety
+ *
+ * As for [`ob_add_market`].
+ */
+enum ObStatus ob_remove_market(struct ObEngine *engine, const char *market);
+
+/**
+ * Places an order, reporting its executions to the callback and its
+ * outcome through `result`, which may be null. `side` is an [`ObSide`] and
+ * `order_type` an [`ObOrderType`]. `price` is ignored for market orders.
+ *
+ * # Safety
+ *
+ * `engine` must be null or a live handle, `market` null or a NUL-terminated
+ * string and `result` null or writable.
+ */
+enum ObStatus ob_place_order(struct ObEngine *engine,
+                             const char *market,
+                             uint32_t side,
+                             uint32_t order_type,
+                             struct ObDecimal price,
+                             struct ObDecimal qty,
+                             struct ObOrderResult *result);
+
+/**
+ * Cancels a resting order, or returns `NotFound` if it is not resting.
+ *
+ * # Safety
+ *
+ * As for [`ob_place_order`].
+ */
+enum ObStatus ob_cancel_order(struct ObEngine *engine,
+                              const char *market,
+                              struct ObOrderId order_id,
+                              struct ObOrderResult *result);
+
+/**
+ * # Safety
+ *
+ * `engine` must be null or a live handle, `market` null or a NUL-terminated
+ * string and `bbo` null or writable.
+ */
+enum ObStatus ob_get_bbo(struct ObEngine *engine, const char *market, struct ObBbo *bbo);
+
+/**
+ * Writes up to `capacity` levels of `side`, an [`ObSide`], best first, into
+ * `levels` and their number into `count`. Depth is limited to the published snapshot.
+ *
+ * # Safety
+ *
+ * `engine` must be null or a live handle, `market` null or a NUL-terminated
+ * string, `levels` valid for `capacity` writes (or null if `capacity` is 0)
+ * and `count` null or writable.
+ */
+enum ObStatus ob_get_depth(struct ObEngine *engine,
+                           const char *market,
+                           uint32_t side,
+                           struct ObLevel *levels,
+                           size_t capacity,
+                           size_t *count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SAMBROOMY_ORDERBOOK_H */
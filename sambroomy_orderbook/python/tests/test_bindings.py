@@ -0,0 +1,172 @@
+"""Tests for the Python bindings.
+
+Build and install the wheel first, e.g. with `maturin develop`, then run
+`python -m pytest python/tests` (or `python -m unittest discover python/tests`).
+"""
+
+import importlib.util
+import unittest
+import uuid
+from decimal import Decimal
+
+from orderbooklib import MatchingEngine, OrderBook, OrderRequest
+
+HAS_PANDAS = importlib.util.find_spec("pandas") is not None
+HAS_POLARS = importlib.util.find_spec("polars") is not None
+
+
+def seeded_book():
+    book = OrderBook()
+    for side, qty, price in [
+        ("ask", 5, "101"),
+        ("ask", 7, "102"),
+        ("bid", 4, "99"),
+        ("bid", 6, "98"),
+    ]:
+        book.add_order(OrderRequest(side, qty, price))
+    return book
+
+
+class OrderRequestTest(unittest.TestCase):
+    def test_fields(self):
+        order = OrderRequest("buy", "1.5", price=Decimal("100.25"))
+        self.assertEqual(order.side, "bid")
+        self.assertEqual(order.qty, Decimal("1.5"))
+        self.assertEqual(order.price, Decimal("100.25"))
+        self.assertEqual(order.order_type, "limit")
+        self.assertIsInstance(order.id, uuid.UUID)
+
+        market = OrderRequest("ask", 2, order_type="market")
+        self.assertIsNone(market.price)
+        self.assertEqual(
+            OrderRequest("bid", 1, 10, id="a").id, OrderRequest("ask", 2, 11, id="a").id
+        )
+
+    def test_invalid_requests_raise(self):
+        with self.assertRaises(ValueError):
+            OrderRequest("up", 1, 100)
+        with self.assertRaises(ValueError):
+            OrderRequest("bid", 1)
+        with self.assertRaises(ValueError):
+            OrderRequest("bid", 0, 100)
+
+
+class OrderBookTest(unittest.TestCase):
+    def test_matching(self):
+        book = seeded_book()
+        self.assertEqual(len(book), 4)
+        self.assertEqual((book.best_bid(), book.best_ask()), (Decimal(99), Decimal(101)))
+        self.assertEqual(book.spread(), Decimal(2))
+
+        order = OrderRequest("bid", 8, "102", order_type="ioc")
+        result, executions = book.add_order(order)
+        self.assertEqual(result.id, order.id)
+        self.assertEqual(result.status, "filled")
+        self.assertEqual(result.avg_fill_price, Decimal("101.375"))
+        self.assertEqual([(e.qty, e.price) for e in executions], [(5, 101), (3, 102)])
+        self.assertTrue(all(e.take_side == "bid" for e in executions))
+        self.assertEqual(executions[0].taker_order_id, order.id)
+        self.assertIsNone(executions[0].maker_fee)
+        self.assertGreater(executions[0].timestamp_ns, 0)
+        self.assertEqual(len(result.fills), 2)
+
+    def test_resting_orders(self):
+        book = seeded_book()
+        order = OrderRequest("bid", 3, "100")
+        result, executions = book.add_order(order)
+        self.assertEqual((result.status, executions), ("open", []))
+        self.assertEqual(book.get_order(order.id).remaining_qty, 3)
+
+        self.assertEqual(book.cancel_order(order.id, 1).remaining_qty, 2)
+        result, executions = book.amend_order(order.id, Decimal(101), 2)
+        self.assertEqual(result.status, "filled")
+        self.assertEqual(executions[0].price, 101)
+        self.assertIsNone(book.amend_order(order.id, 100, 1))
+        self.assertIsNone(book.delete_order(order.id))
+
+        state = book.get_order_book_state()
+        self.assertEqual(state.asks, [(102, 7), (101, 3)])
+        self.assertEqual(state.bids, [(99, 4), (98, 6)])
+        self.assertEqual(
+            state.to_dict(),
+            {
+                "side": ["ask", "ask", "bid", "bid"],
+                "price": [102.0, 101.0, 99.0, 98.0],
+                "qty": [7.0, 3.0, 4.0, 6.0],
+            },
+        )
+
+    def test_duplicate_ids_and_bad_reductions_raise(self):
+        book = seeded_book()
+        order = OrderRequest("bid", 3, "100", id="a")
+        book.add_order(order)
+        with self.assertRaises(ValueError):
+            book.add_order(order)
+        with self.assertRaises(ValueError):
+            book.add_order(OrderRequest("ask", 1, "105", id="a"))
+        with self.assertRaises(ValueError):
+            book.cancel_order(order.id, -3)
+        self.assertEqual(book.get_order(order.id).remaining_qty, 3)
+        self.assertEqual(len(book), 5)
+
+    @unittest.skipUnless(HAS_PANDAS, "pandas is not installed")
+    def test_to_pandas(self):
+        frame = seeded_book().to_pandas()
+        self.assertEqual(list(frame.columns), ["side", "price", "qty"])
+        self.assertEqual(frame["qty"].sum(), 22.0)
+
+    @unittest.skipUnless(HAS_POLARS, "polars is not installed")
+    def test_to_polars(self):
+        frame = seeded_book().to_polars()
+        self.assertEqual(frame.columns, ["side", "price", "qty"])
+        self.assertEqual(frame["price"].to_list(), [102.0, 101.0, 99.0, 98.0])
+
+
+class MatchingEngineTest(unittest.TestCase):
+    def test_markets(self):
+        engine = MatchingEngine()
+        engine.add_market("BTC_USDT")
+        engine.add_market("ETH/USDT")
+        self.assertEqual(engine.get_markets(), ["BTC_USDT", "ETH_USDT"])
+        with self.assertRaises(ValueError):
+            engine.add_market("BTC-USDT")
+        with self.assertRaises(ValueError):
+            engine.place_order("DOGE_USDT", OrderRequest("bid", 1, 1))
+        engine.remove_market("ETH_USDT")
+        self.assertEqual(engine.get_markets(), ["BTC_USDT"])
+
+    def test_orders(self):
+        engine = MatchingEngine()
+        engine.add_market("BTC_USDT")
+        ask = OrderRequest("ask", 2, "100")
+        engine.place_order("BTC_USDT", ask)
+        engine.place_order("BTC_USDT", OrderRequest("bid", 1, "98"))
+        self.assertEqual(engine.get_best_bid_ask("BTC_USDT"), (98, 100))
+        self.assertEqual(engine.get_mid_price("BTC_USDT"), 99)
+
+        result, executions = engine.place_order(
+            "BTC_USDT", OrderRequest("bid", 1, order_type="market")
+        )
+        self.assertEqual(result.status, "filled")
+        self.assertEqual(executions[0].maker_order_id, ask.id)
+        self.assertEqual(engine.get_order("BTC_USDT", ask.id).remaining_qty, 1)
+
+        result, _ = engine.amend_order("BTC_USDT", ask.id, 101, 1)
+        self.assertEqual(result.status, "open")
+        self.assertEqual(engine.cancel_order("BTC_USDT", ask.id).status, "cancelled")
+        self.assertIsNone(engine.get_order("BTC_USDT", ask.id))
+        self.assertEqual(engine.get_order_book_state("BTC_USDT").asks, [])
+        self.assertEqual(engine.get_spread("BTC_USDT"), None)
+
+    def test_duplicate_ids_raise(self):
+        engine = MatchingEngine()
+        engine.add_market("BTC_USDT")
+        order = OrderRequest("ask", 2, "100")
+        engine.place_order("BTC_USDT", order)
+        with self.assertRaises(ValueError):
+            engine.place_order("BTC_USDT", order)
+        self.assertEqual(engine.get_order_book_state("BTC_USDT").asks, [(100, 2)])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,489 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    thread::{self, JoinHandle},
+};
+
+use crossbeam_channel::{Receiver, Sender, bounded, unbounded};
+
+use crate::{
+    BookReader, BookSnapshot, OrderBook, OrderBookState, OrderId, OrderRequest, OrderResult, Price,
+    Quantity, Simulation, TradeExecution, TradingPair,
+};
+
+type Reply<T> = Sender<T>;
+
+/// Receiver for a command's reply, or why the command could not be queued.
+pub type Pending<T> = Result<Receiver<T>, String>;
+
+type Placed = (OrderResult, Vec<TradeExecution>);
+type BestPrices = (Option<Price>, Option<Price>);
+
+/// A request to a single market's thread.
+pub enum Command {
+    Place(OrderRequest, Reply<Placed>),
+    Cancel(OrderId, Reply<Option<OrderResult>>),
+    Reduce(OrderId, Quantity, Reply<Option<OrderResult>>),
+    Amend(OrderId, Price, Quantity, Reply<Option<Placed>>),
+    Order(OrderId, Reply<Option<OrderResult>>),
+    State(Reply<OrderBookState>),
+    BestBidAsk(Reply<BestPrices>),
+    Spread(Reply<Option<Price>>),
+    Volume(Reply<Quantity>),
+    Simulate(OrderRequest, Reply<Simulation>),
+    Shutdown,
+}
+
+/// Most commands applied before a snapshot is published, so a busy market
+/// still refreshes its readers.
+const MAX_BATCH: usize = 1024;
+
+/// Applies one command. Returns `None` on shutdown, otherwise whether the
+/// book may have changed. A dropped reply receiver is not an error: the
+/// caller simply did not wait for the answer.
+fn apply(book: &mut OrderBook, command: Command) -> Option<bool> {
+    let mutated = match command {
+        Command::Place(order, reply) => {
+            let _ = reply.send(book.add_order(order));
+            true
+        }
+        Command::Cancel(id, reply) => {
+            let _ = reply.send(book.delete_order(id));
+            true
+        }
+        Command::Reduce(id, qty, reply) => {
+            let _ = reply.send(book.cancel_order(id, qty));
+            true
+        }
+        Command::Amend(id, price, qty, reply) => {
+            let _ = reply.send(book.amend_order(id, price, qty));
+            true
+        }
+        Command::Order(id, reply) => {
+            let _ = reply.send(book.get_order(id).cloned().map(OrderResult::from));
+            false
+        }
+        Command::State(reply) => {
+            let _ = reply.send(book.get_order_book_state());
+            false
+        }
+        Command::BestBidAsk(reply) => {
+            let _ = reply.send(book.best_prices());
+            false
+        }
+        Command::Spread(reply) => {
+            let _ = reply.send(book.spread());
+            false
+        }
+        Command::Volume(reply) => {
+            let _ = reply.send(book.get_total_volume());
+            false
+        }
+        Command::Simulate(order, reply) => {
+            let _ = reply.send(book.simulate(&order));
+            false
+        }
+        Command::Shutdown => return None,
+    };
+    Some(mutated)
+}
+
+/// Applies commands to a book in arrival order until shutdown or until all
+/// senders are gone, publishing a snapshot after each batch that changed it.
+fn run_market(mut book: OrderBook, commands: Receiver<Command>) -> OrderBook {
+    while let Ok(first) = commands.recv() {
+        let mut dirty = false;
+        for command in std::iter::once(first).chain(commands.try_iter().take(MAX_BATCH - 1)) {
+            match apply(&mut book, command) {
+                Some(mutated) => dirty |= mutated,
+                None => {
+                    if dirty {
+                        book.publish();
+                    }
+                    return book;
+                }
+            }
+        }
+        if dirty {
+            book.publish();
+        }
+    }
+    book
+}
+
+struct Market {
+    handle: MarketHandle,
+    thread: Option<JoinHandle<OrderBook>>,
+}
+
+/// Cloneable entry point to one market of an [`ActorEngine`].
+///
+/// Holds the market's command sender, so unlike the [`EngineHandle`]
+/// methods it never touches the engine's market table. Once the market is
+/// removed every command fails, even if a market for the same pair is
+/// added again.
+#[derive(Clone)]
+pub struct MarketHandle {
+    pair: TradingPair,
+    commands: Sender<Command>,
+    reader: BookReader,
+}
+
+impl MarketHandle {
+    fn send<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Pending<T> {
+        let (reply, response) = bounded(1);
+        self.commands
+            .send(command(reply))
+            .map_err(|_| format!("Market for {} has shut down", self.pair))?;
+        Ok(response)
+    }
+
+    pub fn pair(&self) -> &TradingPair {
+        &self.pair
+    }
+
+    /// Latest published snapshot, read without going through the market's
+    /// command queue.
+    pub fn snapshot(&self) -> Arc<BookSnapshot> {
+        self.reader.load()
+    }
+
+    pub fn book_reader(&self) -> BookReader {
+        self.reader.clone()
+    }
+
+    pub fn place_order(&self, order: OrderRequest) -> Pending<Placed> {
+        self.send(|reply| Command::Place(order, reply))
+    }
+
+    pub fn cancel_order(&self, order_id: OrderId) -> Pending<Option<OrderResult>> {
+        self.send(|reply| Command::Cancel(order_id, reply))
+    }
+
+    pub fn reduce_order(&self, order_id: OrderId, qty: Quantity) -> Pending<Option<OrderResult>> {
+        self.send(|reply| Command::Reduce(order_id, qty, reply))
+    }
+
+    pub fn amend_order(
+        &self,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> Pending<Option<Placed>> {
+        self.send(|reply| Command::Amend(order_id, price, qty, reply))
+    }
+
+    pub fn get_order(&self, order_id: OrderId) -> Pending<Option<OrderResult>> {
+        self.send(|reply| Command::Order(order_id, reply))
+    }
+
+    pub fn get_order_book_state(&self) -> Pending<OrderBookState> {
+        self.send(Command::State)
+    }
+
+    pub fn get_best_bid_ask(&self) -> Pending<BestPrices> {
+        self.send(Command::BestBidAsk)
+    }
+
+    pub fn get_spread(&self) -> Pending<Option<Price>> {
+        self.send(Command::Spread)
+    }
+
+    pub fn get_volume(&self) -> Pending<Quantity> {
+        self.send(Command::Volume)
+    }
+
+    pub fn simulate_order(&self, order: OrderRequest) -> Pending<Simulation> {
+        self.send(|reply| Command::Simulate(order, reply))
+    }
+}
+
+/// Cloneable entry point to an [`ActorEngine`].
+///
+/// Every call enqueues a command on the market's thread and returns a
+/// receiver for the reply, so callers can pipeline many requests and only
+/// block when they need the answer. Commands to one market are applied in
+/// the order they were sent; different markets run in parallel.
+///
+/// Each call looks the market up in a table shared by all handles; callers
+/// sending many commands to one market should resolve it once with
+/// [`market`](Self::market) and keep the [`MarketHandle`].
+#[derive(Clone)]
+pub struct EngineHandle {
+    markets: Arc<RwLock<HashMap<TradingPair, Market>>>,
+}
+
+impl EngineHandle {
+    pub fn market(&self, pair: &TradingPair) -> Result<MarketHandle, String> {
+        self.markets
+            .read()
+            .map_err(|e| e.to_string())?
+            .get(pair)
+            .map(|market| market.handle.clone())
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    pub fn add_market(&self, pair: TradingPair) -> Result<(), String> {
+        let mut markets = self.markets.write().map_err(|e| e.to_string())?;
+        if markets.contains_key(&pair) {
+            return Err(format!("Market for {} already exists", pair));
+        }
+        let (commands, inbox) = unbounded();
+        let book = OrderBook::default();
+        let reader = book.reader();
+        let thread = thread::Builder::new()
+            .name(format!("market-{}", pair))
+            .spawn(move || run_market(book, inbox))
+            .map_err(|e| e.to_string())?;
+        let handle = MarketHandle {
+            pair: pair.clone(),
+            commands,
+            reader,
+        };
+        markets.insert(
+            pair,
+            Market {
+                handle,
+                thread: Some(thread),
+            },
+        );
+        Ok(())
+    }
+
+    /// Stops the market's thread after it drains already queued commands and
+    /// returns the final book.
+    pub fn remove_market(&self, pair: &TradingPair) -> Result<OrderBook, String> {
+        let mut market = self
+            .markets
+            .write()
+            .map_err(|e| e.to_string())?
+            .remove(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))?;
+        let _ = market.handle.commands.send(Command::Shutdown);
+        market
+            .thread
+            .take()
+            .expect("market thread joined twice")
+            .join()
+            .map_err(|_| format!("market thread for {} panicked", pair))
+    }
+
+    pub fn get_markets(&self) -> Vec<TradingPair> {
+        self.markets
+            .read()
+            .map(|markets| markets.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn market_exists(&self, pair: &TradingPair) -> bool {
+        self.markets
+            .read()
+            .is_ok_and(|markets| markets.contains_key(pair))
+    }
+
+    pub fn snapshot(&self, pair: &TradingPair) -> Result<Arc<BookSnapshot>, String> {
+        Ok(self.market(pair)?.snapshot())
+    }
+
+    pub fn book_reader(&self, pair: &TradingPair) -> Result<BookReader, String> {
+        Ok(self.market(pair)?.book_reader())
+    }
+
+    pub fn place_order(&self, pair: &TradingPair, order: OrderRequest) -> Pending<Placed> {
+        self.market(pair)?.place_order(order)
+    }
+
+    pub fn cancel_order(
+        &self,
+        pair: &TradingPair,
+        order_id: OrderId,
+    ) -> Pending<Option<OrderResult>> {
+        self.market(pair)?.cancel_order(order_id)
+    }
+
+    pub fn reduce_order(
+        &self,
+        pair: &TradingPair,
+        order_id: OrderId,
+        qty: Quantity,
+    ) -> Pending<Option<OrderResult>> {
+        self.market(pair)?.reduce_order(order_id, qty)
+    }
+
+    pub fn amend_order(
+        &self,
+        pair: &TradingPair,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> Pending<Option<Placed>> {
+        self.market(pair)?.amend_order(order_id, price, qty)
+    }
+
+    pub fn get_order(&self, pair: &TradingPair, order_id: OrderId) -> Pending<Option<OrderResult>> {
+        self.market(pair)?.get_order(order_id)
+    }
+
+    pub fn get_order_book_state(&self, pair: &TradingPair) -> Pending<OrderBookState> {
+        self.market(pair)?.get_order_book_state()
+    }
+
+    pub fn get_best_bid_ask(&self, pair: &TradingPair) -> Pending<BestPrices> {
+        self.market(pair)?.get_best_bid_ask()
+    }
+
+    pub fn get_spread(&self, pair: &TradingPair) -> Pending<Option<Price>> {
+        self.market(pair)?.get_spread()
+    }
+
+    pub fn get_volume(&self, pair: &TradingPair) -> Pending<Quantity> {
+        self.market(pair)?.get_volume()
+    }
+
+    pub fn simulate_order(&self, pair: &TradingPair, order: OrderRequest) -> Pending<Simulation> {
+        self.market(pair)?.simulate_order(order)
+    }
+}
+
+/// [`crate::MatchingEngine`] variant that runs every market on its own
+/// thread. Dropping it shuts down and joins all market threads.
+///
+/// Its markets are bare [`OrderBook`]s: orders go straight to matching,
+/// with no risk checks, trading states or circuit breakers, no ledger
+/// reservations or settlement, and no fees. Put those in front of the
+/// handle if the venue needs them.
+pub struct ActorEngine {
+    handle: EngineHandle,
+}
+
+impl ActorEngine {
+    pub fn new() -> Self {
+        Self {
+            handle: EngineHandle {
+                markets: Arc::new(RwLock::new(HashMap::new())),
+            },
+        }
+    }
+
+    pub fn handle(&self) -> EngineHandle {
+        self.handle.clone()
+    }
+
+    /// Shuts down every market and returns the final books.
+    pub fn shutdown(self) -> HashMap<TradingPair, OrderBook> {
+        self.handle
+            .get_markets()
+            .into_iter()
+            .filter_map(|pair| {
+                let book = self.handle.remove_market(&pair).ok()?;
+                Some((pair, book))
+            })
+            .collect()
+    }
+}
+
+impl Default for ActorEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ActorEngine {
+    fn drop(&mut self) {
+        for pair in self.handle.get_markets() {
+            let _ = self.handle.remove_market(&pair);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MatchingEngine, OrderStatus, OrderType, Side};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use rust_decimal::Decimal;
+
+    type Trade = (Price, Quantity, OrderId, OrderId, Side);
+    type Outcome = (
+        OrderStatus,
+        Quantity,
+        Vec<(Price, Quantity, OrderId)>,
+        Vec<Trade>,
+    );
+
+    /// What both engines must agree on; timestamps differ by construction.
+    fn outcome((result, executions): &Placed) -> Outcome {
+        let fills = result
+            .fills()
+            .iter()
+            .map(|f| (f.price, f.qty, f.order_id))
+            .collect();
+        let trades = executions
+            .iter()
+            .map(|e| {
+                (
+                    e.price,
+                    e.qty,
+                    e.maker_order_id,
+                    e.taker_order_id,
+                    e.take_side,
+                )
+            })
+            .collect();
+        (result.status, result.remaining_qty, fills, trades)
+    }
+
+    #[test]
+    fn pipelined_commands_match_the_matching_engine() {
+        let engine = ActorEngine::new();
+        let handle = engine.handle();
+        let pairs: Vec<_> = (0..4)
+            .map(|i| TradingPair::new(format!("BASE{i}"), "QUOTE".to_string()))
+            .collect();
+        let mut expected = MatchingEngine::new();
+        for pair in &pairs {
+            handle.add_market(pair.clone()).unwrap();
+            expected.add_market(pair.clone()).unwrap();
+        }
+        let markets: Vec<_> = pairs.iter().map(|p| handle.market(p).unwrap()).collect();
+
+        let mut rng = StdRng::seed_from_u64(33);
+        let mut replies = Vec::new();
+        for _ in 0..2000 {
+            let market = &markets[rng.random_range(0..markets.len())];
+            let side = if rng.random_bool(0.5) {
+                Side::Bid
+            } else {
+                Side::Ask
+            };
+            let price = Decimal::from(rng.random_range(95..=105));
+            let order = OrderRequest::new(side, rng.random_range(1..=10), OrderType::Limit(price));
+            let want = expected.place_order(market.pair(), order).unwrap();
+            replies.push((market.place_order(order).unwrap(), want));
+        }
+        for (reply, want) in replies {
+            assert_eq!(outcome(&reply.recv().unwrap()), outcome(&want));
+        }
+
+        let books = engine.shutdown();
+        for (pair, book) in books {
+            assert_eq!(
+                book.get_order_book_state(),
+                expected.get_order_book_state(&pair).unwrap()
+            );
+        }
+        assert!(
+            handle
+                .place_order(
+                    &pairs[0],
+                    OrderRequest::new(Side::Bid, 1, OrderType::Market)
+                )
+                .is_err()
+        );
+        assert!(
+            markets[0]
+                .place_order(OrderRequest::new(Side::Bid, 1, OrderType::Market))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,812 @@
+//! Binance spot REST facade over a [`MatchingEngine`].
+//!
+//! Implements the subset of `/api/v3` that trading bots rely on, with
+//! Binance's parameter names, `BASEQUOTE` symbols, `{"code", "msg"}` errors
+//! and HMAC-SHA256 signed endpoints, so an unmodified Binance client can be
+//! pointed at a local engine:
+//!
+//! - `GET /api/v3/ping`, `GET /api/v3/time`, `GET /api/v3/exchangeInfo`
+//! - `GET /api/v3/depth`, `GET /api/v3/ticker/bookTicker`
+//! - `POST|GET|DELETE /api/v3/order` and `GET /api/v3/openOrders` (signed)
+//!
+//! Binance order ids are sequential integers mapped onto engine ids. Only
+//! orders entered through the facade are tracked, and each API key only
+//! sees and cancels its own. An API key tied to a ledger account with
+//! [`BinanceFacade::set_account`] trades that account's balances, which an
+//! engine with the ledger enabled requires.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Json, Router,
+    extract::{RawQuery, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use tokio::net::TcpListener;
+
+use crate::{
+    AccountId, MatchingEngine, OrderId, OrderRequest, OrderResult, OrderStatus, OrderType, Price,
+    Quantity, Side, TradeExecution, TradingPair,
+};
+
+const DEFAULT_RECV_WINDOW: u64 = 5_000;
+const MAX_RECV_WINDOW: u64 = 60_000;
+const DEFAULT_DEPTH_LIMIT: usize = 100;
+const MAX_DEPTH_LIMIT: usize = 5_000;
+
+/// Error in Binance's `{"code": -1121, "msg": "Invalid symbol."}` shape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinanceError {
+    pub code: i32,
+    pub msg: String,
+}
+
+impl BinanceError {
+    pub fn new(code: i32, msg: impl Into<String>) -> Self {
+        Self {
+            code,
+            msg: msg.into(),
+        }
+    }
+
+    fn mandatory(param: &str) -> Self {
+        Self::new(
+            -1102,
+            format!(
+                "Mandatory parameter '{}' was not sent, was empty/null, or malformed.",
+                param
+            ),
+        )
+    }
+
+    fn illegal_chars(param: &str) -> Self {
+        Self::new(
+            -1100,
+            format!("Illegal characters found in parameter '{}'.", param),
+        )
+    }
+
+    fn bad_symbol() -> Self {
+        Self::new(-1121, "Invalid symbol.")
+    }
+
+    fn no_such_order() -> Self {
+        Self::new(-2013, "Order does not exist.")
+    }
+
+    fn status(&self) -> StatusCode {
+        match self.code {
+            -2014 | -2015 => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for BinanceError {
+    fn into_response(self) -> Response {
+        (
+            self.status(),
+            Json(json!({ "code": self.code, "msg": self.msg })),
+        )
+            .into_response()
+    }
+}
+
+type BinanceResult = Result<Json<Value>, BinanceError>;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn dec(d: Decimal) -> String {
+    format!("{:.8}", d)
+}
+
+fn symbol(pair: &TradingPair) -> String {
+    format!("{}{}", pair.base(), pair.quote()).to_uppercase()
+}
+
+/// Decoded request parameters from the query string and form body.
+struct Params(Vec<(String, String)>);
+
+impl Params {
+    fn parse(query: &str, body: &str) -> Self {
+        Params(
+            form_urlencoded::parse(query.as_bytes())
+                .chain(form_urlencoded::parse(body.as_bytes()))
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect(),
+        )
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, v)| k == name && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, BinanceError> {
+        self.get(name).ok_or_else(|| BinanceError::mandatory(name))
+    }
+
+    fn decimal(&self, name: &str) -> Result<Option<Decimal>, BinanceError> {
+        self.get(name)
+            .map(|v| v.parse().map_err(|_| BinanceError::illegal_chars(name)))
+            .transpose()
+    }
+
+    fn u64(&self, name: &str) -> Result<Option<u64>, BinanceError> {
+        self.get(name)
+            .map(|v| v.parse().map_err(|_| BinanceError::illegal_chars(name)))
+            .transpose()
+    }
+}
+
+/// Splits the raw `signature=` pair off `raw`, keeping the rest byte for
+/// byte since that is what the client signed.
+fn strip_signature(raw: &str) -> (String, Option<String>) {
+    let mut signature = None;
+    let rest: Vec<&str> = raw
+        .split('&')
+        .filter(|pair| match pair.strip_prefix("signature=") {
+            Some(value) => {
+                signature = form_urlencoded::parse(format!("s={}", value).as_bytes())
+                    .next()
+                    .map(|(_, v)| v.into_owned());
+                false
+            }
+            None => true,
+        })
+        .collect();
+    (rest.join("&"), signature)
+}
+
+#[derive(Debug, Clone)]
+struct BinanceOrder {
+    /// Key that placed the order.
+    api_key: String,
+    symbol: String,
+    pair: TradingPair,
+    order_id: u64,
+    engine_id: OrderId,
+    client_order_id: String,
+    side: &'static str,
+    order_type: &'static str,
+    time_in_force: &'static str,
+    price: Price,
+    orig_qty: Quantity,
+    executed_qty: Quantity,
+    cumulative_quote_qty: Decimal,
+    status: &'static str,
+    time: u64,
+    update_time: u64,
+}
+
+impl BinanceOrder {
+    fn is_open(&self) -> bool {
+        matches!(self.status, "NEW" | "PARTIALLY_FILLED")
+    }
+
+    fn fill(&mut self, execution: &TradeExecution, at: u64) {
+        self.executed_qty += execution.qty;
+        self.cumulative_quote_qty += execution.qty * execution.price;
+        self.status = if self.executed_qty >= self.orig_qty {
+            "FILLED"
+        } else {
+            "PARTIALLY_FILLED"
+        };
+        self.update_time = at;
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "symbol": self.symbol,
+            "orderId": self.order_id,
+            "orderListId": -1,
+            "clientOrderId": self.client_order_id,
+            "price": dec(self.price),
+            "origQty": dec(self.orig_qty),
+            "executedQty": dec(self.executed_qty),
+            "cummulativeQuoteQty": dec(self.cumulative_quote_qty),
+            "status": self.status,
+            "timeInForce": self.time_in_force,
+            "type": self.order_type,
+            "side": self.side,
+            "stopPrice": dec(Decimal::ZERO),
+            "icebergQty": dec(Decimal::ZERO),
+            "time": self.time,
+            "updateTime": self.update_time,
+            "isWorking": true,
+            "workingTime": self.time,
+            "origQuoteOrderQty": dec(Decimal::ZERO),
+            "selfTradePreventionMode": "NONE",
+        })
+    }
+}
+
+/// Binance status of a just-placed order. Whatever did not fill or rest
+/// immediately expired.
+fn taker_status(result: &OrderResult, resting: bool) -> &'static str {
+    match result.status {
+        OrderStatus::Filled => "FILLED",
+        _ if resting && result.filled_qty() > Decimal::ZERO => "PARTIALLY_FILLED",
+        _ if resting => "NEW",
+        _ => "EXPIRED",
+    }
+}
+
+struct Core {
+    engine: MatchingEngine,
+    orders: HashMap<u64, BinanceOrder>,
+    by_engine_id: HashMap<OrderId, u64>,
+    /// Keyed by API key, symbol and client order id.
+    by_client_id: HashMap<(String, String, String), u64>,
+    next_order_id: u64,
+    next_trade_id: u64,
+}
+
+impl Core {
+    fn pair(&self, symbol: &str) -> Result<TradingPair, BinanceError> {
+        let symbol = symbol.to_uppercase();
+        self.engine
+            .get_markets()
+            .into_iter()
+            .find(|pair| self::symbol(pair) == symbol)
+            .ok_or_else(BinanceError::bad_symbol)
+    }
+
+    /// The order `api_key` refers to in `params`. Other keys' orders do not
+    /// exist as far as it is concerned.
+    fn find_order(
+        &self,
+        api_key: &str,
+        symbol: &str,
+        params: &Params,
+    ) -> Result<u64, BinanceError> {
+        let id = if let Some(id) = params.u64("orderId")? {
+            id
+        } else if let Some(client_id) = params.get("origClientOrderId") {
+            *self
+                .by_client_id
+                .get(&(
+                    api_key.to_string(),
+                    symbol.to_string(),
+                    client_id.to_string(),
+                ))
+                .ok_or_else(BinanceError::no_such_order)?
+        } else {
+            return Err(BinanceError::new(
+                -1102,
+                "Param 'origClientOrderId' or 'orderId' must be sent, but both were empty/null!",
+            ));
+        };
+        match self.orders.get(&id) {
+            Some(order) if order.api_key == api_key && order.symbol == symbol => Ok(id),
+            _ => Err(BinanceError::no_such_order()),
+        }
+    }
+}
+
+/// Binance-compatible HTTP server. Cheap to clone.
+#[derive(Clone)]
+pub struct BinanceFacade {
+    core: Arc<Mutex<Core>>,
+    keys: Arc<RwLock<HashMap<String, String>>>,
+    accounts: Arc<RwLock<HashMap<String, AccountId>>>,
+}
+
+impl BinanceFacade {
+    pub fn new(engine: MatchingEngine) -> Self {
+        Self {
+            core: Arc::new(Mutex::new(Core {
+                engine,
+                orders: HashMap::new(),
+                by_engine_id: HashMap::new(),
+                by_client_id: HashMap::new(),
+                next_order_id: 1,
+                next_trade_id: 1,
+            })),
+            keys: Arc::default(),
+            accounts: Arc::default(),
+        }
+    }
+
+    /// Accepts signed requests carrying `api_key` in `X-MBX-APIKEY` and
+    /// signed with `secret`.
+    pub fn add_api_key(&self, api_key: impl Into<String>, secret: impl Into<String>) {
+        self.keys
+            .write()
+            .unwrap()
+            .insert(api_key.into(), secret.into());
+    }
+
+    /// Places `api_key`'s orders for `account` with
+    /// [`MatchingEngine::place_order_for`], so they reserve and settle
+    /// against its ledger balances and pay its fee tier.
+    pub fn set_account(&self, api_key: impl Into<String>, account: AccountId) {
+        self.accounts
+            .write()
+            .unwrap()
+            .insert(api_key.into(), account);
+    }
+
+    pub fn with_engine<T>(&self, f: impl FnOnce(&mut MatchingEngine) -> T) -> T {
+        f(&mut self.core.lock().unwrap().engine)
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/api/v3/ping", get(|| async { Json(json!({})) }))
+            .route(
+                "/api/v3/time",
+                get(|| async { Json(json!({ "serverTime": now_ms() })) }),
+            )
+            .route("/api/v3/exchangeInfo", get(exchange_info))
+            .route("/api/v3/depth", get(depth))
+            .route("/api/v3/ticker/bookTicker", get(book_ticker))
+            .route(
+                "/api/v3/order",
+                get(query_order).post(new_order).delete(cancel_order),
+            )
+            .route("/api/v3/openOrders", get(open_orders))
+            .with_state(self.clone())
+    }
+
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+
+    /// Checks API key, signature and timestamp the way Binance does for
+    /// `TRADE` and `USER_DATA` endpoints, then returns the API key and the
+    /// parameters.
+    fn signed(
+        &self,
+        headers: &HeaderMap,
+        query: &str,
+        body: &str,
+    ) -> Result<(String, Params), BinanceError> {
+        let api_key = headers
+            .get("X-MBX-APIKEY")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| BinanceError::new(-2014, "API-key format invalid."))?;
+        let secret = self
+            .keys
+            .read()
+            .unwrap()
+            .get(api_key)
+            .cloned()
+            .ok_or_else(|| {
+                BinanceError::new(-2015, "Invalid API-key, IP, or permissions for action.")
+            })?;
+
+        let (query_payload, query_signature) = strip_signature(query);
+        let (body_payload, body_signature) = strip_signature(body);
+        let signature = query_signature
+            .or(body_signature)
+            .ok_or_else(|| BinanceError::mandatory("signature"))?;
+        let invalid = || BinanceError::new(-1022, "Signature for this request is not valid.");
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("any key length");
+        mac.update(query_payload.as_bytes());
+        mac.update(body_payload.as_bytes());
+        mac.verify_slice(&hex::decode(signature).map_err(|_| invalid())?)
+            .map_err(|_| invalid())?;
+
+        let params = Params::parse(query, body);
+        let timestamp = params
+            .u64("timestamp")?
+            .ok_or_else(|| BinanceError::mandatory("timestamp"))?;
+        let recv_window = params.u64("recvWindow")?.unwrap_or(DEFAULT_RECV_WINDOW);
+        if recv_window > MAX_RECV_WINDOW {
+            return Err(BinanceError::new(
+                -1131,
+                "recvWindow must be less than 60000",
+            ));
+        }
+        let now = now_ms();
+        if timestamp > now + 1_000 || now.saturating_sub(timestamp) > recv_window {
+            return Err(BinanceError::new(
+                -1021,
+                "Timestamp for this request is outside of the recvWindow.",
+            ));
+        }
+        Ok((api_key.to_string(), params))
+    }
+}
+
+fn symbol_info(pair: &TradingPair) -> Value {
+    json!({
+        "symbol": symbol(pair),
+        "status": "TRADING",
+        "baseAsset": pair.base(),
+        "baseAssetPrecision": 8,
+        "quoteAsset": pair.quote(),
+        "quotePrecision": 8,
+        "quoteAssetPrecision": 8,
+        "orderTypes": ["LIMIT", "LIMIT_MAKER", "MARKET"],
+        "icebergAllowed": false,
+        "ocoAllowed": false,
+        "otoAllowed": false,
+        "quoteOrderQtyMarketAllowed": false,
+        "allowTrailingStop": false,
+        "cancelReplaceAllowed": false,
+        "isSpotTradingAllowed": true,
+        "isMarginTradingAllowed": false,
+        "filters": [],
+        "permissions": [],
+        "permissionSets": [["SPOT"]],
+        "defaultSelfTradePreventionMode": "NONE",
+        "allowedSelfTradePreventionModes": ["NONE"],
+    })
+}
+
+async fn exchange_info(
+    State(facade): State<BinanceFacade>,
+    RawQuery(query): RawQuery,
+) -> BinanceResult {
+    let params = Params::parse(query.as_deref().unwrap_or_default(), "");
+    let core = facade.core.lock().unwrap();
+    let mut pairs = match params.get("symbol") {
+        Some(symbol) => vec![core.pair(symbol)?],
+        None => core.engine.get_markets(),
+    };
+    pairs.sort_by_key(symbol);
+    Ok(Json(json!({
+        "timezone": "UTC",
+        "serverTime": now_ms(),
+        "rateLimits": [],
+        "exchangeFilters": [],
+        "symbols": pairs.iter().map(symbol_info).collect::<Vec<_>>(),
+    })))
+}
+
+async fn depth(State(facade): State<BinanceFacade>, RawQuery(query): RawQuery) -> BinanceResult {
+    let params = Params::parse(query.as_deref().unwrap_or_default(), "");
+    let core = facade.core.lock().unwrap();
+    let pair = core.pair(params.required("symbol")?)?;
+    let limit = params
+        .u64("limit")?
+        .map_or(DEFAULT_DEPTH_LIMIT, |l| l as usize)
+        .min(MAX_DEPTH_LIMIT);
+    let state = core
+        .engine
+        .get_order_book_state(&pair)
+        .map_err(|_| BinanceError::bad_symbol())?;
+    let version = core
+        .engine
+        .reader()
+        .snapshot(&pair)
+        .map_err(|_| BinanceError::bad_symbol())?
+        .version;
+    let levels = |levels: &[(Price, Quantity)]| {
+        levels
+            .iter()
+            .take(limit)
+            .map(|(p, q)| [dec(*p), dec(*q)])
+            .collect::<Vec<_>>()
+    };
+    Ok(Json(json!({
+        "lastUpdateId": version,
+        "bids": levels(&state.bids),
+        "asks": levels(&state.asks),
+    })))
+}
+
+async fn book_ticker(
+    State(facade): State<BinanceFacade>,
+    RawQuery(query): RawQuery,
+) -> BinanceResult {
+    let params = Params::parse(query.as_deref().unwrap_or_default(), "");
+    let core = facade.core.lock().unwrap();
+    let reader = core.engine.reader();
+    let ticker = |pair: &TradingPair| -> Result<Value, BinanceError> {
+        let snapshot = reader
+            .snapshot(pair)
+            .map_err(|_| BinanceError::bad_symbol())?;
+        let (bid_price, bid_qty) = snapshot.best_bid().unwrap_or_default();
+        let (ask_price, ask_qty) = snapshot.best_ask().unwrap_or_default();
+        Ok(json!({
+            "symbol": symbol(pair),
+            "bidPrice": dec(bid_price),
+            "bidQty": dec(bid_qty),
+            "askPrice": dec(ask_price),
+            "askQty": dec(ask_qty),
+        }))
+    };
+
+    if let Some(symbol) = params.get("symbol") {
+        return Ok(Json(ticker(&core.pair(symbol)?)?));
+    }
+    let mut pairs = match params.get("symbols") {
+        Some(list) => serde_json::from_str::<Vec<String>>(list)
+            .map_err(|_| BinanceError::illegal_chars("symbols"))?
+            .iter()
+            .map(|s| core.pair(s))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => core.engine.get_markets(),
+    };
+    pairs.sort_by_key(symbol);
+    let tickers = pairs.iter().map(ticker).collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(Value::Array(tickers)))
+}
+
+async fn new_order(
+    State(facade): State<BinanceFacade>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+    body: String,
+) -> BinanceResult {
+    let (api_key, params) = facade.signed(&headers, query.as_deref().unwrap_or_default(), &body)?;
+    let mut core = facade.core.lock().unwrap();
+    let pair = core.pair(params.required("symbol")?)?;
+    let symbol = symbol(&pair);
+
+    let (side, side_name) = match params.required("side")? {
+        "BUY" => (Side::Bid, "BUY"),
+        "SELL" => (Side::Ask, "SELL"),
+        _ => return Err(BinanceError::new(-1117, "Invalid side.")),
+    };
+    let qty = params
+        .decimal("quantity")?
+        .ok_or_else(|| BinanceError::mandatory("quantity"))?;
+    if qty <= Decimal::ZERO {
+        return Err(BinanceError::new(-1013, "Invalid quantity."));
+    }
+    let limit_price = || -> Result<Price, BinanceError> {
+        let price = params
+            .decimal("price")?
+            .ok_or_else(|| BinanceError::mandatory("price"))?;
+        if price <= Decimal::ZERO {
+            return Err(BinanceError::new(-1013, "Invalid price."));
+        }
+        Ok(price)
+    };
+
+    let (order_type, type_name, tif) = match params.required("type")? {
+        "MARKET" => (OrderType::Market, "MARKET", "GTC"),
+        "LIMIT" => {
+            let price = limit_price()?;
+            match params.required("timeInForce")? {
+                "GTC" => (OrderType::Limit(price), "LIMIT", "GTC"),
+                "IOC" => (OrderType::IOC(price), "LIMIT", "IOC"),
+                "FOK" => (OrderType::FOK(price), "LIMIT", "FOK"),
+                _ => return Err(BinanceError::new(-1115, "Invalid timeInForce.")),
+            }
+        }
+        "LIMIT_MAKER" => {
+            let price = limit_price()?;
+            let (bid, ask) = core
+                .engine
+                .get_best_bid_ask(&pair)
+                .map_err(|_| BinanceError::bad_symbol())?;
+            let crosses = match side {
+                Side::Bid => ask.is_some_and(|ask| price >= ask),
+                Side::Ask => bid.is_some_and(|bid| price <= bid),
+            };
+            if crosses {
+                return Err(BinanceError::new(
+                    -2010,
+                    "Order would immediately match and take.",
+                ));
+            }
+            (OrderType::Limit(price), "LIMIT_MAKER", "GTC")
+        }
+        _ => return Err(BinanceError::new(-1116, "Invalid orderType.")),
+    };
+
+    let order_id = core.next_order_id;
+    let client_order_id = params
+        .get("newClientOrderId")
+        .map_or_else(|| format!("local-{}", order_id), str::to_string);
+    let client_key = (api_key.clone(), symbol.clone(), client_order_id.clone());
+    if let Some(existing) = core.by_client_id.get(&client_key)
+        && core.orders[existing].is_open()
+    {
+        return Err(BinanceError::new(-2010, "Duplicate order sent."));
+    }
+
+    let order = OrderRequest::new(side, qty, order_type);
+    let account = facade.accounts.read().unwrap().get(&api_key).copied();
+    let placed = match account {
+        Some(account) => core.engine.place_order_for(account, &pair, order),
+        None => core.engine.place_order(&pair, order),
+    };
+    let (result, executions) = placed.map_err(|e| BinanceError::new(-2010, e.to_string()))?;
+    core.next_order_id += 1;
+    let now = now_ms();
+
+    let mut fills = Vec::with_capacity(executions.len());
+    for execution in &executions {
+        if let Some(maker) = core.by_engine_id.get(&execution.maker_order_id).copied()
+            && let Some(order) = core.orders.get_mut(&maker)
+        {
+            order.fill(execution, now);
+        }
+        let (commission, commission_asset) = match &execution.taker_fee {
+            Some(fee) => (fee.amount, fee.asset.as_str()),
+            None => (Decimal::ZERO, pair.quote()),
+        };
+        fills.push(json!({
+            "price": dec(execution.price),
+            "qty": dec(execution.qty),
+            "commission": dec(commission),
+            "commissionAsset": commission_asset,
+            "tradeId": core.next_trade_id,
+        }));
+        core.next_trade_id += 1;
+    }
+
+    let resting = core
+        .engine
+        .get_order(&pair, result.get_id())
+        .ok()
+        .flatten()
+        .is_some();
+    let executed_qty = result.filled_qty();
+    let order = BinanceOrder {
+        api_key,
+        symbol: symbol.clone(),
+        pair,
+        order_id,
+        engine_id: result.get_id(),
+        client_order_id: client_order_id.clone(),
+        side: side_name,
+        order_type: type_name,
+        time_in_force: tif,
+        price: order_type.price().unwrap_or_default(),
+        orig_qty: qty,
+        executed_qty,
+        cumulative_quote_qty: executions.iter().map(|e| e.qty * e.price).sum(),
+        status: taker_status(&result, resting),
+        time: now,
+        update_time: now,
+    };
+    core.by_engine_id.insert(order.engine_id, order_id);
+    core.by_client_id.insert(client_key, order_id);
+
+    let ack = json!({
+        "symbol": symbol,
+        "orderId": order_id,
+        "orderListId": -1,
+        "clientOrderId": client_order_id,
+        "transactTime": now,
+    });
+    let response = match params.get("newOrderRespType").unwrap_or("FULL") {
+        "ACK" => ack,
+        resp_type => {
+            let mut response = order.to_json();
+            let object = response.as_object_mut().expect("order json is an object");
+            for key in ["stopPrice", "icebergQty", "time", "updateTime", "isWorking"] {
+                object.remove(key);
+            }
+            object.insert("transactTime".to_string(), now.into());
+            if resp_type == "FULL" {
+                object.insert("fills".to_string(), fills.into());
+            }
+            response
+        }
+    };
+    core.orders.insert(order_id, order);
+    Ok(Json(response))
+}
+
+async fn query_order(
+    State(facade): State<BinanceFacade>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> BinanceResult {
+    let (api_key, params) = facade.signed(&headers, query.as_deref().unwrap_or_default(), "")?;
+    let core = facade.core.lock().unwrap();
+    let symbol = symbol(&core.pair(params.required("symbol")?)?);
+    let id = core.find_order(&api_key, &symbol, &params)?;
+    Ok(Json(core.orders[&id].to_json()))
+}
+
+async fn cancel_order(
+    State(facade): State<BinanceFacade>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+    body: String,
+) -> BinanceResult {
+    let (api_key, params) = facade.signed(&headers, query.as_deref().unwrap_or_default(), &body)?;
+    let mut core = facade.core.lock().unwrap();
+    let symbol = symbol(&core.pair(params.required("symbol")?)?);
+    let unknown = || BinanceError::new(-2011, "Unknown order sent.");
+    let id = core
+        .find_order(&api_key, &symbol, &params)
+        .map_err(|_| unknown())?;
+    let order = core.orders[&id].clone();
+    if !order.is_open() {
+        return Err(unknown());
+    }
+    core.engine
+        .cancel_order(&order.pair, order.engine_id)
+        .map_err(|e| BinanceError::new(-2011, e.to_string()))?
+        .ok_or_else(unknown)?;
+
+    let now = now_ms();
+    let order = core.orders.get_mut(&id).expect("order found above");
+    order.status = "CANCELED";
+    order.update_time = now;
+    Ok(Json(json!({
+        "symbol": order.symbol,
+        "origClientOrderId": order.client_order_id,
+        "orderId": order.order_id,
+        "orderListId": -1,
+        "clientOrderId": params
+            .get("newClientOrderId")
+            .map_or_else(|| format!("cancel-{}", order.order_id), str::to_string),
+        "transactTime": now,
+        "price": dec(order.price),
+        "origQty": dec(order.orig_qty),
+        "executedQty": dec(order.executed_qty),
+        "cummulativeQuoteQty": dec(order.cumulative_quote_qty),
+        "status": order.status,
+        "timeInForce": order.time_in_force,
+        "type": order.order_type,
+        "side": order.side,
+        "selfTradePreventionMode": "NONE",
+    })))
+}
+
+async fn open_orders(
+    State(facade): State<BinanceFacade>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> BinanceResult {
+    let (api_key, params) = facade.signed(&headers, query.as_deref().unwrap_or_default(), "")?;
+    let core = facade.core.lock().unwrap();
+    let symbol = params
+        .get("symbol")
+        .map(|s| core.pair(s).map(|p| symbol(&p)))
+        .transpose()?;
+    let mut orders: Vec<_> = core
+        .orders
+        .values()
+        .filter(|o| {
+            o.is_open() && o.api_key == api_key && symbol.as_ref().is_none_or(|s| *s == o.symbol)
+        })
+        .collect();
+    orders.sort_by_key(|o| o.order_id);
+    Ok(Json(Value::Array(
+        orders.into_iter().map(BinanceOrder::to_json).collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(payload: &str, secret: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn signs_like_binance() {
+        // Example from Binance's "SIGNED Endpoint Examples".
+        let payload = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        assert_eq!(
+            sign(payload, secret),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+        let (rest, signature) = strip_signature(&format!("{payload}&signature=abc"));
+        assert_eq!(rest, payload);
+        assert_eq!(signature.as_deref(), Some("abc"));
+    }
+}
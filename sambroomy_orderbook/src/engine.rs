@@ -1,10 +1,25 @@
+use crossbeam_channel::Receiver;
+use dashmap::DashMap;
+use polars::prelude::DataFrame;
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
 use crate::{
-    OrderBook, OrderBookState, OrderRequest, OrderResult, Price, Quantity, Side, TradeExecution,
+    AccountId, BookChange, BookReader, BookSnapshot, CircuitBreaker, FeeSchedule, Fees, Fill,
+    Indicative, Ledger, MarkSource, OrderBook, OrderBookState, OrderId, OrderRequest, OrderResult,
+    OrderType, PnlMethod, PositionKeeper, Price, Quantity, RiskChain, RiskContext, RiskRejection,
+    Side, Simulation, TradeCost, TradeExecution, TradeTape, TradingState,
+    trading_state::MarketControl,
 };
 
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    ops::Bound,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct TradingPair {
@@ -16,6 +31,14 @@ impl TradingPair {
     pub fn new(base: String, quote: String) -> TradingPair {
         TradingPair { base, quote }
     }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
 }
 
 impl Display for TradingPair {
@@ -24,14 +47,577 @@ impl Display for TradingPair {
     }
 }
 
+impl FromStr for TradingPair {
+    type Err = String;
+
+    /// Parses `BASE_QUOTE`, `BASE/QUOTE` or `BASE-QUOTE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(['_', '/', '-']) {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => {
+                Ok(TradingPair::new(base.to_string(), quote.to_string()))
+            }
+            _ => Err(format!("invalid trading pair '{}'", s)),
+        }
+    }
+}
+
+/// Which resting orders [`MatchingEngine::open_orders`] lists; `None`
+/// fields match every order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderFilter {
+    pub owner: Option<AccountId>,
+    pub side: Option<Side>,
+    pub min_price: Option<Price>,
+    pub max_price: Option<Price>,
+}
+
+/// Read-only view of every market's published [`BookSnapshot`].
+///
+/// Cloneable and `Send + Sync`, so TUI, API and risk threads can hold one
+/// while the engine keeps matching. Lookups only touch the snapshot map,
+/// never the books themselves.
+#[derive(Debug, Clone, Default)]
+pub struct EngineReader {
+    books: Arc<DashMap<TradingPair, BookReader>>,
+}
+
+impl EngineReader {
+    pub fn get_markets(&self) -> Vec<TradingPair> {
+        self.books.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    pub fn market_exists(&self, pair: &TradingPair) -> bool {
+        self.books.contains_key(pair)
+    }
+
+    pub fn book_reader(&self, pair: &TradingPair) -> Result<BookReader, String> {
+        self.books
+            .get(pair)
+            .map(|reader| reader.clone())
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    pub fn snapshot(&self, pair: &TradingPair) -> Result<Arc<BookSnapshot>, String> {
+        self.books
+            .get(pair)
+            .map(|reader| reader.load())
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    pub fn get_best_bid_ask(
+        &self,
+        pair: &TradingPair,
+    ) -> Result<(Option<Price>, Option<Price>), String> {
+        Ok(self.snapshot(pair)?.best_prices())
+    }
+
+    pub fn get_spread(&self, pair: &TradingPair) -> Result<Option<Price>, String> {
+        Ok(self.snapshot(pair)?.spread())
+    }
+
+    /// Up to `levels` levels per side, limited by the book's snapshot depth.
+    pub fn get_depth(&self, pair: &TradingPair, levels: usize) -> Result<OrderBookState, String> {
+        let snapshot = self.snapshot(pair)?;
+        Ok(OrderBookState {
+            asks: snapshot.asks.iter().take(levels).copied().collect(),
+            bids: snapshot.bids.iter().take(levels).copied().collect(),
+        })
+    }
+}
+
+/// Why the engine turned down an order, cancel or amendment. Converts to
+/// and from the `String` errors used elsewhere, so `?` works either way.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderError {
+    /// A pre-trade [`RiskCheck`](crate::RiskCheck) rejected the order.
+    Risk(RiskRejection),
+    /// The market's [`TradingState`] does not allow the request right now.
+    MarketState {
+        state: TradingState,
+        message: String,
+    },
+    /// Anything else, e.g. an unknown market or an unfunded order.
+    Other(String),
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Risk(rejection) => rejection.fmt(f),
+            OrderError::MarketState { message, .. } | OrderError::Other(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+impl From<RiskRejection> for OrderError {
+    fn from(rejection: RiskRejection) -> Self {
+        OrderError::Risk(rejection)
+    }
+}
+
+impl From<String> for OrderError {
+    fn from(message: String) -> Self {
+        OrderError::Other(message)
+    }
+}
+
+impl From<&str> for OrderError {
+    fn from(message: &str) -> Self {
+        OrderError::Other(message.to_string())
+    }
+}
+
+impl From<OrderError> for String {
+    fn from(error: OrderError) -> Self {
+        error.to_string()
+    }
+}
+
 pub struct MatchingEngine {
     orderbooks: HashMap<TradingPair, OrderBook>,
+    tape: Option<TradeTape>,
+    ledger: Option<Ledger>,
+    fees: Fees,
+    positions: Option<PositionKeeper>,
+    risk: HashMap<TradingPair, HashMap<Option<AccountId>, RiskChain>>,
+    last_prices: HashMap<TradingPair, Price>,
+    controls: HashMap<TradingPair, MarketControl>,
+    order_retention: Option<Duration>,
+    readers: EngineReader,
 }
 
 impl MatchingEngine {
     pub fn new() -> Self {
         Self {
             orderbooks: HashMap::new(),
+            tape: None,
+            ledger: None,
+            fees: Fees::default(),
+            positions: None,
+            risk: HashMap::new(),
+            last_prices: HashMap::new(),
+            controls: HashMap::new(),
+            order_retention: None,
+            readers: EngineReader::default(),
+        }
+    }
+
+    /// Handle for lock-free reads of every market's latest snapshot. Books
+    /// publish after each mutating call.
+    pub fn reader(&self) -> EngineReader {
+        self.readers.clone()
+    }
+
+    /// Applies `f` to the market's book and publishes a new snapshot.
+    ///
+    /// Every public operation that changes a book goes through here exactly
+    /// once, after its checks pass, so readers see one new version per call
+    /// however many orders it matched. Publishing copies the top
+    /// [`snapshot_depth`](OrderBook::snapshot_depth) levels of each side, an
+    /// O(depth) cost paid on every such call.
+    fn mutate<T>(
+        &mut self,
+        pair: &TradingPair,
+        f: impl FnOnce(&mut OrderBook) -> T,
+    ) -> Result<T, String> {
+        let ob = self
+            .orderbooks
+            .get_mut(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))?;
+        let out = f(ob);
+        ob.publish();
+        Ok(out)
+    }
+
+    /// Starts keeping every execution in a [`TradeTape`] instead of only
+    /// returning it from `place_order`/`amend_order`.
+    pub fn enable_trade_tape(&mut self) {
+        self.tape.get_or_insert_with(TradeTape::new);
+    }
+
+    pub fn trade_tape(&self) -> Option<&TradeTape> {
+        self.tape.as_ref()
+    }
+
+    pub fn trade_tape_mut(&mut self) -> Option<&mut TradeTape> {
+        self.tape.as_mut()
+    }
+
+    /// Starts settling trades between accounts. From then on orders must be
+    /// placed with [`place_order_for`](Self::place_order_for), so the ledger
+    /// can only be enabled while every book is empty.
+    ///
+    /// Of the gateways, only the Binance facade knows accounts (see
+    /// [`BinanceFacade::set_account`](crate::BinanceFacade::set_account));
+    /// the others place orders with [`place_order`](Self::place_order),
+    /// which an engine with the ledger enabled refuses.
+    pub fn enable_ledger(&mut self) -> Result<(), String> {
+        if self.ledger.is_some() {
+            return Ok(());
+        }
+        if let Some((pair, _)) = self.orderbooks.iter().find(|(_, ob)| !ob.is_empty()) {
+            return Err(format!("Market for {} has resting orders", pair));
+        }
+        self.ledger = Some(Ledger::new());
+        Ok(())
+    }
+
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
+    }
+
+    pub fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        self.ledger.as_mut()
+    }
+
+    pub fn set_fee_schedule(
+        &mut self,
+        pair: &TradingPair,
+        schedule: FeeSchedule,
+    ) -> Result<(), String> {
+        self.book(pair)?;
+        self.fees.set_market_schedule(pair.clone(), schedule);
+        Ok(())
+    }
+
+    /// Overrides the market schedules for `account`, whose orders are known
+    /// through the ledger.
+    pub fn set_account_fee_schedule(&mut self, account: AccountId, schedule: FeeSchedule) {
+        self.fees.set_account_schedule(account, schedule);
+    }
+
+    pub fn fees(&self) -> &Fees {
+        &self.fees
+    }
+
+    /// Starts tracking positions and PnL of the ledger's accounts from every
+    /// fill.
+    pub fn enable_positions(&mut self) {
+        self.positions.get_or_insert_with(PositionKeeper::new);
+    }
+
+    pub fn positions(&self) -> Option<&PositionKeeper> {
+        self.positions.as_ref()
+    }
+
+    pub fn mark_price(
+        &self,
+        pair: &TradingPair,
+        source: MarkSource,
+    ) -> Result<Option<Price>, String> {
+        let book = self.book(pair)?;
+        Ok(match source {
+            MarkSource::Mid => book.mid_price(),
+            MarkSource::LastTrade => self.last_prices.get(pair).copied(),
+        })
+    }
+
+    /// Unrealized PnL of `account` in `pair`, or `None` without a mark price
+    /// or a position.
+    pub fn unrealized_pnl(
+        &self,
+        account: AccountId,
+        pair: &TradingPair,
+        method: PnlMethod,
+        source: MarkSource,
+    ) -> Result<Option<Decimal>, String> {
+        let mark = self.mark_price(pair, source)?;
+        let position = self
+            .positions
+            .as_ref()
+            .ok_or("positions are not enabled")?
+            .position(account, pair);
+        Ok(mark
+            .zip(position)
+            .map(|(mark, p)| p.unrealized(method, mark)))
+    }
+
+    /// Every position as a frame, marked at `source`.
+    pub fn positions_frame(&self, source: MarkSource) -> crate::Result<DataFrame> {
+        let positions = self.positions.as_ref().ok_or("positions are not enabled")?;
+        let mut marks = HashMap::new();
+        for pair in self.orderbooks.keys() {
+            if let Some(mark) = self.mark_price(pair, source)? {
+                marks.insert(pair.clone(), mark);
+            }
+        }
+        Ok(positions.frame(&marks)?)
+    }
+
+    /// Runs `chain` before every order in `pair`: with no `account` on all
+    /// of them, otherwise after the market's chain on `account`'s orders.
+    /// Replaces any chain set for the same market and account.
+    pub fn set_risk_chain(
+        &mut self,
+        pair: &TradingPair,
+        account: Option<AccountId>,
+        chain: RiskChain,
+    ) -> Result<(), String> {
+        self.book(pair)?;
+        self.risk
+            .entry(pair.clone())
+            .or_default()
+            .insert(account, chain);
+        Ok(())
+    }
+
+    fn pre_trade(
+        &mut self,
+        account: Option<AccountId>,
+        pair: &TradingPair,
+        order: &OrderRequest,
+        amends: Option<OrderId>,
+    ) -> Result<(), OrderError> {
+        let Some(chains) = self.risk.get_mut(pair) else {
+            return Ok(());
+        };
+        let ctx = RiskContext {
+            pair,
+            account,
+            book: self
+                .orderbooks
+                .get(pair)
+                .ok_or_else(|| format!("Market for {} does not exist", pair))?,
+            last_price: self.last_prices.get(pair).copied(),
+            position: account.and_then(|a| self.positions.as_ref()?.position(a, pair)),
+            open_orders: account
+                .zip(self.ledger.as_ref())
+                .map_or(0, |(a, ledger)| ledger.open_orders(a)),
+            amends,
+            now: SystemTime::now(),
+        };
+        if let Some(chain) = chains.get_mut(&None) {
+            chain.check(&ctx, order)?;
+        }
+        if account.is_some()
+            && let Some(chain) = chains.get_mut(&account)
+        {
+            chain.check(&ctx, order)?;
+        }
+        Ok(())
+    }
+
+    pub fn market_state(&self, pair: &TradingPair) -> Result<TradingState, String> {
+        self.control(pair).map(|c| c.state)
+    }
+
+    /// Why `pair` was last halted by its circuit breaker, until it resumes.
+    pub fn halt_reason(&self, pair: &TradingPair) -> Result<Option<&str>, String> {
+        self.control(pair).map(|c| c.reason.as_deref())
+    }
+
+    /// Moves `pair` to `state`. Closing cancels every resting order, and a
+    /// closed market can only be reopened.
+    pub fn set_market_state(
+        &mut self,
+        pair: &TradingPair,
+        state: TradingState,
+    ) -> Result<(), String> {
+        let control = self
+            .controls
+            .get_mut(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))?;
+        if control.state == TradingState::Closed && state != TradingState::Open {
+            return Err(format!(
+                "Market for {} is closed and must be reopened first",
+                pair
+            ));
+        }
+        control.state = state;
+        control.reason = None;
+        if let Some(breaker) = control.breaker.as_mut() {
+            breaker.reset();
+        }
+        if state == TradingState::Closed {
+            let ids: Vec<OrderId> = self.book(pair)?.order_loc.keys().copied().collect();
+            self.mutate(pair, OrderBook::clear)?;
+            if let Some(ledger) = self.ledger.as_mut() {
+                for id in ids {
+                    ledger.release_to(id, Decimal::ZERO);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Halts `pair` whenever `breaker` trips; `None` removes it.
+    pub fn set_circuit_breaker(
+        &mut self,
+        pair: &TradingPair,
+        breaker: Option<CircuitBreaker>,
+    ) -> Result<(), String> {
+        self.controls
+            .get_mut(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))?
+            .breaker = breaker;
+        Ok(())
+    }
+
+    fn control(&self, pair: &TradingPair) -> Result<&MarketControl, String> {
+        self.controls
+            .get(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    /// Rejects `order` if its id is still resting on `pair`. System-level
+    /// orders are exempt: re-entering one tops up the resting order.
+    fn check_order_id(&self, pair: &TradingPair, order: &OrderRequest) -> Result<(), OrderError> {
+        let live = self.book(pair)?.order_loc.contains_key(&order.id());
+        if live && !matches!(order.order_type, OrderType::SystemLevel(_)) {
+            return Err("duplicate order id".into());
+        }
+        Ok(())
+    }
+
+    /// Halts `pair` and rejects `order` if any fill it would get trips the
+    /// circuit breaker, so nothing trades beyond the band.
+    fn check_breaker(
+        &mut self,
+        pair: &TradingPair,
+        order: &OrderRequest,
+    ) -> Result<(), OrderError> {
+        let Some(mut breaker) = self.control(pair)?.breaker.clone() else {
+            return Ok(());
+        };
+        let simulation = self.simulate_order(pair, order)?;
+        let tripped = simulation
+            .executions
+            .iter()
+            .find_map(|e| breaker.observe(e.timestamp, e.price));
+        match (tripped, self.controls.get_mut(pair)) {
+            (Some(reason), Some(control)) => {
+                control.state = TradingState::Halted;
+                control.reason = Some(reason);
+                Err(state_rejection(pair, control))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects `order` if `pair`'s state does not take new orders, or takes
+    /// only those that would rest without trading.
+    fn check_order_state(
+        &self,
+        pair: &TradingPair,
+        order: &OrderRequest,
+    ) -> Result<(), OrderError> {
+        let control = self.control(pair)?;
+        if !control.state.accepts_orders() {
+            return Err(state_rejection(pair, control));
+        }
+        if control.state == TradingState::PostOnly {
+            let book = self.book(pair)?;
+            let crosses = match (order.order_type, order.side) {
+                (OrderType::Limit(price) | OrderType::SystemLevel(price), Side::Bid) => {
+                    book.best_ask().is_some_and(|ask| price >= ask)
+                }
+                (OrderType::Limit(price) | OrderType::SystemLevel(price), Side::Ask) => {
+                    book.best_bid().is_some_and(|bid| price <= bid)
+                }
+                _ => true,
+            };
+            if crosses {
+                return Err(OrderError::MarketState {
+                    state: control.state,
+                    message: format!(
+                        "Market for {} is post-only and the order would take liquidity",
+                        pair
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_cancel_state(&self, pair: &TradingPair) -> Result<(), OrderError> {
+        let control = self.control(pair)?;
+        if !control.state.accepts_cancels() {
+            return Err(state_rejection(pair, control));
+        }
+        Ok(())
+    }
+
+    /// Streams every visible change to `pair`'s book, e.g. to feed an
+    /// [`ItchEncoder`](crate::ItchEncoder).
+    pub fn subscribe_book_changes(
+        &mut self,
+        pair: &TradingPair,
+    ) -> Result<Receiver<BookChange>, String> {
+        self.orderbooks
+            .get_mut(pair)
+            .map(OrderBook::subscribe_changes)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    /// Sets the fees of `executions` and books them against positions.
+    fn charge(&mut self, pair: &TradingPair, executions: &mut [TradeExecution]) {
+        for execution in executions {
+            let owner = |id| self.ledger.as_ref().and_then(|l| l.owner(id));
+            let (maker, taker) = (
+                owner(execution.maker_order_id),
+                owner(execution.taker_order_id),
+            );
+            self.fees.apply(pair, execution, maker, taker);
+            self.last_prices.insert(pair.clone(), execution.price);
+            if let Some(positions) = self.positions.as_mut() {
+                positions.record(pair, execution, maker, taker);
+            }
+        }
+    }
+
+    /// Settles `executions` and shrinks the reservations of `order_id` and
+    /// of every order that traded to what their resting remainder can still
+    /// spend. An execution the ledger cannot pay for is skipped and reported
+    /// once the rest are settled; the book has already traded it.
+    fn settle(
+        &mut self,
+        pair: &TradingPair,
+        order_id: Option<OrderId>,
+        executions: &[TradeExecution],
+    ) -> Result<(), String> {
+        let (Some(ledger), Some(ob)) = (self.ledger.as_mut(), self.orderbooks.get(pair)) else {
+            return Ok(());
+        };
+        let mut settled = Ok(());
+        for execution in executions {
+            settled = settled.and(ledger.settle(pair, execution));
+        }
+        let traded = executions
+            .iter()
+            .flat_map(|e| [e.maker_order_id, e.taker_order_id]);
+        let orders = order_id.into_iter().chain(traded);
+        for id in orders {
+            let keep = match (ob.order_loc.get(&id), ob.get_order(id)) {
+                (Some((Side::Ask, _)), Some(order)) => order.remaining_qty,
+                (Some((Side::Bid, price)), Some(order)) => price * order.remaining_qty,
+                _ => Decimal::ZERO,
+            };
+            ledger.release_to(id, keep);
+        }
+        settled
+    }
+
+    /// Keeps `executions` on the tape and feeds them to `pair`'s circuit
+    /// breaker, halting it should they trip it after all.
+    fn record(&mut self, pair: &TradingPair, executions: &[TradeExecution]) {
+        if let Some(tape) = self.tape.as_mut() {
+            tape.record(pair, executions);
+        }
+        if let Some(control) = self.controls.get_mut(pair)
+            && let Some(breaker) = control.breaker.as_mut()
+        {
+            let tripped = executions
+                .iter()
+                .filter_map(|e| breaker.observe(e.timestamp, e.price))
+                .last();
+            if let Some(reason) = tripped {
+                control.state = TradingState::Halted;
+                control.reason = Some(reason);
+            }
         }
     }
 
@@ -39,41 +625,283 @@ impl MatchingEngine {
         if self.orderbooks.contains_key(&pair) {
             Err(format!("Market for {} already exists", pair))
         } else {
-            self.orderbooks.insert(pair.clone(), OrderBook::default());
+            let mut ob = OrderBook::default();
+            ob.set_order_retention(self.order_retention);
+            self.readers.books.insert(pair.clone(), ob.reader());
+            self.controls.insert(pair.clone(), MarketControl::default());
+            self.orderbooks.insert(pair, ob);
             Ok(())
         }
     }
 
     pub fn remove_market(&mut self, pair: &TradingPair) -> Result<(), String> {
-        if self.orderbooks.remove(pair).is_some() {
+        if let Some(ob) = self.orderbooks.remove(pair) {
+            self.readers.books.remove(pair);
+            self.risk.remove(pair);
+            self.last_prices.remove(pair);
+            self.controls.remove(pair);
+            if let Some(ledger) = self.ledger.as_mut() {
+                for order_id in ob.order_loc.keys() {
+                    ledger.release_to(*order_id, Decimal::ZERO);
+                }
+            }
             Ok(())
         } else {
             Err(format!("market for {} dose not exist", pair))
         }
     }
 
+    /// Places `order` without an account; refused once the ledger is
+    /// enabled.
     pub fn place_order(
         &mut self,
         pair: &TradingPair,
         order: OrderRequest,
-    ) -> Result<(OrderResult, Vec<TradeExecution>), String> {
-        self.orderbooks
-            .get_mut(pair)
-            .map(|ob| ob.add_order(order))
-            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    ) -> Result<(OrderResult, Vec<TradeExecution>), OrderError> {
+        if self.ledger.is_some() {
+            return Err("orders need an account while the ledger is enabled".into());
+        }
+        self.check_order_id(pair, &order)?;
+        self.check_order_state(pair, &order)?;
+        self.pre_trade(None, pair, &order, None)?;
+        self.check_breaker(pair, &order)?;
+        let (result, mut executions) = self.mutate(pair, |ob| ob.add_order(order))?;
+        self.charge(pair, &mut executions);
+        self.record(pair, &executions);
+        Ok((result, executions))
+    }
+
+    /// Places `order` on behalf of `account`, first reserving the most it
+    /// can spend: its quantity of base for asks, and for bids its limit
+    /// times its quantity of quote, or the simulated cost of a market order.
+    pub fn place_order_for(
+        &mut self,
+        account: AccountId,
+        pair: &TradingPair,
+        order: OrderRequest,
+    ) -> Result<(OrderResult, Vec<TradeExecution>), OrderError> {
+        self.check_order_id(pair, &order)?;
+        self.check_order_state(pair, &order)?;
+        self.pre_trade(Some(account), pair, &order, None)?;
+        self.check_breaker(pair, &order)?;
+        let amount = match (order.side, order.price()) {
+            (Side::Ask, _) => order.qty,
+            (Side::Bid, Some(price)) => price * order.qty,
+            (Side::Bid, None) => self
+                .simulate_order(pair, &order)?
+                .executions
+                .iter()
+                .map(|e| e.qty * e.price)
+                .sum(),
+        };
+        let asset = reserved_asset(pair, order.side);
+        let ledger = self.ledger.as_mut().ok_or("the ledger is not enabled")?;
+        ledger.reserve(account, order.id(), asset, amount)?;
+
+        let (result, mut executions) = self.mutate(pair, |ob| ob.add_order(order))?;
+        self.charge(pair, &mut executions);
+        let settled = self.settle(pair, Some(order.id()), &executions);
+        self.record(pair, &executions);
+        settled?;
+        Ok((result, executions))
+    }
+
+    /// Starts a call phase in `pair`: orders collect without matching until
+    /// [`uncross`](Self::uncross), with the last trade as reference price.
+    pub fn start_auction(&mut self, pair: &TradingPair) -> Result<(), String> {
+        let reference = self.last_prices.get(pair).copied();
+        self.mutate(pair, |ob| ob.start_auction(reference))
+    }
+
+    pub fn indicative(&self, pair: &TradingPair) -> Result<Option<Indicative>, String> {
+        Ok(self.book(pair)?.indicative())
+    }
+
+    /// Ends `pair`'s call phase, trading every crossing order at the
+    /// equilibrium price.
+    pub fn uncross(&mut self, pair: &TradingPair) -> Result<Vec<TradeExecution>, String> {
+        let mut executions = self.mutate(pair, OrderBook::uncross)?;
+        self.charge(pair, &mut executions);
+        let settled = self.settle(pair, None, &executions);
+        self.record(pair, &executions);
+        settled?;
+        Ok(executions)
+    }
+
+    pub fn simulate_order(
+        &self,
+        pair: &TradingPair,
+        order: &OrderRequest,
+    ) -> Result<Simulation, String> {
+        Ok(self.book(pair)?.simulate(order))
     }
 
     pub fn cancel_order(
         &mut self,
         pair: &TradingPair,
         order_id: Uuid,
+    ) -> Result<Option<OrderResult>, OrderError> {
+        self.check_cancel_state(pair)?;
+        let cancelled = self.mutate(pair, |ob| ob.delete_order(order_id))?;
+        self.settle(pair, Some(order_id), &[])?;
+        Ok(cancelled)
+    }
+
+    pub fn reduce_order(
+        &mut self,
+        pair: &TradingPair,
+        order_id: Uuid,
+        qty: Quantity,
+    ) -> Result<Option<OrderResult>, OrderError> {
+        if qty <= Quantity::ZERO {
+            return Err("qty must be positive".into());
+        }
+        self.check_cancel_state(pair)?;
+        let reduced = self.mutate(pair, |ob| ob.cancel_order(order_id, qty))?;
+        self.settle(pair, Some(order_id), &[])?;
+        Ok(reduced)
+    }
+
+    pub fn amend_order(
+        &mut self,
+        pair: &TradingPair,
+        order_id: Uuid,
+        price: Price,
+        qty: Quantity,
+    ) -> Result<Option<(OrderResult, Vec<TradeExecution>)>, OrderError> {
+        if price <= Price::ZERO {
+            return Err("price must be positive".into());
+        }
+        if qty <= Quantity::ZERO {
+            return Err("qty must be positive".into());
+        }
+        if let Some(&(side, _)) = self.book(pair)?.order_loc.get(&order_id) {
+            let account = self.ledger.as_ref().and_then(|l| l.owner(order_id));
+            let amended = OrderRequest::new_with_id(order_id, side, qty, OrderType::Limit(price));
+            self.check_order_state(pair, &amended)?;
+            self.pre_trade(account, pair, &amended, Some(order_id))?;
+            self.check_breaker(pair, &amended)?;
+        }
+        if let Some(ledger) = self.ledger.as_mut() {
+            // Top the reservation up to what the amended order could spend;
+            // `settle` hands back the excess afterwards.
+            let ob = self
+                .orderbooks
+                .get(pair)
+                .ok_or_else(|| format!("Market for {} does not exist", pair))?;
+            if let (Some(&(side, _)), Some(reservation)) =
+                (ob.order_loc.get(&order_id), ledger.reservation(order_id))
+            {
+                let needed = match side {
+                    Side::Ask => qty,
+                    Side::Bid => price * qty,
+                };
+                let extra = needed - reservation.amount;
+                if extra > Decimal::ZERO {
+                    let account = reservation.account;
+                    ledger.reserve(account, order_id, reserved_asset(pair, side), extra)?;
+                }
+            }
+        }
+        let mut amended = self.mutate(pair, |ob| ob.amend_order(order_id, price, qty))?;
+        if let Some((_, executions)) = amended.as_mut() {
+            self.charge(pair, executions);
+        }
+        let executions = amended.as_ref().map_or(&[][..], |(_, e)| e.as_slice());
+        let settled = self.settle(pair, Some(order_id), executions);
+        if let Some((_, executions)) = &amended {
+            self.record(pair, executions);
+        }
+        settled?;
+        Ok(amended)
+    }
+
+    pub fn get_order(
+        &self,
+        pair: &TradingPair,
+        order_id: Uuid,
     ) -> Result<Option<OrderResult>, String> {
         self.orderbooks
-            .get_mut(pair)
-            .map(|ob| ob.delete_order(order_id))
+            .get(pair)
+            .map(|ob| ob.get_order(order_id).cloned().map(OrderResult::from))
             .ok_or_else(|| format!("Market for {} does not exist", pair))
     }
 
+    /// Keeps filled and cancelled orders answering
+    /// [`order_status`](Self::order_status) for `retention` in every
+    /// market, including those added later. `None`, the default, forgets
+    /// them as soon as they leave the book.
+    pub fn set_order_retention(&mut self, retention: Option<Duration>) {
+        self.order_retention = retention;
+        for ob in self.orderbooks.values_mut() {
+            ob.set_order_retention(retention);
+        }
+    }
+
+    /// The latest published [`BookSnapshot`] of `pair`, but with up to
+    /// `levels` levels per side read from the book itself, so it can go
+    /// deeper than the snapshot depth.
+    pub fn deep_snapshot(&self, pair: &TradingPair, levels: usize) -> Result<BookSnapshot, String> {
+        let book = self.book(pair)?;
+        let published = book.snapshot();
+        Ok(BookSnapshot {
+            version: published.version,
+            timestamp: published.timestamp,
+            bids: book.bids.levels().take(levels).collect(),
+            asks: book.asks.levels().take(levels).collect(),
+            indicative: published.indicative,
+        })
+    }
+
+    /// Like [`get_order`](Self::get_order), but also finds orders that
+    /// left the book within the retention period.
+    pub fn order_status(
+        &self,
+        pair: &TradingPair,
+        order_id: OrderId,
+    ) -> Result<Option<OrderResult>, String> {
+        Ok(self.book(pair)?.order_status(order_id))
+    }
+
+    /// Resting orders in `pair` that match `filter`, bids first, each side
+    /// from the best price outwards and in time priority. Owners are only
+    /// known while the ledger is enabled, so filtering by owner without it
+    /// is an error.
+    pub fn open_orders(
+        &self,
+        pair: &TradingPair,
+        filter: &OrderFilter,
+    ) -> Result<Vec<OrderResult>, String> {
+        if filter.owner.is_some() && self.ledger.is_none() {
+            return Err("filtering by owner needs the ledger".to_string());
+        }
+        let prices = (
+            filter.min_price.map_or(Bound::Unbounded, Bound::Included),
+            filter.max_price.map_or(Bound::Unbounded, Bound::Included),
+        );
+        let owner = |id| self.ledger.as_ref().and_then(|l| l.owner(id));
+        Ok(self
+            .book(pair)?
+            .open_orders(filter.side, prices)
+            .into_iter()
+            .filter(|order| filter.owner.is_none() || owner(order.id) == filter.owner)
+            .map(|order| OrderResult::from(order.clone()))
+            .collect())
+    }
+
+    /// Every fill of an order, oldest first, with its time, price and the
+    /// counterparty order. `None` for an order that is neither resting nor
+    /// retained.
+    pub fn fill_history(
+        &self,
+        pair: &TradingPair,
+        order_id: OrderId,
+    ) -> Result<Option<Vec<Fill>>, String> {
+        Ok(self
+            .order_status(pair, order_id)?
+            .map(|result| result.fills().to_vec()))
+    }
+
     pub fn get_order_book_state(&self, pair: &TradingPair) -> Result<OrderBookState, String> {
         self.orderbooks
             .get(pair)
@@ -127,6 +955,53 @@ impl MatchingEngine {
             .ok_or_else(|| format!("Market for {} does not exist", pair))
     }
 
+    fn book(&self, pair: &TradingPair) -> Result<&OrderBook, String> {
+        self.orderbooks
+            .get(pair)
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    pub fn get_mid_price(&self, pair: &TradingPair) -> Result<Option<Price>, String> {
+        Ok(self.book(pair)?.mid_price())
+    }
+
+    pub fn get_microprice(&self, pair: &TradingPair) -> Result<Option<Price>, String> {
+        Ok(self.book(pair)?.microprice())
+    }
+
+    pub fn get_imbalance(
+        &self,
+        pair: &TradingPair,
+        levels: usize,
+    ) -> Result<Option<Decimal>, String> {
+        Ok(self.book(pair)?.imbalance(levels))
+    }
+
+    pub fn get_weighted_mid(
+        &self,
+        pair: &TradingPair,
+        levels: usize,
+    ) -> Result<Option<Price>, String> {
+        Ok(self.book(pair)?.weighted_mid(levels))
+    }
+
+    pub fn get_cost_to_trade(
+        &self,
+        pair: &TradingPair,
+        side: Side,
+        qty: Quantity,
+    ) -> Result<Option<TradeCost>, String> {
+        Ok(self.book(pair)?.cost_to_trade(side, qty))
+    }
+
+    pub fn get_liquidity_within_bps(
+        &self,
+        pair: &TradingPair,
+        bps: Decimal,
+    ) -> Result<Option<(Quantity, Quantity)>, String> {
+        Ok(self.book(pair)?.liquidity_within_bps(bps))
+    }
+
     pub fn get_markets(&self) -> Vec<TradingPair> {
         self.orderbooks.keys().cloned().collect()
     }
@@ -136,6 +1011,25 @@ impl MatchingEngine {
     }
 }
 
+fn state_rejection(pair: &TradingPair, control: &MarketControl) -> OrderError {
+    let message = match &control.reason {
+        Some(reason) => format!("Market for {} is {}: {}", pair, control.state, reason),
+        None => format!("Market for {} is {}", pair, control.state),
+    };
+    OrderError::MarketState {
+        state: control.state,
+        message,
+    }
+}
+
+/// The asset an order on `side` of `pair` pays with.
+fn reserved_asset(pair: &TradingPair, side: Side) -> &str {
+    match side {
+        Side::Ask => pair.base(),
+        Side::Bid => pair.quote(),
+    }
+}
+
 impl Default for MatchingEngine {
     fn default() -> Self {
         Self::new()
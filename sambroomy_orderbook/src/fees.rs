@@ -0,0 +1,200 @@
+//! Maker/taker fee schedules.
+//!
+//! A [`FeeSchedule`] is a list of [`FeeTier`]s chosen by how much quote an
+//! account traded in the market over a rolling window. Schedules are set per
+//! market and can be overridden per account; each side of a fill pays its
+//! rate on what it receives, so buyers pay in base and sellers in quote.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    AccountId, Fee, Liquidity, Quantity, Side, TradeExecution, TradingPair, orderbook::Timestamp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    /// Quote volume over the window needed to reach this tier.
+    pub min_volume: Quantity,
+    /// Negative for a rebate.
+    pub maker_rate: Decimal,
+    pub taker_rate: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeSchedule {
+    tiers: Vec<FeeTier>,
+    window: Duration,
+}
+
+impl FeeSchedule {
+    pub fn flat(maker_rate: Decimal, taker_rate: Decimal) -> Result<Self, String> {
+        Self::tiered(
+            Duration::ZERO,
+            vec![FeeTier {
+                min_volume: Decimal::ZERO,
+                maker_rate,
+                taker_rate,
+            }],
+        )
+    }
+
+    /// `tiers` must start at zero volume and raise `min_volume` strictly.
+    pub fn tiered(window: Duration, tiers: Vec<FeeTier>) -> Result<Self, String> {
+        match tiers.first() {
+            None => return Err("a fee schedule needs at least one tier".to_string()),
+            Some(first) if !first.min_volume.is_zero() => {
+                return Err("the first fee tier must start at zero volume".to_string());
+            }
+            Some(_) => {}
+        }
+        if tiers.windows(2).any(|w| w[0].min_volume >= w[1].min_volume) {
+            return Err("fee tiers must have increasing volumes".to_string());
+        }
+        let one = Decimal::ONE;
+        if let Some(tier) = tiers.iter().find(|t| {
+            t.maker_rate.abs() >= one || t.taker_rate.abs() >= one || t.taker_rate < Decimal::ZERO
+        }) {
+            return Err(format!(
+                "invalid fee rates {} maker / {} taker",
+                tier.maker_rate, tier.taker_rate
+            ));
+        }
+        Ok(Self { tiers, window })
+    }
+
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The highest tier `volume` reaches.
+    pub fn tier(&self, volume: Quantity) -> &FeeTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|t| t.min_volume <= volume)
+            .unwrap_or(&self.tiers[0])
+    }
+}
+
+/// Fee schedules and the traded volume that selects their tiers.
+#[derive(Debug, Default)]
+pub struct Fees {
+    markets: HashMap<TradingPair, FeeSchedule>,
+    accounts: HashMap<AccountId, FeeSchedule>,
+    volumes: HashMap<(AccountId, TradingPair), VecDeque<(Timestamp, Quantity)>>,
+}
+
+impl Fees {
+    pub fn set_market_schedule(&mut self, pair: TradingPair, schedule: FeeSchedule) {
+        self.markets.insert(pair, schedule);
+    }
+
+    /// Overrides the market schedules for `account` in every market.
+    pub fn set_account_schedule(&mut self, account: AccountId, schedule: FeeSchedule) {
+        self.accounts.insert(account, schedule);
+    }
+
+    pub fn schedule(&self, account: Option<AccountId>, pair: &TradingPair) -> Option<&FeeSchedule> {
+        account
+            .and_then(|a| self.accounts.get(&a))
+            .or_else(|| self.markets.get(pair))
+    }
+
+    /// Quote volume `account` traded in `pair` within `window` of `now`.
+    pub fn volume(
+        &self,
+        account: AccountId,
+        pair: &TradingPair,
+        window: Duration,
+        now: Timestamp,
+    ) -> Quantity {
+        self.volumes
+            .get(&(account, pair.clone()))
+            .into_iter()
+            .flatten()
+            .filter(|(at, _)| in_window(*at, window, now))
+            .map(|(_, notional)| *notional)
+            .sum()
+    }
+
+    /// Sets the fees of both sides of `execution`. Tiers come from each
+    /// account's volume before this fill; orders without an account pay the
+    /// first tier.
+    pub fn apply(
+        &mut self,
+        pair: &TradingPair,
+        execution: &mut TradeExecution,
+        maker: Option<AccountId>,
+        taker: Option<AccountId>,
+    ) {
+        let maker_side = execution.take_side.opposite();
+        execution.maker_fee = self.fee(pair, execution, maker, maker_side, Liquidity::Maker);
+        execution.taker_fee = self.fee(
+            pair,
+            execution,
+            taker,
+            execution.take_side,
+            Liquidity::Taker,
+        );
+
+        let notional = execution.qty * execution.price;
+        for account in [maker, taker].into_iter().flatten() {
+            let window = self.window_of(account, pair);
+            let history = self.volumes.entry((account, pair.clone())).or_default();
+            while history
+                .front()
+                .is_some_and(|(at, _)| !in_window(*at, window, execution.timestamp))
+            {
+                history.pop_front();
+            }
+            history.push_back((execution.timestamp, notional));
+        }
+    }
+
+    fn fee(
+        &self,
+        pair: &TradingPair,
+        execution: &TradeExecution,
+        account: Option<AccountId>,
+        side: Side,
+        liquidity: Liquidity,
+    ) -> Option<Fee> {
+        let schedule = self.schedule(account, pair)?;
+        let volume = account.map_or(Decimal::ZERO, |a| {
+            self.volume(a, pair, schedule.window, execution.timestamp)
+        });
+        let tier = schedule.tier(volume);
+        let rate = match liquidity {
+            Liquidity::Maker => tier.maker_rate,
+            Liquidity::Taker => tier.taker_rate,
+        };
+        let (received, asset) = match side {
+            Side::Bid => (execution.qty, pair.base()),
+            Side::Ask => (execution.qty * execution.price, pair.quote()),
+        };
+        Some(Fee {
+            amount: received * rate,
+            asset: asset.to_string(),
+            liquidity,
+        })
+    }
+
+    /// Volume older than the account's current window is never needed.
+    fn window_of(&self, account: AccountId, pair: &TradingPair) -> Duration {
+        self.schedule(Some(account), pair)
+            .map_or(Duration::ZERO, FeeSchedule::window)
+    }
+}
+
+fn in_window(at: Timestamp, window: Duration, now: Timestamp) -> bool {
+    now.duration_since(at).map_or(true, |age| age < window)
+}
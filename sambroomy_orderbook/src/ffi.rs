@@ -0,0 +1,577 @@
+//! C ABI for embedding a [`MatchingEngine`] in-process.
+//!
+//! The engine is an opaque [`ObEngine`] handle; everything else crosses the
+//! boundary as plain `#[repr(C)]` values. Every fallible call returns an
+//! [`ObStatus`], and on failure [`ob_last_error`] describes what went wrong.
+//! Prices and quantities are exact [`ObDecimal`]s. Markets are named like
+//! `"BTC_USDT"`. `include/orderbook.h` declares the same API for C and C++;
+//! regenerate it with `cbindgen --config cbindgen.toml --output
+//! include/orderbook.h` after changing this module.
+//!
+//! Enums passed in are plain `u32`s and checked, since C lets any integer
+//! through an enum parameter; bad values fail with `InvalidArgument`.
+//!
+//! A handle is not thread-safe: callers must serialise calls on it.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr,
+    time::UNIX_EPOCH,
+};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    MatchingEngine, OrderId, OrderRequest, OrderResult, OrderStatus, OrderType, Price, Quantity,
+    Side, TradeExecution, TradingPair,
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObStatus {
+    Ok = 0,
+    NullArgument = 1,
+    InvalidArgument = 2,
+    UnknownMarket = 3,
+    MarketExists = 4,
+    /// The engine refused the request, e.g. a risk check or market state.
+    Rejected = 5,
+    NotFound = 6,
+    /// A Rust panic was caught; the handle should be freed.
+    Internal = 7,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObSide {
+    Bid = 0,
+    Ask = 1,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObOrderType {
+    Limit = 0,
+    Market = 1,
+    Ioc = 2,
+    Fok = 3,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObOrderStatus {
+    Open = 0,
+    Filled = 1,
+    PartiallyFilled = 2,
+    Cancelled = 3,
+}
+
+/// `mantissa / 10^scale`, with `scale` at most 28.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObDecimal {
+    pub mantissa: i64,
+    pub scale: u32,
+}
+
+/// The 16 bytes of an order's UUID.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObOrderId {
+    pub bytes: [u8; 16],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObLevel {
+    pub price: ObDecimal,
+    pub qty: ObDecimal,
+}
+
+/// Top of book; a missing side has `has_bid`/`has_ask` false and a zero
+/// level.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObBbo {
+    pub has_bid: bool,
+    pub bid: ObLevel,
+    pub has_ask: bool,
+    pub ask: ObLevel,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObOrderResult {
+    pub id: ObOrderId,
+    pub status: ObOrderStatus,
+    pub filled_qty: ObDecimal,
+    pub remaining_qty: ObDecimal,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObExecution {
+    pub taker_order_id: ObOrderId,
+    pub maker_order_id: ObOrderId,
+    pub take_side: ObSide,
+    pub price: ObDecimal,
+    pub qty: ObDecimal,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp_ns: u64,
+}
+
+/// Called once per execution, on the calling thread, before the call that
+/// caused it returns. `market` and `execution` are only valid during the
+/// call.
+pub type ObExecutionCallback = Option<
+    unsafe extern "C" fn(
+        market: *const c_char,
+        execution: *const ObExecution,
+        user_data: *mut c_void,
+    ),
+>;
+
+pub struct ObEngine {
+    engine: MatchingEngine,
+    callback: ObExecutionCallback,
+    user_data: *mut c_void,
+    last_error: CString,
+}
+
+type Failure = (ObStatus, String);
+
+impl ObEngine {
+    /// Runs `f`, recording any failure (or panic) as the last error.
+    fn call(&mut self, f: impl FnOnce(&mut Self) -> Result<(), Failure>) -> ObStatus {
+        let outcome = catch_unwind(AssertUnwindSafe(|| f(self)))
+            .unwrap_or_else(|_| Err((ObStatus::Internal, "the engine panicked".to_string())));
+        match outcome {
+            Ok(()) => {
+                self.last_error = CString::default();
+                ObStatus::Ok
+            }
+            Err((status, message)) => {
+                self.last_error = CString::new(message.replace('\0', " ")).unwrap_or_default();
+                status
+            }
+        }
+    }
+
+    fn market(&self, market: *const c_char) -> Result<TradingPair, Failure> {
+        let pair = market_name(market)?;
+        if !self.engine.market_exists(&pair) {
+            return Err((
+                ObStatus::UnknownMarket,
+                format!("Market for {} does not exist", pair),
+            ));
+        }
+        Ok(pair)
+    }
+
+    fn report(&self, pair: &TradingPair, executions: &[TradeExecution]) {
+        let Some(callback) = self.callback else {
+            return;
+        };
+        let market = CString::new(pair.to_string()).unwrap_or_default();
+        for execution in executions {
+            let execution = ObExecution::from(execution);
+            // SAFETY: the caller registered `callback` for `user_data` and
+            // both pointers outlive the call.
+            unsafe { callback(market.as_ptr(), &execution, self.user_data) };
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Failure {
+    (ObStatus::InvalidArgument, message.into())
+}
+
+fn market_name(market: *const c_char) -> Result<TradingPair, Failure> {
+    if market.is_null() {
+        return Err((ObStatus::NullArgument, "market is null".to_string()));
+    }
+    // SAFETY: the caller passes a NUL-terminated string.
+    let name = unsafe { CStr::from_ptr(market) }
+        .to_str()
+        .map_err(|_| invalid("market is not UTF-8"))?;
+    name.parse().map_err(invalid)
+}
+
+impl TryFrom<ObDecimal> for Decimal {
+    type Error = Failure;
+
+    fn try_from(value: ObDecimal) -> Result<Self, Failure> {
+        Decimal::try_from_i128_with_scale(value.mantissa.into(), value.scale)
+            .map_err(|e| invalid(format!("invalid decimal: {}", e)))
+    }
+}
+
+impl From<Decimal> for ObDecimal {
+    /// Saturates values whose mantissa needs more than 64 bits, which no
+    /// realistic price or quantity does once normalised.
+    fn from(value: Decimal) -> Self {
+        let value = value.normalize();
+        let mantissa = value.mantissa();
+        Self {
+            mantissa: mantissa.clamp(i64::MIN.into(), i64::MAX.into()) as i64,
+            scale: value.scale(),
+        }
+    }
+}
+
+impl From<OrderId> for ObOrderId {
+    fn from(id: OrderId) -> Self {
+        Self {
+            bytes: id.into_bytes(),
+        }
+    }
+}
+
+impl From<ObOrderId> for OrderId {
+    fn from(id: ObOrderId) -> Self {
+        OrderId::from_bytes(id.bytes)
+    }
+}
+
+impl From<Side> for ObSide {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Bid => ObSide::Bid,
+            Side::Ask => ObSide::Ask,
+        }
+    }
+}
+
+impl From<ObSide> for Side {
+    fn from(side: ObSide) -> Self {
+        match side {
+            ObSide::Bid => Side::Bid,
+            ObSide::Ask => Side::Ask,
+        }
+    }
+}
+
+impl TryFrom<u32> for ObSide {
+    type Error = Failure;
+
+    fn try_from(value: u32) -> Result<Self, Failure> {
+        match value {
+            0 => Ok(ObSide::Bid),
+            1 => Ok(ObSide::Ask),
+            _ => Err(invalid(format!("invalid side {}", value))),
+        }
+    }
+}
+
+impl TryFrom<u32> for ObOrderType {
+    type Error = Failure;
+
+    fn try_from(value: u32) -> Result<Self, Failure> {
+        match value {
+            0 => Ok(ObOrderType::Limit),
+            1 => Ok(ObOrderType::Market),
+            2 => Ok(ObOrderType::Ioc),
+            3 => Ok(ObOrderType::Fok),
+            _ => Err(invalid(format!("invalid order type {}", value))),
+        }
+    }
+}
+
+impl From<&OrderResult> for ObOrderResult {
+    fn from(result: &OrderResult) -> Self {
+        Self {
+            id: result.get_id().into(),
+            status: match result.status {
+                OrderStatus::Open => ObOrderStatus::Open,
+                OrderStatus::Filled => ObOrderStatus::Filled,
+                OrderStatus::PartiallyFilled => ObOrderStatus::PartiallyFilled,
+                OrderStatus::Cancelled => ObOrderStatus::Cancelled,
+            },
+            filled_qty: result.filled_qty().into(),
+            remaining_qty: result.remaining_qty.into(),
+        }
+    }
+}
+
+impl From<&TradeExecution> for ObExecution {
+    fn from(execution: &TradeExecution) -> Self {
+        Self {
+            taker_order_id: execution.taker_order_id.into(),
+            maker_order_id: execution.maker_order_id.into(),
+            take_side: execution.take_side.into(),
+            price: execution.price.into(),
+            qty: execution.qty.into(),
+            timestamp_ns: execution
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64),
+        }
+    }
+}
+
+fn level((price, qty): (Price, Quantity)) -> ObLevel {
+    ObLevel {
+        price: price.into(),
+        qty: qty.into(),
+    }
+}
+
+/// Runs `f` on the engine behind `engine`, or reports a null handle.
+fn with_engine(
+    engine: *mut ObEngine,
+    f: impl FnOnce(&mut ObEngine) -> Result<(), Failure>,
+) -> ObStatus {
+    // SAFETY: non-null handles come from `ob_engine_new` and are not yet
+    // freed.
+    match unsafe { engine.as_mut() } {
+        Some(engine) => engine.call(f),
+        None => ObStatus::NullArgument,
+    }
+}
+
+/// Writes `value` through `out`, which the caller allows to be null when it
+/// does not want the value.
+fn write<T>(out: *mut T, value: T) {
+    // SAFETY: non-null output pointers point to writable `T`s.
+    if let Some(out) = unsafe { out.as_mut() } {
+        *out = value;
+    }
+}
+
+/// Creates an engine without markets. Free it with [`ob_engine_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn ob_engine_new() -> *mut ObEngine {
+    Box::into_raw(Box::new(ObEngine {
+        engine: MatchingEngine::new(),
+        callback: None,
+        user_data: ptr::null_mut(),
+        last_error: CString::default(),
+    }))
+}
+
+/// Frees `engine` and every market in it. Null is ignored.
+///
+/// # Safety
+///
+/// `engine` must come from [`ob_engine_new`] and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_engine_free(engine: *mut ObEngine) {
+    if !engine.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+/// Describes the last failed call on `engine`; empty after a successful one.
+/// The string lives until the next call on `engine`.
+///
+/// # Safety
+///
+/// `engine` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_last_error(engine: *const ObEngine) -> *const c_char {
+    // SAFETY: guaranteed by the caller.
+    match unsafe { engine.as_ref() } {
+        Some(engine) => engine.last_error.as_ptr(),
+        None => c"engine is null".as_ptr(),
+    }
+}
+
+/// Calls `callback` with `user_data` for every execution from now on; a null
+/// `callback` stops the calls.
+///
+/// # Safety
+///
+/// `engine` must be null or a live handle, and `callback` must be safe to
+/// call with `user_data` for as long as it is registered.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_set_execution_callback(
+    engine: *mut ObEngine,
+    callback: ObExecutionCallback,
+    user_data: *mut c_void,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        engine.callback = callback;
+        engine.user_data = user_data;
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `engine` must be null or a live handle and `market` null or a
+/// NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_add_market(engine: *mut ObEngine, market: *const c_char) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = market_name(market)?;
+        engine
+            .engine
+            .add_market(pair)
+            .map_err(|e| (ObStatus::MarketExists, e))
+    })
+}
+
+/// Removes `market` and drops its resting orders.
+///
+/// # Safety
+///
+/// As for [`ob_add_market`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_remove_market(
+    engine: *mut ObEngine,
+    market: *const c_char,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = engine.market(market)?;
+        engine
+            .engine
+            .remove_market(&pair)
+            .map_err(|e| (ObStatus::UnknownMarket, e))
+    })
+}
+
+/// Places an order, reporting its executions to the callback and its
+/// outcome through `result`, which may be null. `side` is an [`ObSide`] and
+/// `order_type` an [`ObOrderType`]. `price` is ignored for market orders.
+///
+/// # Safety
+///
+/// `engine` must be null or a live handle, `market` null or a NUL-terminated
+/// string and `result` null or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_place_order(
+    engine: *mut ObEngine,
+    market: *const c_char,
+    side: u32,
+    order_type: u32,
+    price: ObDecimal,
+    qty: ObDecimal,
+    result: *mut ObOrderResult,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = engine.market(market)?;
+        let side = ObSide::try_from(side)?;
+        let order_type = ObOrderType::try_from(order_type)?;
+        let qty = Decimal::try_from(qty)?;
+        if qty <= Decimal::ZERO {
+            return Err(invalid(format!("invalid quantity {}", qty)));
+        }
+        let price = Decimal::try_from(price)?;
+        let order_type = match order_type {
+            ObOrderType::Market => OrderType::Market,
+            _ if price <= Decimal::ZERO => return Err(invalid(format!("invalid price {}", price))),
+            ObOrderType::Limit => OrderType::Limit(price),
+            ObOrderType::Ioc => OrderType::IOC(price),
+            ObOrderType::Fok => OrderType::FOK(price),
+        };
+        let order = OrderRequest::new(side.into(), qty, order_type);
+        let (placed, executions) = engine
+            .engine
+            .place_order(&pair, order)
+            .map_err(|e| (ObStatus::Rejected, e.to_string()))?;
+        engine.report(&pair, &executions);
+        write(result, ObOrderResult::from(&placed));
+        Ok(())
+    })
+}
+
+/// Cancels a resting order, or returns `NotFound` if it is not resting.
+///
+/// # Safety
+///
+/// As for [`ob_place_order`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_cancel_order(
+    engine: *mut ObEngine,
+    market: *const c_char,
+    order_id: ObOrderId,
+    result: *mut ObOrderResult,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = engine.market(market)?;
+        let id = OrderId::from(order_id);
+        let cancelled = engine
+            .engine
+            .cancel_order(&pair, id)
+            .map_err(|e| (ObStatus::Rejected, e.to_string()))?
+            .ok_or_else(|| (ObStatus::NotFound, format!("order {} is not resting", id)))?;
+        write(result, ObOrderResult::from(&cancelled));
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `engine` must be null or a live handle, `market` null or a NUL-terminated
+/// string and `bbo` null or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_get_bbo(
+    engine: *mut ObEngine,
+    market: *const c_char,
+    bbo: *mut ObBbo,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = engine.market(market)?;
+        let state = engine
+            .engine
+            .reader()
+            .get_depth(&pair, 1)
+            .map_err(|e| (ObStatus::UnknownMarket, e))?;
+        let empty = level((Decimal::ZERO, Decimal::ZERO));
+        let (bid, ask) = (state.bids.first().copied(), state.asks.first().copied());
+        write(
+            bbo,
+            ObBbo {
+                has_bid: bid.is_some(),
+                bid: bid.map_or(empty, level),
+                has_ask: ask.is_some(),
+                ask: ask.map_or(empty, level),
+            },
+        );
+        Ok(())
+    })
+}
+
+/// Writes up to `capacity` levels of `side`, an [`ObSide`], best first, into
+/// `levels` and their number into `count`. Depth is limited to the published snapshot.
+///
+/// # Safety
+///
+/// `engine` must be null or a live handle, `market` null or a NUL-terminated
+/// string, `levels` valid for `capacity` writes (or null if `capacity` is 0)
+/// and `count` null or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ob_get_depth(
+    engine: *mut ObEngine,
+    market: *const c_char,
+    side: u32,
+    levels: *mut ObLevel,
+    capacity: usize,
+    count: *mut usize,
+) -> ObStatus {
+    with_engine(engine, |engine| {
+        let pair = engine.market(market)?;
+        let side = ObSide::try_from(side)?;
+        if levels.is_null() && capacity > 0 {
+            return Err((ObStatus::NullArgument, "levels is null".to_string()));
+        }
+        let state = engine
+            .engine
+            .reader()
+            .get_depth(&pair, capacity)
+            .map_err(|e| (ObStatus::UnknownMarket, e))?;
+        let book_side = match side {
+            ObSide::Bid => state.bids,
+            ObSide::Ask => state.asks,
+        };
+        for (i, l) in book_side.iter().enumerate() {
+            // SAFETY: `levels` has room for `capacity` entries and `get_depth`
+            // returns at most that many.
+            unsafe { levels.add(i).write(level(*l)) };
+        }
+        write(count, book_side.len());
+        Ok(())
+    })
+}
@@ -0,0 +1,264 @@
+//! FIX 4.4 tag=value encoding.
+//!
+//! A [`FixMessage`] holds every field after `BodyLength(9)` and before
+//! `CheckSum(10)`, in wire order; [`FixMessage::encode`] adds the standard
+//! header and trailer and [`FixMessage::decode`] strips and verifies them.
+
+use std::{fmt::Display, str::FromStr};
+
+pub const SOH: u8 = 0x01;
+pub const BEGIN_STRING: &str = "FIX.4.4";
+
+/// Tags used by the gateway.
+pub mod tag {
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_SEQ_NO: u32 = 7;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECK_SUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const END_SEQ_NO: u32 = 16;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const NEW_SEQ_NO: u32 = 36;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const POSS_DUP_FLAG: u32 = 43;
+    pub const PRICE: u32 = 44;
+    pub const REF_SEQ_NUM: u32 = 45;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const ENCRYPT_METHOD: u32 = 98;
+    pub const CXL_REJ_REASON: u32 = 102;
+    pub const ORD_REJ_REASON: u32 = 103;
+    pub const HEART_BT_INT: u32 = 108;
+    pub const TEST_REQ_ID: u32 = 112;
+    pub const ORIG_SENDING_TIME: u32 = 122;
+    pub const GAP_FILL_FLAG: u32 = 123;
+    pub const RESET_SEQ_NUM_FLAG: u32 = 141;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const REF_TAG_ID: u32 = 371;
+    pub const REF_MSG_TYPE: u32 = 372;
+    pub const SESSION_REJECT_REASON: u32 = 373;
+    pub const BUSINESS_REJECT_REASON: u32 = 380;
+    pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+}
+
+/// `MsgType(35)` values used by the gateway.
+pub mod msg_type {
+    pub const HEARTBEAT: &str = "0";
+    pub const TEST_REQUEST: &str = "1";
+    pub const RESEND_REQUEST: &str = "2";
+    pub const REJECT: &str = "3";
+    pub const SEQUENCE_RESET: &str = "4";
+    pub const LOGOUT: &str = "5";
+    pub const EXECUTION_REPORT: &str = "8";
+    pub const ORDER_CANCEL_REJECT: &str = "9";
+    pub const LOGON: &str = "A";
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+    pub const ORDER_CANCEL_REPLACE_REQUEST: &str = "G";
+    pub const BUSINESS_MESSAGE_REJECT: &str = "j";
+
+    /// Session-level messages, which are gap-filled rather than resent.
+    pub fn is_admin(msg_type: &str) -> bool {
+        matches!(msg_type, "0" | "1" | "2" | "3" | "4" | "5" | "A")
+    }
+}
+
+/// Standard header fields that follow `MsgType(35)`.
+pub const HEADER_TAGS: [u32; 6] = [
+    tag::SENDER_COMP_ID,
+    tag::TARGET_COMP_ID,
+    tag::MSG_SEQ_NUM,
+    tag::POSS_DUP_FLAG,
+    tag::SENDING_TIME,
+    tag::ORIG_SENDING_TIME,
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixMessage {
+    fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            fields: vec![(tag::MSG_TYPE, msg_type.to_string())],
+        }
+    }
+
+    pub fn with(mut self, tag: u32, value: impl Display) -> Self {
+        self.push(tag, value);
+        self
+    }
+
+    pub fn push(&mut self, tag: u32, value: impl Display) {
+        self.fields.push((tag, value.to_string()));
+    }
+
+    /// Replaces the first `tag` field, or appends one.
+    pub fn set(&mut self, tag: u32, value: impl Display) {
+        match self.fields.iter_mut().find(|(t, _)| *t == tag) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.push(tag, value),
+        }
+    }
+
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn parse<T: FromStr>(&self, tag: u32) -> Option<T> {
+        self.get(tag)?.parse().ok()
+    }
+
+    pub fn msg_type(&self) -> &str {
+        self.get(tag::MSG_TYPE).unwrap_or_default()
+    }
+
+    pub fn seq_num(&self) -> Option<u64> {
+        self.parse(tag::MSG_SEQ_NUM)
+    }
+
+    pub fn flag(&self, tag: u32) -> bool {
+        self.get(tag) == Some("Y")
+    }
+
+    pub fn fields(&self) -> &[(u32, String)] {
+        &self.fields
+    }
+
+    /// Fields other than `MsgType(35)` and the [`HEADER_TAGS`].
+    pub fn body(&self) -> impl Iterator<Item = &(u32, String)> {
+        self.fields
+            .iter()
+            .filter(|(t, _)| *t != tag::MSG_TYPE && !HEADER_TAGS.contains(t))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(self.fields.len() * 8);
+        for (tag, value) in &self.fields {
+            body.extend_from_slice(format!("{}={}", tag, value).as_bytes());
+            body.push(SOH);
+        }
+        let mut out = format!("8={}\x019={}\x01", BEGIN_STRING, body.len()).into_bytes();
+        out.extend_from_slice(&body);
+        let checksum = checksum(&out);
+        out.extend_from_slice(format!("10={:03}\x01", checksum).as_bytes());
+        out
+    }
+
+    /// Decodes the first message in `buf`. Returns the message and the number
+    /// of bytes it used, or `None` if `buf` does not hold a whole message yet.
+    pub fn decode(buf: &[u8]) -> Result<Option<(FixMessage, usize)>, String> {
+        let begin = format!("8={}\x019=", BEGIN_STRING);
+        let begin = begin.as_bytes();
+        if buf.len() < begin.len() {
+            return if begin.starts_with(buf) {
+                Ok(None)
+            } else {
+                Err("message does not start with BeginString".to_string())
+            };
+        }
+        if !buf.starts_with(begin) {
+            return Err("message does not start with BeginString".to_string());
+        }
+
+        let rest = &buf[begin.len()..];
+        let Some(len_end) = rest.iter().position(|&b| b == SOH) else {
+            return if rest.len() > 10 {
+                Err("BodyLength is not terminated".to_string())
+            } else {
+                Ok(None)
+            };
+        };
+        let body_len: usize = std::str::from_utf8(&rest[..len_end])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or("BodyLength is not a number")?;
+        let body_start = begin.len() + len_end + 1;
+        let body_end = body_start + body_len;
+        let total = body_end + 7;
+        if buf.len() < total {
+            return Ok(None);
+        }
+
+        let trailer = &buf[body_end..total];
+        if !trailer.starts_with(b"10=") || trailer[6] != SOH {
+            return Err("CheckSum is not where BodyLength says".to_string());
+        }
+        let expected: u8 = std::str::from_utf8(&trailer[3..6])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or("CheckSum is not a number")?;
+        if checksum(&buf[..body_end]) != expected {
+            return Err("CheckSum mismatch".to_string());
+        }
+
+        let body = &buf[body_start..body_end];
+        if body.last() != Some(&SOH) {
+            return Err("body does not end with SOH".to_string());
+        }
+        let mut fields = Vec::new();
+        for field in body[..body.len() - 1].split(|&b| b == SOH) {
+            let field = std::str::from_utf8(field).map_err(|e| e.to_string())?;
+            let (tag, value) = field
+                .split_once('=')
+                .ok_or_else(|| format!("field '{}' has no '='", field))?;
+            let tag = tag.parse().map_err(|_| format!("invalid tag '{}'", tag))?;
+            fields.push((tag, value.to_string()));
+        }
+        if fields.first().map(|(t, _)| *t) != Some(tag::MSG_TYPE) {
+            return Err("MsgType is not the third field".to_string());
+        }
+        Ok(Some((FixMessage { fields }, total)))
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_what_it_encodes_and_rejects_corruption() {
+        let msg = FixMessage::new(msg_type::HEARTBEAT)
+            .with(tag::SENDER_COMP_ID, "A")
+            .with(tag::TARGET_COMP_ID, "B")
+            .with(tag::MSG_SEQ_NUM, 7);
+        let mut wire = msg.encode();
+        assert!(wire.starts_with(b"8=FIX.4.4\x019="));
+
+        for cut in 0..wire.len() {
+            assert_eq!(FixMessage::decode(&wire[..cut]), Ok(None));
+        }
+        wire.extend_from_slice(b"8=FIX");
+        let (decoded, used) = FixMessage::decode(&wire).unwrap().unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!(used, wire.len() - 5);
+
+        wire[20] ^= 1;
+        assert!(FixMessage::decode(&wire).is_err());
+    }
+}
@@ -0,0 +1,1061 @@
+//! FIX 4.4 order-entry acceptor over a [`MatchingEngine`].
+//!
+//! The session layer handles Logon, Heartbeat, TestRequest, ResendRequest,
+//! SequenceReset and Logout. Sequence numbers and sent messages are kept per
+//! counterparty `SenderCompID`, so they survive reconnects and fills that
+//! happen while a session is offline can be recovered with a ResendRequest.
+//!
+//! Application messages map onto the engine:
+//!
+//! | Message                       | Engine call    | Answer                              |
+//! |-------------------------------|----------------|-------------------------------------|
+//! | `NewOrderSingle(D)`           | `place_order`  | `ExecutionReport` New, Trade, ...   |
+//! | `OrderCancelRequest(F)`       | `cancel_order` | `ExecutionReport` Canceled or `9`   |
+//! | `OrderCancelReplaceRequest(G)`| `amend_order`  | `ExecutionReport` Replaced or `9`   |
+//!
+//! Resting orders are reported to the session that entered them whenever
+//! someone else trades against them.
+
+pub mod message;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use log::{debug, info, warn};
+use rust_decimal::Decimal;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+use crate::{
+    MatchingEngine, OrderError, OrderId, OrderRequest, OrderType, Price, Quantity, Side,
+    TradeExecution, TradingPair,
+};
+use message::{FixMessage, msg_type, tag};
+
+const LOGON_TIMEOUT: Duration = Duration::from_secs(10);
+const TICK: Duration = Duration::from_millis(100);
+/// Largest amount of unparsed input tolerated before dropping a connection.
+const MAX_BUFFERED: usize = 1 << 16;
+
+// SessionRejectReason(373)
+const REQUIRED_TAG_MISSING: u32 = 1;
+const VALUE_INCORRECT: u32 = 5;
+const COMP_ID_PROBLEM: u32 = 9;
+const INVALID_MSG_TYPE: u32 = 11;
+
+// OrdRejReason(103)
+const UNKNOWN_SYMBOL: u32 = 1;
+const EXCHANGE_CLOSED: u32 = 2;
+const ORDER_EXCEEDS_LIMIT: u32 = 3;
+const DUPLICATE_ORDER: u32 = 6;
+const INCORRECT_QUANTITY: u32 = 13;
+const OTHER: u32 = 99;
+
+// CxlRejReason(102)
+const TOO_LATE_TO_CANCEL: u32 = 0;
+const UNKNOWN_ORDER: u32 = 1;
+
+fn utc_timestamp() -> String {
+    Utc::now().format("%Y%m%d-%H:%M:%S%.3f").to_string()
+}
+
+/// Persistent state of one counterparty, kept across connections.
+struct SessionStore {
+    next_out: u64,
+    next_in: u64,
+    sent: BTreeMap<u64, FixMessage>,
+    /// Writer of the connection currently logged on as this counterparty.
+    outbox: Option<mpsc::UnboundedSender<Vec<u8>>>,
+}
+
+impl SessionStore {
+    fn new() -> Self {
+        Self {
+            next_out: 1,
+            next_in: 1,
+            sent: BTreeMap::new(),
+            outbox: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FixOrder {
+    owner: String,
+    cl_ord_id: String,
+    symbol: String,
+    pair: TradingPair,
+    side: Side,
+    ord_type: &'static str,
+    time_in_force: Option<String>,
+    price: Option<Price>,
+    order_qty: Quantity,
+    cum_qty: Quantity,
+    notional: Decimal,
+    open: bool,
+}
+
+impl FixOrder {
+    fn fill(&mut self, qty: Quantity, price: Price) {
+        self.cum_qty += qty;
+        self.notional += qty * price;
+        if self.cum_qty >= self.order_qty {
+            self.open = false;
+        }
+    }
+
+    fn ord_status(&self) -> &'static str {
+        if self.cum_qty >= self.order_qty {
+            "2"
+        } else if !self.open {
+            "4"
+        } else if self.cum_qty > Decimal::ZERO {
+            "1"
+        } else {
+            "0"
+        }
+    }
+
+    fn leaves_qty(&self) -> Quantity {
+        if self.open {
+            self.order_qty - self.cum_qty
+        } else {
+            Quantity::ZERO
+        }
+    }
+
+    fn avg_px(&self) -> Price {
+        if self.cum_qty.is_zero() {
+            Price::ZERO
+        } else {
+            self.notional / self.cum_qty
+        }
+    }
+}
+
+fn side_code(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "1",
+        Side::Ask => "2",
+    }
+}
+
+/// Fields of a validated NewOrderSingle.
+struct NewOrder {
+    cl_ord_id: String,
+    symbol: String,
+    pair: TradingPair,
+    request: OrderRequest,
+    ord_type: &'static str,
+    time_in_force: Option<String>,
+}
+
+struct Core {
+    engine: MatchingEngine,
+    comp_id: String,
+    sessions: HashMap<String, SessionStore>,
+    orders: HashMap<OrderId, FixOrder>,
+    cl_ord_ids: HashMap<(String, String), OrderId>,
+    next_exec_id: u64,
+}
+
+impl Core {
+    fn session(&mut self, peer: &str) -> &mut SessionStore {
+        self.sessions
+            .entry(peer.to_string())
+            .or_insert_with(SessionStore::new)
+    }
+
+    /// Stamps the standard header onto `msg`, logs it for resends and
+    /// queues it on the peer's connection if it is logged on.
+    fn send(&mut self, peer: &str, msg: FixMessage) {
+        let comp_id = self.comp_id.clone();
+        let session = self.session(peer);
+        let seq = session.next_out;
+        session.next_out += 1;
+        let mut out = FixMessage::new(msg.msg_type())
+            .with(tag::SENDER_COMP_ID, comp_id)
+            .with(tag::TARGET_COMP_ID, peer)
+            .with(tag::MSG_SEQ_NUM, seq)
+            .with(tag::SENDING_TIME, utc_timestamp());
+        for (t, v) in msg.body() {
+            out.push(*t, v);
+        }
+        if let Some(outbox) = &session.outbox {
+            let _ = outbox.send(out.encode());
+        }
+        session.sent.insert(seq, out);
+    }
+
+    /// Answers a ResendRequest: application messages go out again with
+    /// `PossDupFlag=Y`, runs of session messages become one gap fill.
+    fn resend(&mut self, peer: &str, begin: u64, end: u64) {
+        let comp_id = self.comp_id.clone();
+        let session = self.session(peer);
+        let Some(outbox) = session.outbox.clone() else {
+            return;
+        };
+        let last = match end {
+            0 => session.next_out - 1,
+            end => end.min(session.next_out - 1),
+        };
+        let gap_fill = |seq: u64, new_seq: u64| {
+            FixMessage::new(msg_type::SEQUENCE_RESET)
+                .with(tag::SENDER_COMP_ID, &comp_id)
+                .with(tag::TARGET_COMP_ID, peer)
+                .with(tag::MSG_SEQ_NUM, seq)
+                .with(tag::POSS_DUP_FLAG, "Y")
+                .with(tag::SENDING_TIME, utc_timestamp())
+                .with(tag::GAP_FILL_FLAG, "Y")
+                .with(tag::NEW_SEQ_NO, new_seq)
+                .encode()
+        };
+
+        let mut gap_start = None;
+        for seq in begin.max(1)..=last {
+            match session.sent.get(&seq) {
+                Some(sent) if !msg_type::is_admin(sent.msg_type()) => {
+                    if let Some(start) = gap_start.take() {
+                        let _ = outbox.send(gap_fill(start, seq));
+                    }
+                    let mut dup = FixMessage::new(sent.msg_type())
+                        .with(tag::SENDER_COMP_ID, &comp_id)
+                        .with(tag::TARGET_COMP_ID, peer)
+                        .with(tag::MSG_SEQ_NUM, seq)
+                        .with(tag::POSS_DUP_FLAG, "Y")
+                        .with(tag::SENDING_TIME, utc_timestamp())
+                        .with(
+                            tag::ORIG_SENDING_TIME,
+                            sent.get(tag::SENDING_TIME).unwrap_or_default(),
+                        );
+                    for (t, v) in sent.body() {
+                        dup.push(*t, v);
+                    }
+                    let _ = outbox.send(dup.encode());
+                }
+                _ => {
+                    gap_start.get_or_insert(seq);
+                }
+            }
+        }
+        if let Some(start) = gap_start {
+            let _ = outbox.send(gap_fill(start, last + 1));
+        }
+    }
+
+    fn session_reject(
+        &mut self,
+        peer: &str,
+        msg: &FixMessage,
+        reason: u32,
+        ref_tag: Option<u32>,
+        text: &str,
+    ) {
+        let mut reject = FixMessage::new(msg_type::REJECT);
+        if let Some(seq) = msg.seq_num() {
+            reject.push(tag::REF_SEQ_NUM, seq);
+        }
+        if let Some(ref_tag) = ref_tag {
+            reject.push(tag::REF_TAG_ID, ref_tag);
+        }
+        reject.push(tag::REF_MSG_TYPE, msg.msg_type());
+        reject.push(tag::SESSION_REJECT_REASON, reason);
+        reject.push(tag::TEXT, text);
+        self.send(peer, reject);
+    }
+
+    /// Sends a session Reject for the first missing tag, if any.
+    fn require(&mut self, peer: &str, msg: &FixMessage, tags: &[u32]) -> bool {
+        match tags.iter().find(|&&t| msg.get(t).is_none()) {
+            Some(&missing) => {
+                self.session_reject(
+                    peer,
+                    msg,
+                    REQUIRED_TAG_MISSING,
+                    Some(missing),
+                    "Required tag missing",
+                );
+                false
+            }
+            None => true,
+        }
+    }
+
+    fn logout(&mut self, peer: &str, text: &str) {
+        self.send(
+            peer,
+            FixMessage::new(msg_type::LOGOUT).with(tag::TEXT, text),
+        );
+    }
+
+    fn next_exec_id(&mut self) -> u64 {
+        self.next_exec_id += 1;
+        self.next_exec_id
+    }
+
+    /// Sends an ExecutionReport for `order_id` to the session that owns it.
+    fn report(
+        &mut self,
+        order_id: OrderId,
+        exec_type: &str,
+        last: Option<(Quantity, Price)>,
+        orig_cl_ord_id: Option<&str>,
+    ) {
+        let exec_id = self.next_exec_id();
+        let order = &self.orders[&order_id];
+        let mut msg = FixMessage::new(msg_type::EXECUTION_REPORT)
+            .with(tag::ORDER_ID, order_id)
+            .with(tag::CL_ORD_ID, &order.cl_ord_id);
+        if let Some(orig) = orig_cl_ord_id {
+            msg.push(tag::ORIG_CL_ORD_ID, orig);
+        }
+        msg.push(tag::EXEC_ID, exec_id);
+        msg.push(tag::EXEC_TYPE, exec_type);
+        msg.push(tag::ORD_STATUS, order.ord_status());
+        msg.push(tag::SYMBOL, &order.symbol);
+        msg.push(tag::SIDE, side_code(order.side));
+        msg.push(tag::ORDER_QTY, order.order_qty);
+        msg.push(tag::ORD_TYPE, order.ord_type);
+        if let Some(price) = order.price {
+            msg.push(tag::PRICE, price);
+        }
+        if let Some(tif) = &order.time_in_force {
+            msg.push(tag::TIME_IN_FORCE, tif);
+        }
+        if let Some((qty, price)) = last {
+            msg.push(tag::LAST_QTY, qty);
+            msg.push(tag::LAST_PX, price);
+        }
+        msg.push(tag::LEAVES_QTY, order.leaves_qty());
+        msg.push(tag::CUM_QTY, order.cum_qty);
+        msg.push(tag::AVG_PX, order.avg_px());
+        msg.push(tag::TRANSACT_TIME, utc_timestamp());
+        let owner = order.owner.clone();
+        self.send(&owner, msg);
+    }
+
+    fn reject_order(&mut self, peer: &str, msg: &FixMessage, reason: u32, text: &str) {
+        let exec_id = self.next_exec_id();
+        let mut reject = FixMessage::new(msg_type::EXECUTION_REPORT)
+            .with(tag::ORDER_ID, "NONE")
+            .with(tag::CL_ORD_ID, msg.get(tag::CL_ORD_ID).unwrap_or_default())
+            .with(tag::EXEC_ID, exec_id)
+            .with(tag::EXEC_TYPE, "8")
+            .with(tag::ORD_STATUS, "8");
+        for t in [tag::SYMBOL, tag::SIDE, tag::ORDER_QTY, tag::ORD_TYPE] {
+            if let Some(v) = msg.get(t) {
+                reject.push(t, v);
+            }
+        }
+        reject.push(tag::LEAVES_QTY, 0);
+        reject.push(tag::CUM_QTY, 0);
+        reject.push(tag::AVG_PX, 0);
+        reject.push(tag::ORD_REJ_REASON, reason);
+        reject.push(tag::TEXT, text);
+        reject.push(tag::TRANSACT_TIME, utc_timestamp());
+        self.send(peer, reject);
+    }
+
+    fn cancel_reject(
+        &mut self,
+        peer: &str,
+        msg: &FixMessage,
+        order_id: Option<OrderId>,
+        reason: u32,
+        text: &str,
+    ) {
+        let status = order_id
+            .and_then(|id| self.orders.get(&id))
+            .map_or("8", FixOrder::ord_status);
+        let response_to = match msg.msg_type() {
+            msg_type::ORDER_CANCEL_REQUEST => "1",
+            _ => "2",
+        };
+        let reject = FixMessage::new(msg_type::ORDER_CANCEL_REJECT)
+            .with(
+                tag::ORDER_ID,
+                order_id.map_or_else(|| "NONE".to_string(), |id| id.to_string()),
+            )
+            .with(tag::CL_ORD_ID, msg.get(tag::CL_ORD_ID).unwrap_or_default())
+            .with(
+                tag::ORIG_CL_ORD_ID,
+                msg.get(tag::ORIG_CL_ORD_ID).unwrap_or_default(),
+            )
+            .with(tag::ORD_STATUS, status)
+            .with(tag::CXL_REJ_RESPONSE_TO, response_to)
+            .with(tag::CXL_REJ_REASON, reason)
+            .with(tag::TEXT, text);
+        self.send(peer, reject);
+    }
+
+    /// Updates and reports both sides of each execution that belong to
+    /// FIX sessions.
+    fn apply_executions(&mut self, executions: &[TradeExecution]) {
+        for execution in executions {
+            for id in [execution.taker_order_id, execution.maker_order_id] {
+                if let Some(order) = self.orders.get_mut(&id) {
+                    order.fill(execution.qty, execution.price);
+                    self.report(id, "F", Some((execution.qty, execution.price)), None);
+                }
+            }
+        }
+    }
+
+    fn validate_new_order(&self, peer: &str, msg: &FixMessage) -> Result<NewOrder, (u32, String)> {
+        let cl_ord_id = msg.get(tag::CL_ORD_ID).unwrap_or_default().to_string();
+        if self
+            .cl_ord_ids
+            .contains_key(&(peer.to_string(), cl_ord_id.clone()))
+        {
+            return Err((DUPLICATE_ORDER, format!("duplicate ClOrdID {}", cl_ord_id)));
+        }
+        let symbol = msg.get(tag::SYMBOL).unwrap_or_default().to_string();
+        let pair: TradingPair = symbol.parse().map_err(|e| (UNKNOWN_SYMBOL, e))?;
+        if !self.engine.market_exists(&pair) {
+            return Err((UNKNOWN_SYMBOL, format!("unknown symbol {}", symbol)));
+        }
+        let side = match msg.get(tag::SIDE) {
+            Some("1") => Side::Bid,
+            Some("2") => Side::Ask,
+            _ => return Err((OTHER, "Side must be 1 (buy) or 2 (sell)".to_string())),
+        };
+        let qty = msg
+            .parse::<Quantity>(tag::ORDER_QTY)
+            .filter(|qty| *qty > Decimal::ZERO)
+            .ok_or_else(|| (INCORRECT_QUANTITY, "OrderQty must be positive".to_string()))?;
+
+        let time_in_force = msg.get(tag::TIME_IN_FORCE).map(str::to_string);
+        let (ord_type, order_type) = match msg.get(tag::ORD_TYPE) {
+            Some("1") => ("1", OrderType::Market),
+            Some("2") => {
+                let price = msg
+                    .parse::<Price>(tag::PRICE)
+                    .filter(|price| *price > Decimal::ZERO)
+                    .ok_or_else(|| (OTHER, "limit orders need a positive Price".to_string()))?;
+                let order_type = match time_in_force.as_deref() {
+                    None | Some("0") | Some("1") => OrderType::Limit(price),
+                    Some("3") => OrderType::IOC(price),
+                    Some("4") => OrderType::FOK(price),
+                    Some(tif) => return Err((OTHER, format!("unsupported TimeInForce {}", tif))),
+                };
+                ("2", order_type)
+            }
+            other => {
+                return Err((
+                    OTHER,
+                    format!("unsupported OrdType {}", other.unwrap_or_default()),
+                ));
+            }
+        };
+
+        Ok(NewOrder {
+            cl_ord_id,
+            symbol,
+            pair,
+            request: OrderRequest::new(side, qty, order_type),
+            ord_type,
+            time_in_force,
+        })
+    }
+
+    fn new_order_single(&mut self, peer: &str, msg: &FixMessage) {
+        let required = [
+            tag::CL_ORD_ID,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::ORDER_QTY,
+            tag::ORD_TYPE,
+        ];
+        if !self.require(peer, msg, &required) {
+            return;
+        }
+        let order = match self.validate_new_order(peer, msg) {
+            Ok(order) => order,
+            Err((reason, text)) => return self.reject_order(peer, msg, reason, &text),
+        };
+        let (result, executions) = match self.engine.place_order(&order.pair, order.request) {
+            Ok(placed) => placed,
+            Err(OrderError::Risk(rejection)) => {
+                return self.reject_order(peer, msg, ORDER_EXCEEDS_LIMIT, &rejection.to_string());
+            }
+            Err(e @ OrderError::MarketState { .. }) => {
+                return self.reject_order(peer, msg, EXCHANGE_CLOSED, &e.to_string());
+            }
+            Err(e) => return self.reject_order(peer, msg, OTHER, &e.to_string()),
+        };
+
+        let id = result.get_id();
+        self.cl_ord_ids
+            .insert((peer.to_string(), order.cl_ord_id.clone()), id);
+        self.orders.insert(
+            id,
+            FixOrder {
+                owner: peer.to_string(),
+                cl_ord_id: order.cl_ord_id,
+                symbol: order.symbol,
+                pair: order.pair.clone(),
+                side: order.request.side,
+                ord_type: order.ord_type,
+                time_in_force: order.time_in_force,
+                price: order.request.price(),
+                order_qty: order.request.qty,
+                cum_qty: Quantity::ZERO,
+                notional: Decimal::ZERO,
+                open: true,
+            },
+        );
+        self.report(id, "0", None, None);
+        self.apply_executions(&executions);
+
+        // IOC, FOK and market remainders never rest.
+        let resting = matches!(self.engine.get_order(&order.pair, id), Ok(Some(_)));
+        if !resting && let Some(order) = self.orders.get_mut(&id).filter(|o| o.open) {
+            order.open = false;
+            self.report(id, "4", None, None);
+        }
+    }
+
+    /// Resolves `OrigClOrdID(41)` to an open order of `peer`, or sends an
+    /// OrderCancelReject.
+    fn open_order(&mut self, peer: &str, msg: &FixMessage) -> Option<OrderId> {
+        let orig = msg.get(tag::ORIG_CL_ORD_ID).unwrap_or_default();
+        let cl_ord_id = msg.get(tag::CL_ORD_ID).unwrap_or_default();
+        let Some(&id) = self.cl_ord_ids.get(&(peer.to_string(), orig.to_string())) else {
+            self.cancel_reject(peer, msg, None, UNKNOWN_ORDER, "unknown order");
+            return None;
+        };
+        let order = &self.orders[&id];
+        let text = if !order.open {
+            "order is not open"
+        } else if msg.get(tag::SYMBOL) != Some(order.symbol.as_str())
+            || msg.get(tag::SIDE) != Some(side_code(order.side))
+        {
+            "Symbol or Side does not match the order"
+        } else if self
+            .cl_ord_ids
+            .contains_key(&(peer.to_string(), cl_ord_id.to_string()))
+        {
+            "duplicate ClOrdID"
+        } else {
+            return Some(id);
+        };
+        let reason = if order.open {
+            OTHER
+        } else {
+            TOO_LATE_TO_CANCEL
+        };
+        self.cancel_reject(peer, msg, Some(id), reason, text);
+        None
+    }
+
+    fn rename(&mut self, peer: &str, id: OrderId, cl_ord_id: &str) {
+        self.cl_ord_ids
+            .insert((peer.to_string(), cl_ord_id.to_string()), id);
+        if let Some(order) = self.orders.get_mut(&id) {
+            order.cl_ord_id = cl_ord_id.to_string();
+        }
+    }
+
+    fn order_cancel_request(&mut self, peer: &str, msg: &FixMessage) {
+        let required = [tag::CL_ORD_ID, tag::ORIG_CL_ORD_ID, tag::SYMBOL, tag::SIDE];
+        if !self.require(peer, msg, &required) {
+            return;
+        }
+        let Some(id) = self.open_order(peer, msg) else {
+            return;
+        };
+        let pair = self.orders[&id].pair.clone();
+        match self.engine.cancel_order(&pair, id) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return self.cancel_reject(
+                    peer,
+                    msg,
+                    Some(id),
+                    TOO_LATE_TO_CANCEL,
+                    "order is gone",
+                );
+            }
+            Err(e) => return self.cancel_reject(peer, msg, Some(id), OTHER, &e.to_string()),
+        }
+        if let Some(order) = self.orders.get_mut(&id) {
+            order.open = false;
+        }
+        let orig = msg.get(tag::ORIG_CL_ORD_ID).unwrap_or_default();
+        self.rename(peer, id, msg.get(tag::CL_ORD_ID).unwrap_or_default());
+        self.report(id, "4", None, Some(orig));
+    }
+
+    fn order_cancel_replace_request(&mut self, peer: &str, msg: &FixMessage) {
+        let required = [
+            tag::CL_ORD_ID,
+            tag::ORIG_CL_ORD_ID,
+            tag::SYMBOL,
+            tag::SIDE,
+            tag::ORDER_QTY,
+            tag::ORD_TYPE,
+        ];
+        if !self.require(peer, msg, &required) {
+            return;
+        }
+        let Some(id) = self.open_order(peer, msg) else {
+            return;
+        };
+        let order = &self.orders[&id];
+        if order.ord_type != "2" || msg.get(tag::ORD_TYPE) != Some("2") {
+            return self.cancel_reject(
+                peer,
+                msg,
+                Some(id),
+                OTHER,
+                "only limit orders can be replaced",
+            );
+        }
+        let Some(price) = msg
+            .parse::<Price>(tag::PRICE)
+            .filter(|p| *p > Decimal::ZERO)
+        else {
+            return self.cancel_reject(peer, msg, Some(id), OTHER, "Price must be positive");
+        };
+        let Some(qty) = msg
+            .parse::<Quantity>(tag::ORDER_QTY)
+            .filter(|qty| *qty > order.cum_qty)
+        else {
+            return self.cancel_reject(peer, msg, Some(id), OTHER, "OrderQty must exceed CumQty");
+        };
+
+        let pair = order.pair.clone();
+        // OrderQty(38) includes what already filled, while the engine takes
+        // the new open quantity and keeps the fills, so both end up with the
+        // same initial quantity.
+        let remaining = qty - order.cum_qty;
+        let executions = match self.engine.amend_order(&pair, id, price, remaining) {
+            Ok(Some((_, executions))) => executions,
+            Ok(None) => {
+                return self.cancel_reject(
+                    peer,
+                    msg,
+                    Some(id),
+                    TOO_LATE_TO_CANCEL,
+                    "order is gone",
+                );
+            }
+            Err(e) => return self.cancel_reject(peer, msg, Some(id), OTHER, &e.to_string()),
+        };
+        if let Some(order) = self.orders.get_mut(&id) {
+            order.order_qty = qty;
+            order.price = Some(price);
+        }
+        let orig = msg.get(tag::ORIG_CL_ORD_ID).unwrap_or_default();
+        self.rename(peer, id, msg.get(tag::CL_ORD_ID).unwrap_or_default());
+        self.report(id, "5", None, Some(orig));
+        self.apply_executions(&executions);
+    }
+}
+
+struct Shared {
+    core: Mutex<Core>,
+}
+
+/// FIX 4.4 acceptor exposing a [`MatchingEngine`]. Cheap to clone.
+#[derive(Clone)]
+pub struct FixGateway {
+    shared: Arc<Shared>,
+}
+
+enum Flow {
+    Continue,
+    Disconnect,
+}
+
+/// State of one TCP connection.
+struct Connection {
+    outbox: mpsc::UnboundedSender<Vec<u8>>,
+    /// Counterparty `SenderCompID`, once logged on.
+    peer: Option<String>,
+    heartbeat: Option<Duration>,
+    connected_at: Instant,
+    last_received: Instant,
+    last_sent: Instant,
+    test_request: Option<Instant>,
+    /// Highest sequence number already covered by our ResendRequest.
+    resend_until: u64,
+}
+
+impl FixGateway {
+    /// Creates an acceptor answering as `comp_id`.
+    pub fn new(engine: MatchingEngine, comp_id: impl Into<String>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                core: Mutex::new(Core {
+                    engine,
+                    comp_id: comp_id.into(),
+                    sessions: HashMap::new(),
+                    orders: HashMap::new(),
+                    cl_ord_ids: HashMap::new(),
+                    next_exec_id: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Runs `f` against the engine, e.g. to add markets while serving.
+    pub fn with_engine<T>(&self, f: impl FnOnce(&mut MatchingEngine) -> T) -> T {
+        f(&mut self.shared.core.lock().unwrap().engine)
+    }
+
+    /// Accepts connections until the listener fails.
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let gateway = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = gateway.handle_connection(stream).await {
+                    debug!("FIX connection {} closed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(self, stream: TcpStream) -> std::io::Result<()> {
+        let (mut reader, mut writer) = stream.into_split();
+        let (outbox, mut outgoing) = mpsc::unbounded_channel::<Vec<u8>>();
+        let now = Instant::now();
+        let mut conn = Connection {
+            outbox,
+            peer: None,
+            heartbeat: None,
+            connected_at: now,
+            last_received: now,
+            last_sent: now,
+            test_request: None,
+            resend_until: 0,
+        };
+        let mut buf = Vec::with_capacity(4096);
+        let mut ticker = tokio::time::interval(TICK);
+
+        let result = loop {
+            let flow = tokio::select! {
+                read = reader.read_buf(&mut buf) => match read {
+                    Ok(0) => break Ok(()),
+                    Ok(_) => {
+                        conn.last_received = Instant::now();
+                        conn.test_request = None;
+                        self.drain(&mut conn, &mut buf)
+                    }
+                    Err(e) => break Err(e),
+                },
+                Some(bytes) = outgoing.recv() => {
+                    if let Err(e) = writer.write_all(&bytes).await {
+                        break Err(e);
+                    }
+                    conn.last_sent = Instant::now();
+                    Flow::Continue
+                }
+                _ = ticker.tick() => self.on_tick(&mut conn),
+            };
+            if let Flow::Disconnect = flow {
+                break Ok(());
+            }
+        };
+
+        if let Some(peer) = &conn.peer {
+            let mut core = self.shared.core.lock().unwrap();
+            core.session(peer).outbox = None;
+            info!("FIX session {} disconnected", peer);
+        }
+        // Flush whatever was queued before the session went away, such as a
+        // Logout.
+        drop(conn);
+        while let Some(bytes) = outgoing.recv().await {
+            writer.write_all(&bytes).await?;
+        }
+        writer.shutdown().await?;
+        result
+    }
+
+    /// Handles every complete message in `buf`.
+    fn drain(&self, conn: &mut Connection, buf: &mut Vec<u8>) -> Flow {
+        loop {
+            match FixMessage::decode(buf) {
+                Ok(Some((msg, used))) => {
+                    buf.drain(..used);
+                    if let Flow::Disconnect = self.handle_message(conn, msg) {
+                        return Flow::Disconnect;
+                    }
+                }
+                Ok(None) if buf.len() > MAX_BUFFERED => {
+                    warn!("FIX message exceeds {} bytes", MAX_BUFFERED);
+                    return Flow::Disconnect;
+                }
+                Ok(None) => return Flow::Continue,
+                Err(e) => {
+                    // Garbled input is dropped without consuming a sequence
+                    // number; resume at the next BeginString.
+                    warn!("dropping garbled FIX data: {}", e);
+                    let next = buf
+                        .windows(5)
+                        .skip(1)
+                        .position(|w| w == b"8=FIX")
+                        .map_or(buf.len(), |p| p + 1);
+                    buf.drain(..next);
+                }
+            }
+        }
+    }
+
+    fn on_tick(&self, conn: &mut Connection) -> Flow {
+        let Some(peer) = conn.peer.clone() else {
+            return if conn.connected_at.elapsed() > LOGON_TIMEOUT {
+                warn!("no Logon within {:?}", LOGON_TIMEOUT);
+                Flow::Disconnect
+            } else {
+                Flow::Continue
+            };
+        };
+        let Some(heartbeat) = conn.heartbeat else {
+            return Flow::Continue;
+        };
+
+        let mut core = self.shared.core.lock().unwrap();
+        match conn.test_request {
+            Some(sent_at) if sent_at.elapsed() >= heartbeat => {
+                warn!("FIX session {} did not answer a TestRequest", peer);
+                return Flow::Disconnect;
+            }
+            Some(_) => {}
+            None if conn.last_received.elapsed() >= heartbeat + heartbeat / 5 => {
+                let test_req_id = format!("TEST-{}", core.session(&peer).next_out);
+                core.send(
+                    &peer,
+                    FixMessage::new(msg_type::TEST_REQUEST).with(tag::TEST_REQ_ID, test_req_id),
+                );
+                conn.test_request = Some(Instant::now());
+                conn.last_sent = Instant::now();
+            }
+            None => {}
+        }
+        if conn.last_sent.elapsed() >= heartbeat {
+            core.send(&peer, FixMessage::new(msg_type::HEARTBEAT));
+            conn.last_sent = Instant::now();
+        }
+        Flow::Continue
+    }
+
+    fn logon(&self, core: &mut Core, conn: &mut Connection, msg: &FixMessage) -> Flow {
+        if msg.msg_type() != msg_type::LOGON {
+            warn!("first FIX message was {} rather than Logon", msg.msg_type());
+            return Flow::Disconnect;
+        }
+        let (Some(peer), Some(seq), Some(heartbeat)) = (
+            msg.get(tag::SENDER_COMP_ID),
+            msg.seq_num(),
+            msg.parse::<u64>(tag::HEART_BT_INT),
+        ) else {
+            warn!("Logon without SenderCompID, MsgSeqNum or HeartBtInt");
+            return Flow::Disconnect;
+        };
+        if msg.get(tag::TARGET_COMP_ID) != Some(core.comp_id.as_str()) {
+            warn!("Logon from {} addressed to another TargetCompID", peer);
+            return Flow::Disconnect;
+        }
+
+        let reset = msg.flag(tag::RESET_SEQ_NUM_FLAG);
+        let session = core.session(peer);
+        if session.outbox.is_some() {
+            warn!("{} is already logged on", peer);
+            return Flow::Disconnect;
+        }
+        if reset {
+            *session = SessionStore::new();
+        }
+        session.outbox = Some(conn.outbox.clone());
+        conn.peer = Some(peer.to_string());
+
+        let expected = session.next_in;
+        if seq < expected {
+            core.logout(
+                peer,
+                &format!(
+                    "MsgSeqNum too low, expecting {} but received {}",
+                    expected, seq
+                ),
+            );
+            return Flow::Disconnect;
+        }
+
+        conn.heartbeat = (heartbeat > 0).then(|| Duration::from_secs(heartbeat));
+        let mut reply = FixMessage::new(msg_type::LOGON)
+            .with(tag::ENCRYPT_METHOD, 0)
+            .with(tag::HEART_BT_INT, heartbeat);
+        if reset {
+            reply.push(tag::RESET_SEQ_NUM_FLAG, "Y");
+        }
+        core.send(peer, reply);
+        if seq == expected {
+            core.session(peer).next_in += 1;
+        } else {
+            self.request_resend(core, conn, peer, expected, seq);
+        }
+        info!("FIX session {} logged on", peer);
+        Flow::Continue
+    }
+
+    fn request_resend(
+        &self,
+        core: &mut Core,
+        conn: &mut Connection,
+        peer: &str,
+        expected: u64,
+        seq: u64,
+    ) {
+        if seq > conn.resend_until {
+            core.send(
+                peer,
+                FixMessage::new(msg_type::RESEND_REQUEST)
+                    .with(tag::BEGIN_SEQ_NO, expected)
+                    .with(tag::END_SEQ_NO, 0),
+            );
+            conn.resend_until = seq;
+        }
+    }
+
+    fn handle_message(&self, conn: &mut Connection, msg: FixMessage) -> Flow {
+        let mut core = self.shared.core.lock().unwrap();
+        let core = &mut *core;
+        let Some(peer) = conn.peer.clone() else {
+            return self.logon(core, conn, &msg);
+        };
+        let peer = peer.as_str();
+
+        let Some(seq) = msg.seq_num() else {
+            core.logout(peer, "MsgSeqNum missing");
+            return Flow::Disconnect;
+        };
+        if msg.get(tag::SENDER_COMP_ID) != Some(peer)
+            || msg.get(tag::TARGET_COMP_ID) != Some(core.comp_id.as_str())
+        {
+            core.session_reject(peer, &msg, COMP_ID_PROBLEM, None, "CompID problem");
+            core.logout(peer, "CompID problem");
+            return Flow::Disconnect;
+        }
+
+        let session = core.session(peer);
+        let expected = session.next_in;
+        // SequenceReset in reset mode applies whatever its MsgSeqNum.
+        if msg.msg_type() == msg_type::SEQUENCE_RESET && !msg.flag(tag::GAP_FILL_FLAG) {
+            match msg.parse::<u64>(tag::NEW_SEQ_NO) {
+                Some(new_seq) if new_seq >= expected => session.next_in = new_seq,
+                _ => core.session_reject(
+                    peer,
+                    &msg,
+                    VALUE_INCORRECT,
+                    Some(tag::NEW_SEQ_NO),
+                    "NewSeqNo may not go backwards",
+                ),
+            }
+            return Flow::Continue;
+        }
+        if seq < expected {
+            if msg.flag(tag::POSS_DUP_FLAG) {
+                return Flow::Continue;
+            }
+            core.logout(
+                peer,
+                &format!(
+                    "MsgSeqNum too low, expecting {} but received {}",
+                    expected, seq
+                ),
+            );
+            return Flow::Disconnect;
+        }
+        if seq > expected {
+            self.request_resend(core, conn, peer, expected, seq);
+            // Resends and logouts are honoured even across a gap.
+            return match msg.msg_type() {
+                msg_type::RESEND_REQUEST => {
+                    self.on_resend_request(core, peer, &msg);
+                    Flow::Continue
+                }
+                msg_type::LOGOUT => {
+                    core.logout(peer, "Logout acknowledged");
+                    Flow::Disconnect
+                }
+                _ => Flow::Continue,
+            };
+        }
+        session.next_in += 1;
+
+        match msg.msg_type() {
+            msg_type::HEARTBEAT | msg_type::REJECT => {}
+            msg_type::TEST_REQUEST => {
+                if core.require(peer, &msg, &[tag::TEST_REQ_ID]) {
+                    let test_req_id = msg.get(tag::TEST_REQ_ID).unwrap_or_default();
+                    core.send(
+                        peer,
+                        FixMessage::new(msg_type::HEARTBEAT).with(tag::TEST_REQ_ID, test_req_id),
+                    );
+                }
+            }
+            msg_type::RESEND_REQUEST => self.on_resend_request(core, peer, &msg),
+            msg_type::SEQUENCE_RESET => match msg.parse::<u64>(tag::NEW_SEQ_NO) {
+                Some(new_seq) if new_seq > seq => core.session(peer).next_in = new_seq,
+                _ => core.session_reject(
+                    peer,
+                    &msg,
+                    VALUE_INCORRECT,
+                    Some(tag::NEW_SEQ_NO),
+                    "NewSeqNo must exceed MsgSeqNum",
+                ),
+            },
+            msg_type::LOGOUT => {
+                core.logout(peer, "Logout acknowledged");
+                return Flow::Disconnect;
+            }
+            msg_type::LOGON => {
+                core.session_reject(peer, &msg, OTHER, None, "already logged on");
+            }
+            msg_type::NEW_ORDER_SINGLE => core.new_order_single(peer, &msg),
+            msg_type::ORDER_CANCEL_REQUEST => core.order_cancel_request(peer, &msg),
+            msg_type::ORDER_CANCEL_REPLACE_REQUEST => core.order_cancel_replace_request(peer, &msg),
+            other if msg_type::is_admin(other) || other.is_empty() => {
+                core.session_reject(peer, &msg, INVALID_MSG_TYPE, None, "Invalid MsgType");
+            }
+            other => {
+                let reject = FixMessage::new(msg_type::BUSINESS_MESSAGE_REJECT)
+                    .with(tag::REF_SEQ_NUM, seq)
+                    .with(tag::REF_MSG_TYPE, other)
+                    .with(tag::BUSINESS_REJECT_REASON, 3)
+                    .with(tag::TEXT, "Unsupported Message Type");
+                core.send(peer, reject);
+            }
+        }
+        Flow::Continue
+    }
+
+    fn on_resend_request(&self, core: &mut Core, peer: &str, msg: &FixMessage) {
+        if !core.require(peer, msg, &[tag::BEGIN_SEQ_NO, tag::END_SEQ_NO]) {
+            return;
+        }
+        match (
+            msg.parse::<u64>(tag::BEGIN_SEQ_NO),
+            msg.parse::<u64>(tag::END_SEQ_NO),
+        ) {
+            (Some(begin), Some(end)) => core.resend(peer, begin, end),
+            _ => core.session_reject(
+                peer,
+                msg,
+                VALUE_INCORRECT,
+                Some(tag::BEGIN_SEQ_NO),
+                "BeginSeqNo and EndSeqNo must be numbers",
+            ),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    BookReader, MatchingEngine, OrderBook, OrderId, OrderRequest, OrderResult, Price, Quantity,
+    TradeExecution, TradingPair,
+};
+
+pub type GatewayResult<T> = Result<T, String>;
+
+/// Venue that the TUI and the replay tool trade against.
+///
+/// A local [`OrderBook`] has a single implicit market and ignores `market`;
+/// a [`MatchingEngine`] requires one of the pairs returned by [`markets`].
+/// Either way the market's [`BookReader`] sees a new snapshot after every
+/// call that may have changed the book.
+///
+/// [`markets`]: OrderGateway::markets
+pub trait OrderGateway {
+    fn markets(&self) -> Vec<TradingPair>;
+
+    fn place(
+        &mut self,
+        market: Option<&TradingPair>,
+        order: OrderRequest,
+    ) -> GatewayResult<(OrderResult, Vec<TradeExecution>)>;
+
+    fn cancel(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>>;
+
+    fn reduce(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+        qty: Quantity,
+    ) -> GatewayResult<Option<OrderResult>>;
+
+    fn amend(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> GatewayResult<Option<(OrderResult, Vec<TradeExecution>)>>;
+
+    /// The order while it rests, or its final state if the venue still
+    /// retains it after it left the book.
+    fn order(
+        &self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>>;
+
+    fn reader(&self, market: Option<&TradingPair>) -> GatewayResult<BookReader>;
+}
+
+impl OrderGateway for OrderBook {
+    fn markets(&self) -> Vec<TradingPair> {
+        Vec::new()
+    }
+
+    fn place(
+        &mut self,
+        _market: Option<&TradingPair>,
+        order: OrderRequest,
+    ) -> GatewayResult<(OrderResult, Vec<TradeExecution>)> {
+        let placed = self.add_order(order);
+        self.publish();
+        Ok(placed)
+    }
+
+    fn cancel(
+        &mut self,
+        _market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>> {
+        let cancelled = self.delete_order(order_id);
+        self.publish();
+        Ok(cancelled)
+    }
+
+    fn reduce(
+        &mut self,
+        _market: Option<&TradingPair>,
+        order_id: OrderId,
+        qty: Quantity,
+    ) -> GatewayResult<Option<OrderResult>> {
+        let reduced = self.cancel_order(order_id, qty);
+        self.publish();
+        Ok(reduced)
+    }
+
+    fn amend(
+        &mut self,
+        _market: Option<&TradingPair>,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> GatewayResult<Option<(OrderResult, Vec<TradeExecution>)>> {
+        let amended = self.amend_order(order_id, price, qty);
+        self.publish();
+        Ok(amended)
+    }
+
+    fn order(
+        &self,
+        _market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>> {
+        Ok(self.order_status(order_id))
+    }
+
+    fn reader(&self, _market: Option<&TradingPair>) -> GatewayResult<BookReader> {
+        Ok(OrderBook::reader(self))
+    }
+}
+
+fn require(market: Option<&TradingPair>) -> GatewayResult<&TradingPair> {
+    market.ok_or_else(|| "No market selected".to_string())
+}
+
+impl OrderGateway for MatchingEngine {
+    fn markets(&self) -> Vec<TradingPair> {
+        let mut markets = self.get_markets();
+        markets.sort_by_key(|pair| pair.to_string());
+        markets
+    }
+
+    fn place(
+        &mut self,
+        market: Option<&TradingPair>,
+        order: OrderRequest,
+    ) -> GatewayResult<(OrderResult, Vec<TradeExecution>)> {
+        Ok(self.place_order(require(market)?, order)?)
+    }
+
+    fn cancel(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>> {
+        Ok(self.cancel_order(require(market)?, order_id)?)
+    }
+
+    fn reduce(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+        qty: Quantity,
+    ) -> GatewayResult<Option<OrderResult>> {
+        Ok(self.reduce_order(require(market)?, order_id, qty)?)
+    }
+
+    fn amend(
+        &mut self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> GatewayResult<Option<(OrderResult, Vec<TradeExecution>)>> {
+        Ok(self.amend_order(require(market)?, order_id, price, qty)?)
+    }
+
+    fn order(
+        &self,
+        market: Option<&TradingPair>,
+        order_id: OrderId,
+    ) -> GatewayResult<Option<OrderResult>> {
+        self.order_status(require(market)?, order_id)
+    }
+
+    fn reader(&self, market: Option<&TradingPair>) -> GatewayResult<BookReader> {
+        MatchingEngine::reader(self).book_reader(require(market)?)
+    }
+}
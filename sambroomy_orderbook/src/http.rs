@@ -0,0 +1,307 @@
+//! JSON-over-HTTP API for a [`MatchingEngine`].
+//!
+//! | Method   | Path                            | Body / query                |
+//! |----------|---------------------------------|-----------------------------|
+//! | `GET`    | `/markets`                      |                             |
+//! | `GET`    | `/markets/{market}/book`        | `?depth=N`                  |
+//! | `GET`    | `/markets/{market}/bbo`         |                             |
+//! | `GET`    | `/markets/{market}/spread`      |                             |
+//! | `GET`    | `/markets/{market}/volume`      |                             |
+//! | `POST`   | `/markets/{market}/orders`      | `{side, type, price?, qty}` |
+//! | `GET`    | `/markets/{market}/orders/{id}` |                             |
+//! | `DELETE` | `/markets/{market}/orders/{id}` |                             |
+//!
+//! Errors are `{"error": "..."}` with 400 for malformed input, 404 for an
+//! unknown market or order, 409 for a request the market's trading state
+//! does not allow, 422 for an order a risk check rejected and 500 for
+//! anything the engine did not expect.
+//! Book reads go through the engine's published snapshots and never wait
+//! for matching, except for a `depth` beyond the snapshot depth, which is
+//! read from the book under the engine lock. Order lookups also find
+//! filled and cancelled orders while the engine retains them (see
+//! [`MatchingEngine::set_order_retention`]).
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::{
+    EngineReader, MatchingEngine, OrderError, OrderId, OrderRequest, Price, Quantity, TradingPair,
+    orderbook::DEFAULT_SNAPSHOT_DEPTH,
+    protocol::{OrderReport, TradeReport, WireOrderType, WireSide},
+};
+
+const DEFAULT_BOOK_DEPTH: usize = 20;
+
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    /// Engine errors left after the market was validated are unexpected.
+    fn internal(message: String) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl From<OrderError> for ApiError {
+    fn from(error: OrderError) -> Self {
+        match error {
+            OrderError::Risk(rejection) => {
+                Self::new(StatusCode::UNPROCESSABLE_ENTITY, rejection.to_string())
+            }
+            OrderError::MarketState { message, .. } => Self::new(StatusCode::CONFLICT, message),
+            OrderError::Other(message) => Self::internal(message),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaceOrder {
+    pub side: WireSide,
+    #[serde(rename = "type")]
+    pub order_type: WireOrderType,
+    #[serde(default)]
+    pub price: Option<Price>,
+    pub qty: Quantity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderResponse {
+    pub order: OrderReport,
+    pub trades: Vec<TradeReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookResponse {
+    pub market: String,
+    pub version: u64,
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DepthQuery {
+    depth: Option<usize>,
+}
+
+/// Shared state behind the router. Cheap to clone.
+#[derive(Clone)]
+pub struct HttpApi {
+    engine: Arc<Mutex<MatchingEngine>>,
+    reader: EngineReader,
+}
+
+impl HttpApi {
+    pub fn new(engine: MatchingEngine) -> Self {
+        Self::from_shared(Arc::new(Mutex::new(engine)))
+    }
+
+    /// Serves an engine that other components also hold.
+    pub fn from_shared(engine: Arc<Mutex<MatchingEngine>>) -> Self {
+        let reader = engine.lock().unwrap().reader();
+        Self { engine, reader }
+    }
+
+    pub fn engine(&self) -> Arc<Mutex<MatchingEngine>> {
+        self.engine.clone()
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/markets", get(markets))
+            .route("/markets/{market}/book", get(book))
+            .route("/markets/{market}/bbo", get(best_bid_ask))
+            .route("/markets/{market}/spread", get(spread))
+            .route("/markets/{market}/volume", get(volume))
+            .route("/markets/{market}/orders", post(place_order))
+            .route(
+                "/markets/{market}/orders/{order_id}",
+                get(order_status).delete(cancel_order),
+            )
+            .with_state(self.clone())
+    }
+
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+
+    fn market(&self, market: &str) -> Result<TradingPair, ApiError> {
+        let pair: TradingPair = market.parse().map_err(ApiError::bad_request)?;
+        if !self.reader.market_exists(&pair) {
+            return Err(ApiError::not_found(format!(
+                "Market for {} does not exist",
+                pair
+            )));
+        }
+        Ok(pair)
+    }
+}
+
+async fn markets(State(api): State<HttpApi>) -> Json<Vec<String>> {
+    let mut markets: Vec<_> = api
+        .reader
+        .get_markets()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    markets.sort();
+    Json(markets)
+}
+
+async fn book(
+    State(api): State<HttpApi>,
+    Path(market): Path<String>,
+    Query(query): Query<DepthQuery>,
+) -> ApiResult<BookResponse> {
+    let pair = api.market(&market)?;
+    let depth = query.depth.unwrap_or(DEFAULT_BOOK_DEPTH);
+    let snapshot = if depth <= DEFAULT_SNAPSHOT_DEPTH {
+        api.reader.snapshot(&pair).map_err(ApiError::not_found)?
+    } else {
+        let engine = api.engine.lock().unwrap();
+        Arc::new(
+            engine
+                .deep_snapshot(&pair, depth)
+                .map_err(ApiError::not_found)?,
+        )
+    };
+    Ok(Json(BookResponse {
+        market: pair.to_string(),
+        version: snapshot.version,
+        bids: snapshot.bids.iter().take(depth).copied().collect(),
+        asks: snapshot.asks.iter().take(depth).copied().collect(),
+    }))
+}
+
+async fn best_bid_ask(
+    State(api): State<HttpApi>,
+    Path(market): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let pair = api.market(&market)?;
+    let snapshot = api.reader.snapshot(&pair).map_err(ApiError::not_found)?;
+    let level = |level: Option<(Price, Quantity)>| {
+        level.map(|(price, qty)| json!({"price": price, "qty": qty}))
+    };
+    Ok(Json(json!({
+        "market": pair.to_string(),
+        "version": snapshot.version,
+        "bid": level(snapshot.best_bid()),
+        "ask": level(snapshot.best_ask()),
+    })))
+}
+
+async fn spread(
+    State(api): State<HttpApi>,
+    Path(market): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let pair = api.market(&market)?;
+    let spread = api.reader.get_spread(&pair).map_err(ApiError::not_found)?;
+    Ok(Json(
+        json!({ "market": pair.to_string(), "spread": spread }),
+    ))
+}
+
+async fn volume(
+    State(api): State<HttpApi>,
+    Path(market): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let pair = api.market(&market)?;
+    let volume = api
+        .engine
+        .lock()
+        .unwrap()
+        .get_volume(&pair)
+        .map_err(ApiError::internal)?;
+    Ok(Json(
+        json!({ "market": pair.to_string(), "volume": volume }),
+    ))
+}
+
+async fn place_order(
+    State(api): State<HttpApi>,
+    Path(market): Path<String>,
+    Json(body): Json<PlaceOrder>,
+) -> Result<(StatusCode, Json<OrderResponse>), ApiError> {
+    let pair = api.market(&market)?;
+    if body.qty <= Decimal::ZERO {
+        return Err(ApiError::bad_request("qty must be positive"));
+    }
+    let order_type = body
+        .order_type
+        .to_order_type(body.price)
+        .map_err(ApiError::bad_request)?;
+    let order = OrderRequest::new(body.side.into(), body.qty, order_type);
+    let (result, executions) = api.engine.lock().unwrap().place_order(&pair, order)?;
+    Ok((
+        StatusCode::CREATED,
+        Json(OrderResponse {
+            order: OrderReport::from(&result),
+            trades: executions.iter().map(TradeReport::from).collect(),
+        }),
+    ))
+}
+
+async fn order_status(
+    State(api): State<HttpApi>,
+    Path((market, order_id)): Path<(String, OrderId)>,
+) -> ApiResult<OrderReport> {
+    let pair = api.market(&market)?;
+    let result = api
+        .engine
+        .lock()
+        .unwrap()
+        .order_status(&pair, order_id)
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::not_found(format!("unknown order {}", order_id)))?;
+    Ok(Json(OrderReport::from(&result)))
+}
+
+async fn cancel_order(
+    State(api): State<HttpApi>,
+    Path((market, order_id)): Path<(String, OrderId)>,
+) -> ApiResult<OrderReport> {
+    let pair = api.market(&market)?;
+    let result = api
+        .engine
+        .lock()
+        .unwrap()
+        .cancel_order(&pair, order_id)?
+        .ok_or_else(|| ApiError::not_found(format!("unknown order {}", order_id)))?;
+    Ok(Json(OrderReport::from(&result)))
+}
@@ -0,0 +1,797 @@
+//! ITCH-style binary market data.
+//!
+//! Every message is a fixed-layout, big-endian record that starts with an
+//! 11-byte header: the message type, a `u16` locate code naming the market
+//! (assigned by [`ItchEncoder::add_market`]) and the event time in
+//! nanoseconds since the Unix epoch.
+//!
+//! | type | message         | length | body                                                    |
+//! |------|-----------------|--------|---------------------------------------------------------|
+//! | `S`  | system event    | 12     | event code                                              |
+//! | `H`  | market state    | 12     | state code                                              |
+//! | `A`  | add order       | 44     | order id, side, qty, price                              |
+//! | `E`  | order executed  | 43     | maker order id, executed qty, match number              |
+//! | `X`  | order cancel    | 35     | order id, cancelled qty                                 |
+//! | `D`  | order delete    | 27     | order id                                                |
+//! | `U`  | order replace   | 43     | order id, new total qty, price                          |
+//! | `P`  | trade           | 68     | aggressor side, qty, price, match number, taker, maker  |
+//!
+//! Order ids are the 16 UUID bytes, sides are `B`/`S`, and prices and
+//! quantities are fixed-point with [`DECIMALS`] decimal places (`i64` and
+//! `u64`). Each fill is an `E`, which updates the maker in the book, followed
+//! by a `P` with the same match number carrying the print.
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BookChange, OrderId, Price, Quantity, Side, TradingPair, orderbook::Timestamp,
+    protocol::WireSide,
+};
+
+/// Decimal places of every fixed-point price and quantity.
+pub const DECIMALS: u32 = 8;
+
+const SCALE: i64 = 100_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SystemEvent {
+    StartOfMessages,
+    EndOfMessages,
+}
+
+impl SystemEvent {
+    fn code(self) -> u8 {
+        match self {
+            SystemEvent::StartOfMessages => b'O',
+            SystemEvent::EndOfMessages => b'C',
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            b'O' => Some(SystemEvent::StartOfMessages),
+            b'C' => Some(SystemEvent::EndOfMessages),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketState {
+    Trading,
+    Auction,
+    Halted,
+    Closed,
+}
+
+impl MarketState {
+    fn code(self) -> u8 {
+        match self {
+            MarketState::Trading => b'T',
+            MarketState::Auction => b'A',
+            MarketState::Halted => b'H',
+            MarketState::Closed => b'C',
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            b'T' => Some(MarketState::Trading),
+            b'A' => Some(MarketState::Auction),
+            b'H' => Some(MarketState::Halted),
+            b'C' => Some(MarketState::Closed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItchMessage {
+    pub locate: u16,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub body: ItchBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ItchBody {
+    SystemEvent(SystemEvent),
+    MarketState(MarketState),
+    AddOrder {
+        order_id: OrderId,
+        side: WireSide,
+        qty: Quantity,
+        price: Price,
+    },
+    OrderExecuted {
+        order_id: OrderId,
+        qty: Quantity,
+        match_number: u64,
+    },
+    OrderCancel {
+        order_id: OrderId,
+        qty: Quantity,
+    },
+    OrderDelete {
+        order_id: OrderId,
+    },
+    OrderReplace {
+        order_id: OrderId,
+        qty: Quantity,
+        price: Price,
+    },
+    Trade {
+        side: WireSide,
+        qty: Quantity,
+        price: Price,
+        match_number: u64,
+        taker_order_id: OrderId,
+        maker_order_id: OrderId,
+    },
+}
+
+impl ItchBody {
+    pub fn msg_type(&self) -> u8 {
+        match self {
+            ItchBody::SystemEvent(_) => b'S',
+            ItchBody::MarketState(_) => b'H',
+            ItchBody::AddOrder { .. } => b'A',
+            ItchBody::OrderExecuted { .. } => b'E',
+            ItchBody::OrderCancel { .. } => b'X',
+            ItchBody::OrderDelete { .. } => b'D',
+            ItchBody::OrderReplace { .. } => b'U',
+            ItchBody::Trade { .. } => b'P',
+        }
+    }
+}
+
+/// Wire length of a message of type `msg_type`.
+pub fn message_len(msg_type: u8) -> Option<usize> {
+    match msg_type {
+        b'S' | b'H' => Some(12),
+        b'A' => Some(44),
+        b'E' | b'U' => Some(43),
+        b'X' => Some(35),
+        b'D' => Some(27),
+        b'P' => Some(68),
+        _ => None,
+    }
+}
+
+impl ItchMessage {
+    /// Appends the wire form to `out`. Fails, leaving `out` untouched, if a
+    /// price or quantity has more than [`DECIMALS`] places or does not fit.
+    pub fn encode(&self, out: &mut Vec<u8>) -> Result<(), String> {
+        let start = out.len();
+        let result = self.write(out);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+
+    fn write(&self, out: &mut Vec<u8>) -> Result<(), String> {
+        out.push(self.body.msg_type());
+        out.extend_from_slice(&self.locate.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        match &self.body {
+            ItchBody::SystemEvent(event) => out.push(event.code()),
+            ItchBody::MarketState(state) => out.push(state.code()),
+            ItchBody::AddOrder {
+                order_id,
+                side,
+                qty,
+                price,
+            } => {
+                out.extend_from_slice(order_id.as_bytes());
+                out.push(side_code(*side));
+                out.extend_from_slice(&fixed_qty(*qty)?.to_be_bytes());
+                out.extend_from_slice(&fixed_price(*price)?.to_be_bytes());
+            }
+            ItchBody::OrderExecuted {
+                order_id,
+                qty,
+                match_number,
+            } => {
+                out.extend_from_slice(order_id.as_bytes());
+                out.extend_from_slice(&fixed_qty(*qty)?.to_be_bytes());
+                out.extend_from_slice(&match_number.to_be_bytes());
+            }
+            ItchBody::OrderCancel { order_id, qty } => {
+                out.extend_from_slice(order_id.as_bytes());
+                out.extend_from_slice(&fixed_qty(*qty)?.to_be_bytes());
+            }
+            ItchBody::OrderDelete { order_id } => out.extend_from_slice(order_id.as_bytes()),
+            ItchBody::OrderReplace {
+                order_id,
+                qty,
+                price,
+            } => {
+                out.extend_from_slice(order_id.as_bytes());
+                out.extend_from_slice(&fixed_qty(*qty)?.to_be_bytes());
+                out.extend_from_slice(&fixed_price(*price)?.to_be_bytes());
+            }
+            ItchBody::Trade {
+                side,
+                qty,
+                price,
+                match_number,
+                taker_order_id,
+                maker_order_id,
+            } => {
+                out.push(side_code(*side));
+                out.extend_from_slice(&fixed_qty(*qty)?.to_be_bytes());
+                out.extend_from_slice(&fixed_price(*price)?.to_be_bytes());
+                out.extend_from_slice(&match_number.to_be_bytes());
+                out.extend_from_slice(taker_order_id.as_bytes());
+                out.extend_from_slice(maker_order_id.as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn side_code(side: WireSide) -> u8 {
+    match side {
+        WireSide::Buy => b'B',
+        WireSide::Sell => b'S',
+    }
+}
+
+fn scaled(value: Decimal) -> Result<i64, String> {
+    value
+        .checked_mul(Decimal::from(SCALE))
+        .filter(|v| v.fract().is_zero())
+        .and_then(|v| v.to_i64())
+        .ok_or_else(|| format!("{} is not representable with {} decimals", value, DECIMALS))
+}
+
+fn fixed_price(price: Price) -> Result<i64, String> {
+    scaled(price)
+}
+
+fn fixed_qty(qty: Quantity) -> Result<u64, String> {
+    u64::try_from(scaled(qty)?).map_err(|_| format!("negative quantity {}", qty))
+}
+
+fn nanos(timestamp: Timestamp) -> u64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Turns [`BookChange`]s into ITCH messages, numbering matches and assigning
+/// each market a locate code.
+#[derive(Debug, Default)]
+pub struct ItchEncoder {
+    locates: HashMap<TradingPair, u16>,
+    match_number: u64,
+}
+
+impl ItchEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns `pair` the next locate code, starting at 1.
+    pub fn add_market(&mut self, pair: TradingPair) -> Result<u16, String> {
+        if self.locates.contains_key(&pair) {
+            return Err(format!("Market for {} already exists", pair));
+        }
+        let locate = u16::try_from(self.locates.len() + 1)
+            .map_err(|_| "no locate codes left".to_string())?;
+        self.locates.insert(pair, locate);
+        Ok(locate)
+    }
+
+    pub fn locate(&self, pair: &TradingPair) -> Result<u16, String> {
+        self.locates
+            .get(pair)
+            .copied()
+            .ok_or_else(|| format!("Market for {} does not exist", pair))
+    }
+
+    /// A feed-wide event, sent with locate code 0.
+    pub fn system_event(&self, event: SystemEvent) -> ItchMessage {
+        ItchMessage {
+            locate: 0,
+            timestamp: nanos(SystemTime::now()),
+            body: ItchBody::SystemEvent(event),
+        }
+    }
+
+    pub fn market_state(
+        &self,
+        pair: &TradingPair,
+        state: MarketState,
+    ) -> Result<ItchMessage, String> {
+        Ok(ItchMessage {
+            locate: self.locate(pair)?,
+            timestamp: nanos(SystemTime::now()),
+            body: ItchBody::MarketState(state),
+        })
+    }
+
+    /// The messages describing `change`. Fills are timestamped with their
+    /// [`TradeExecution`](crate::TradeExecution); everything else with the
+    /// time of the call.
+    pub fn book_change(
+        &mut self,
+        pair: &TradingPair,
+        change: &BookChange,
+    ) -> Result<Vec<ItchMessage>, String> {
+        let locate = self.locate(pair)?;
+        let message = |timestamp, body| ItchMessage {
+            locate,
+            timestamp,
+            body,
+        };
+        let now = nanos(SystemTime::now());
+        Ok(match change {
+            BookChange::Added {
+                order_id,
+                side,
+                price,
+                qty,
+            } => vec![message(
+                now,
+                ItchBody::AddOrder {
+                    order_id: *order_id,
+                    side: WireSide::from(*side),
+                    qty: *qty,
+                    price: *price,
+                },
+            )],
+            BookChange::Executed(execution) | BookChange::Uncrossed(execution) => {
+                self.match_number += 1;
+                let timestamp = nanos(execution.timestamp);
+                // In an uncross the taker was resting too.
+                let resting = match change {
+                    BookChange::Uncrossed(_) => {
+                        vec![execution.maker_order_id, execution.taker_order_id]
+                    }
+                    _ => vec![execution.maker_order_id],
+                };
+                let mut messages: Vec<_> = resting
+                    .into_iter()
+                    .map(|order_id| {
+                        message(
+                            timestamp,
+                            ItchBody::OrderExecuted {
+                                order_id,
+                                qty: execution.qty,
+                                match_number: self.match_number,
+                            },
+                        )
+                    })
+                    .collect();
+                messages.push(message(
+                    timestamp,
+                    ItchBody::Trade {
+                        side: WireSide::from(execution.take_side),
+                        qty: execution.qty,
+                        price: execution.price,
+                        match_number: self.match_number,
+                        taker_order_id: execution.taker_order_id,
+                        maker_order_id: execution.maker_order_id,
+                    },
+                ));
+                messages
+            }
+            BookChange::Reduced { order_id, qty, .. } => vec![message(
+                now,
+                ItchBody::OrderCancel {
+                    order_id: *order_id,
+                    qty: *qty,
+                },
+            )],
+            BookChange::Replaced {
+                order_id,
+                price,
+                qty,
+                ..
+            } => vec![message(
+                now,
+                ItchBody::OrderReplace {
+                    order_id: *order_id,
+                    qty: *qty,
+                    price: *price,
+                },
+            )],
+            BookChange::Deleted { order_id, .. } => vec![message(
+                now,
+                ItchBody::OrderDelete {
+                    order_id: *order_id,
+                },
+            )],
+        })
+    }
+
+    /// Encodes the messages for `change` into `out`. On error nothing is
+    /// written, though a fill still uses up its match number.
+    pub fn encode_change(
+        &mut self,
+        pair: &TradingPair,
+        change: &BookChange,
+        out: &mut Vec<u8>,
+    ) -> Result<(), String> {
+        let start = out.len();
+        for message in self.book_change(pair, change)? {
+            if let Err(e) = message.encode(out) {
+                out.truncate(start);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A validated message borrowed from the wire. Fields are read from the
+/// underlying bytes on access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> MessageRef<'a> {
+    /// Decodes the first message in `buf`. Returns the message and the number
+    /// of bytes it used, or `None` if `buf` does not hold a whole message yet.
+    pub fn decode(buf: &'a [u8]) -> Result<Option<(MessageRef<'a>, usize)>, String> {
+        let Some(&msg_type) = buf.first() else {
+            return Ok(None);
+        };
+        let len = message_len(msg_type)
+            .ok_or_else(|| format!("unknown message type 0x{:02x}", msg_type))?;
+        if buf.len() < len {
+            return Ok(None);
+        }
+        let message = MessageRef { bytes: &buf[..len] };
+        match msg_type {
+            b'S' if SystemEvent::from_code(message.u8_at(11)).is_none() => {
+                return Err(format!(
+                    "unknown system event {:?}",
+                    message.u8_at(11) as char
+                ));
+            }
+            b'H' if MarketState::from_code(message.u8_at(11)).is_none() => {
+                return Err(format!(
+                    "unknown market state {:?}",
+                    message.u8_at(11) as char
+                ));
+            }
+            b'A' | b'P' => {
+                let at = if msg_type == b'A' { 27 } else { 11 };
+                if !matches!(message.u8_at(at), b'B' | b'S') {
+                    return Err(format!("unknown side {:?}", message.u8_at(at) as char));
+                }
+            }
+            _ => {}
+        }
+        Ok(Some((message, len)))
+    }
+
+    /// Decodes every message in `buf`, which must end on a message boundary.
+    pub fn decode_all(mut buf: &'a [u8]) -> Result<Vec<MessageRef<'a>>, String> {
+        let mut messages = Vec::new();
+        while !buf.is_empty() {
+            let (message, used) = Self::decode(buf)?.ok_or("buffer ends inside a message")?;
+            messages.push(message);
+            buf = &buf[used..];
+        }
+        Ok(messages)
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn msg_type(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    pub fn locate(&self) -> u16 {
+        u16::from_be_bytes(self.array_at(1))
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        u64::from_be_bytes(self.array_at(3))
+    }
+
+    /// The order of `A`, `E`, `X`, `D` and `U` messages, or the maker of a `P`.
+    pub fn order_id(&self) -> Option<OrderId> {
+        match self.msg_type() {
+            b'A' | b'E' | b'X' | b'D' | b'U' => Some(self.uuid_at(11)),
+            b'P' => Some(self.uuid_at(52)),
+            _ => None,
+        }
+    }
+
+    pub fn taker_order_id(&self) -> Option<OrderId> {
+        (self.msg_type() == b'P').then(|| self.uuid_at(36))
+    }
+
+    pub fn side(&self) -> Option<Side> {
+        let code = match self.msg_type() {
+            b'A' => self.u8_at(27),
+            b'P' => self.u8_at(11),
+            _ => return None,
+        };
+        Some(if code == b'B' { Side::Bid } else { Side::Ask })
+    }
+
+    pub fn qty(&self) -> Option<Quantity> {
+        let at = match self.msg_type() {
+            b'A' => 28,
+            b'E' | b'X' | b'U' => 27,
+            b'P' => 12,
+            _ => return None,
+        };
+        let raw = u64::from_be_bytes(self.array_at(at));
+        Some(Decimal::from_i128_with_scale(raw as i128, DECIMALS).normalize())
+    }
+
+    pub fn price(&self) -> Option<Price> {
+        let at = match self.msg_type() {
+            b'A' => 36,
+            b'U' => 35,
+            b'P' => 20,
+            _ => return None,
+        };
+        let raw = i64::from_be_bytes(self.array_at(at));
+        Some(Decimal::new(raw, DECIMALS).normalize())
+    }
+
+    pub fn match_number(&self) -> Option<u64> {
+        match self.msg_type() {
+            b'E' => Some(u64::from_be_bytes(self.array_at(35))),
+            b'P' => Some(u64::from_be_bytes(self.array_at(28))),
+            _ => None,
+        }
+    }
+
+    pub fn system_event(&self) -> Option<SystemEvent> {
+        (self.msg_type() == b'S').then(|| SystemEvent::from_code(self.u8_at(11)))?
+    }
+
+    pub fn market_state(&self) -> Option<MarketState> {
+        (self.msg_type() == b'H').then(|| MarketState::from_code(self.u8_at(11)))?
+    }
+
+    /// Copies the message out of the buffer.
+    pub fn to_message(&self) -> ItchMessage {
+        let order_id = || self.order_id().unwrap();
+        let side = || WireSide::from(self.side().unwrap());
+        let qty = || self.qty().unwrap();
+        let price = || self.price().unwrap();
+        let body = match self.msg_type() {
+            b'S' => ItchBody::SystemEvent(self.system_event().unwrap()),
+            b'H' => ItchBody::MarketState(self.market_state().unwrap()),
+            b'A' => ItchBody::AddOrder {
+                order_id: order_id(),
+                side: side(),
+                qty: qty(),
+                price: price(),
+            },
+            b'E' => ItchBody::OrderExecuted {
+                order_id: order_id(),
+                qty: qty(),
+                match_number: self.match_number().unwrap(),
+            },
+            b'X' => ItchBody::OrderCancel {
+                order_id: order_id(),
+                qty: qty(),
+            },
+            b'D' => ItchBody::OrderDelete {
+                order_id: order_id(),
+            },
+            b'U' => ItchBody::OrderReplace {
+                order_id: order_id(),
+                qty: qty(),
+                price: price(),
+            },
+            _ => ItchBody::Trade {
+                side: side(),
+                qty: qty(),
+                price: price(),
+                match_number: self.match_number().unwrap(),
+                taker_order_id: self.taker_order_id().unwrap(),
+                maker_order_id: order_id(),
+            },
+        };
+        ItchMessage {
+            locate: self.locate(),
+            timestamp: self.timestamp(),
+            body,
+        }
+    }
+
+    fn u8_at(&self, at: usize) -> u8 {
+        self.bytes[at]
+    }
+
+    fn array_at<const N: usize>(&self, at: usize) -> [u8; N] {
+        self.bytes[at..at + N].try_into().unwrap()
+    }
+
+    fn uuid_at(&self, at: usize) -> OrderId {
+        OrderId::from_bytes(self.array_at(at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrderBook, OrderRequest, OrderType};
+
+    fn pair() -> TradingPair {
+        TradingPair::new("BTC".to_string(), "USDT".to_string())
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn every_message_round_trips_at_its_fixed_length() {
+        let id = OrderId::new_v4();
+        let other = OrderId::new_v4();
+        let bodies = [
+            ItchBody::SystemEvent(SystemEvent::StartOfMessages),
+            ItchBody::MarketState(MarketState::Halted),
+            ItchBody::AddOrder {
+                order_id: id,
+                side: WireSide::Sell,
+                qty: dec("1.5"),
+                price: dec("27123.12345678"),
+            },
+            ItchBody::OrderExecuted {
+                order_id: id,
+                qty: dec("0.25"),
+                match_number: 7,
+            },
+            ItchBody::OrderCancel {
+                order_id: id,
+                qty: dec("0.00000001"),
+            },
+            ItchBody::OrderDelete { order_id: id },
+            ItchBody::OrderReplace {
+                order_id: id,
+                qty: dec("3"),
+                price: dec("-2.5"),
+            },
+            ItchBody::Trade {
+                side: WireSide::Buy,
+                qty: dec("0.25"),
+                price: dec("27123"),
+                match_number: 7,
+                taker_order_id: other,
+                maker_order_id: id,
+            },
+        ];
+
+        let mut wire = Vec::new();
+        let messages: Vec<_> = bodies
+            .into_iter()
+            .enumerate()
+            .map(|(i, body)| ItchMessage {
+                locate: 3,
+                timestamp: 1_700_000_000_000_000_000 + i as u64,
+                body,
+            })
+            .collect();
+        for message in &messages {
+            let before = wire.len();
+            message.encode(&mut wire).unwrap();
+            assert_eq!(
+                Some(wire.len() - before),
+                message_len(message.body.msg_type())
+            );
+        }
+
+        let decoded = MessageRef::decode_all(&wire).unwrap();
+        let owned: Vec<_> = decoded.iter().map(MessageRef::to_message).collect();
+        assert_eq!(owned, messages);
+        assert_eq!(decoded[7].taker_order_id(), Some(other));
+        assert_eq!(decoded[7].side(), Some(Side::Bid));
+        assert_eq!(decoded[2].price(), Some(dec("27123.12345678")));
+
+        for cut in 0..44 {
+            assert_eq!(MessageRef::decode(&wire[24..24 + cut]), Ok(None));
+        }
+        assert!(MessageRef::decode_all(&wire[..wire.len() - 1]).is_err());
+        assert!(MessageRef::decode(b"Z").is_err());
+    }
+
+    #[test]
+    fn rejects_values_without_a_fixed_point_form() {
+        let mut wire = vec![1, 2, 3];
+        for qty in [dec("0.000000001"), dec("-1"), Decimal::MAX] {
+            let message = ItchMessage {
+                locate: 1,
+                timestamp: 0,
+                body: ItchBody::OrderCancel {
+                    order_id: OrderId::new_v4(),
+                    qty,
+                },
+            };
+            assert!(message.encode(&mut wire).is_err());
+        }
+        assert_eq!(wire, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn book_changes_rebuild_the_resting_book() {
+        let mut encoder = ItchEncoder::new();
+        assert_eq!(encoder.add_market(pair()), Ok(1));
+        assert!(encoder.add_market(pair()).is_err());
+
+        let mut ob = OrderBook::default();
+        let changes = ob.subscribe_changes();
+        let ask = ob.add_order(OrderRequest::new(
+            Side::Ask,
+            5,
+            OrderType::Limit(dec("101")),
+        ));
+        let bid = ob.add_order(OrderRequest::new(Side::Bid, 4, OrderType::Limit(dec("99"))));
+        ob.add_order(OrderRequest::new(
+            Side::Ask,
+            2,
+            OrderType::Limit(dec("102")),
+        ));
+        ob.add_order(OrderRequest::new(Side::Bid, 2, OrderType::Market));
+        ob.cancel_order(bid.0.get_id(), 1);
+        ob.cancel_order(ask.0.get_id(), 1);
+        let gone = ob.add_order(OrderRequest::new(Side::Bid, 1, OrderType::Limit(dec("98"))));
+        ob.delete_order(gone.0.get_id());
+
+        let mut wire = Vec::new();
+        for change in changes.try_iter() {
+            encoder.encode_change(&pair(), &change, &mut wire).unwrap();
+        }
+
+        let mut book: HashMap<OrderId, (Side, Price, Quantity)> = HashMap::new();
+        let mut traded = Decimal::ZERO;
+        for message in MessageRef::decode_all(&wire).unwrap() {
+            assert_eq!(message.locate(), 1);
+            let id = message.order_id().unwrap();
+            match message.msg_type() {
+                b'A' => {
+                    book.insert(
+                        id,
+                        (
+                            message.side().unwrap(),
+                            message.price().unwrap(),
+                            message.qty().unwrap(),
+                        ),
+                    );
+                }
+                b'E' | b'X' => book.get_mut(&id).unwrap().2 -= message.qty().unwrap(),
+                b'D' => assert!(book.remove(&id).is_some()),
+                b'U' => book.get_mut(&id).unwrap().2 = message.qty().unwrap(),
+                b'P' => {
+                    assert_eq!(message.match_number(), Some(1));
+                    traded += message.qty().unwrap();
+                }
+                other => panic!("unexpected message {}", other as char),
+            }
+        }
+        book.retain(|_, (_, _, qty)| !qty.is_zero());
+
+        let expected: HashMap<_, _> = ob
+            .order_loc
+            .iter()
+            .map(|(id, &(side, price))| {
+                (*id, (side, price, ob.get_order(*id).unwrap().remaining_qty))
+            })
+            .collect();
+        assert_eq!(expected.len(), 3);
+        assert_eq!(book, expected);
+        assert_eq!(traded, dec("2"));
+    }
+}
@@ -0,0 +1,322 @@
+//! Account balances for the assets of every market.
+//!
+//! Each account holds a [`Balance`] per asset. Placing an order moves the
+//! most it can spend from `free` to `reserved` (quote for bids, base for
+//! asks); fills pay out of that reservation and credit the counterparty's
+//! `free`, and whatever an order no longer needs is released back to `free`.
+//! Balances only enter or leave through [`Ledger::deposit`] and
+//! [`Ledger::withdraw`], so the total of each asset is conserved by trading.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use crate::{Fee, OrderId, Side, TradeExecution, TradingPair};
+
+pub type AccountId = u64;
+
+/// Collects fees and pays rebates, so it goes negative if rebates ever
+/// exceed the fees taken.
+pub const FEE_ACCOUNT: AccountId = 0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Balance {
+    pub free: Decimal,
+    pub reserved: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.free + self.reserved
+    }
+}
+
+/// Funds held for one order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reservation {
+    pub account: AccountId,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Default)]
+pub struct Ledger {
+    balances: HashMap<AccountId, HashMap<String, Balance>>,
+    reservations: HashMap<OrderId, Reservation>,
+    open_orders: HashMap<AccountId, usize>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn deposit(
+        &mut self,
+        account: AccountId,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<Balance, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!("deposit amount must be positive, got {}", amount));
+        }
+        let balance = self.balance_mut(account, asset);
+        balance.free += amount;
+        Ok(*balance)
+    }
+
+    /// Takes `amount` out of the account's free balance.
+    pub fn withdraw(
+        &mut self,
+        account: AccountId,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<Balance, String> {
+        if amount <= Decimal::ZERO {
+            return Err(format!(
+                "withdrawal amount must be positive, got {}",
+                amount
+            ));
+        }
+        let balance = self.balance_mut(account, asset);
+        if balance.free < amount {
+            return Err(insufficient(asset, balance.free, amount));
+        }
+        balance.free -= amount;
+        Ok(*balance)
+    }
+
+    pub fn balance(&self, account: AccountId, asset: &str) -> Balance {
+        self.balances
+            .get(&account)
+            .and_then(|assets| assets.get(asset))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn balances(&self, account: AccountId) -> impl Iterator<Item = (&str, Balance)> {
+        self.balances
+            .get(&account)
+            .into_iter()
+            .flatten()
+            .map(|(asset, balance)| (asset.as_str(), *balance))
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = AccountId> + '_ {
+        self.balances.keys().copied()
+    }
+
+    /// Free plus reserved `asset` over every account.
+    pub fn total(&self, asset: &str) -> Decimal {
+        self.balances
+            .values()
+            .filter_map(|assets| assets.get(asset))
+            .map(Balance::total)
+            .sum()
+    }
+
+    pub fn reservation(&self, order_id: OrderId) -> Option<&Reservation> {
+        self.reservations.get(&order_id)
+    }
+
+    /// Orders of `account` holding a reservation, i.e. resting in any market.
+    pub fn open_orders(&self, account: AccountId) -> usize {
+        self.open_orders.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn owner(&self, order_id: OrderId) -> Option<AccountId> {
+        self.reservations.get(&order_id).map(|r| r.account)
+    }
+
+    /// Moves `amount` of `asset` from free to reserved for `order_id`, adding
+    /// to any reservation the order already has.
+    pub(crate) fn reserve(
+        &mut self,
+        account: AccountId,
+        order_id: OrderId,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<(), String> {
+        if let Some(existing) = self.reservations.get(&order_id)
+            && (existing.account != account || existing.asset != asset)
+        {
+            return Err(format!("order {} belongs to another account", order_id));
+        }
+        let balance = self.balance_mut(account, asset);
+        if balance.free < amount {
+            return Err(insufficient(asset, balance.free, amount));
+        }
+        balance.free -= amount;
+        balance.reserved += amount;
+        let open_orders = &mut self.open_orders;
+        self.reservations
+            .entry(order_id)
+            .or_insert_with(|| {
+                *open_orders.entry(account).or_default() += 1;
+                Reservation {
+                    account,
+                    asset: asset.to_string(),
+                    amount: Decimal::ZERO,
+                }
+            })
+            .amount += amount;
+        Ok(())
+    }
+
+    /// Releases whatever `order_id` holds beyond `keep`, forgetting the order
+    /// once nothing is kept.
+    pub(crate) fn release_to(&mut self, order_id: OrderId, keep: Decimal) {
+        let Some(reservation) = self.reservations.get_mut(&order_id) else {
+            return;
+        };
+        let excess = reservation.amount - keep;
+        if excess > Decimal::ZERO {
+            reservation.amount = keep;
+            let (account, asset) = (reservation.account, reservation.asset.clone());
+            let balance = self.balance_mut(account, &asset);
+            balance.reserved -= excess;
+            balance.free += excess;
+        }
+        if keep <= Decimal::ZERO
+            && let Some(reservation) = self.reservations.remove(&order_id)
+            && let Some(open) = self.open_orders.get_mut(&reservation.account)
+        {
+            *open -= 1;
+        }
+    }
+
+    /// Pays both sides of `execution` out of their orders' reservations,
+    /// less their fees, which go to [`FEE_ACCOUNT`]. Fails, leaving every
+    /// balance untouched, if either order has not reserved enough.
+    pub(crate) fn settle(
+        &mut self,
+        pair: &TradingPair,
+        execution: &TradeExecution,
+    ) -> Result<(), String> {
+        let (taker, maker) = (&execution.taker_fee, &execution.maker_fee);
+        let ((buy_order, buy_fee), (sell_order, sell_fee)) = match execution.take_side {
+            Side::Bid => (
+                (execution.taker_order_id, taker),
+                (execution.maker_order_id, maker),
+            ),
+            Side::Ask => (
+                (execution.maker_order_id, maker),
+                (execution.taker_order_id, taker),
+            ),
+        };
+        let fee = |fee: &Option<Fee>| fee.as_ref().map_or(Decimal::ZERO, |f| f.amount);
+        let (buy_fee, sell_fee) = (fee(buy_fee), fee(sell_fee));
+        let notional = execution.qty * execution.price;
+        self.check_reserved(buy_order, notional)?;
+        self.check_reserved(sell_order, execution.qty)?;
+        let buyer = self.spend(buy_order, notional)?;
+        let seller = self.spend(sell_order, execution.qty)?;
+        self.balance_mut(buyer, pair.base()).free += execution.qty - buy_fee;
+        self.balance_mut(seller, pair.quote()).free += notional - sell_fee;
+        self.balance_mut(FEE_ACCOUNT, pair.base()).free += buy_fee;
+        self.balance_mut(FEE_ACCOUNT, pair.quote()).free += sell_fee;
+        Ok(())
+    }
+
+    fn check_reserved(&self, order_id: OrderId, amount: Decimal) -> Result<(), String> {
+        let reserved = self
+            .reservations
+            .get(&order_id)
+            .ok_or_else(|| format!("order {} has no reservation", order_id))?
+            .amount;
+        if reserved < amount {
+            return Err(format!(
+                "order {} spends {} out of {} reserved",
+                order_id, amount, reserved
+            ));
+        }
+        Ok(())
+    }
+
+    fn spend(&mut self, order_id: OrderId, amount: Decimal) -> Result<AccountId, String> {
+        self.check_reserved(order_id, amount)?;
+        let reservation = self
+            .reservations
+            .get_mut(&order_id)
+            .ok_or_else(|| format!("order {} has no reservation", order_id))?;
+        reservation.amount -= amount;
+        let (account, asset) = (reservation.account, reservation.asset.clone());
+        self.balance_mut(account, &asset).reserved -= amount;
+        Ok(account)
+    }
+
+    fn balance_mut(&mut self, account: AccountId, asset: &str) -> &mut Balance {
+        let assets = self.balances.entry(account).or_default();
+        if !assets.contains_key(asset) {
+            assets.insert(asset.to_string(), Balance::default());
+        }
+        assets.get_mut(asset).unwrap()
+    }
+}
+
+fn insufficient(asset: &str, free: Decimal, required: Decimal) -> String {
+    format!(
+        "insufficient {} balance: {} free, {} required",
+        asset, free, required
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrderBook, OrderRequest, OrderType};
+
+    fn pair() -> TradingPair {
+        TradingPair::new("BTC".to_string(), "USDT".to_string())
+    }
+
+    /// A 2 BTC trade at 100 between a bid from account 1 and an ask from 2.
+    fn trade() -> (OrderId, OrderId, TradeExecution) {
+        let mut book = OrderBook::default();
+        let ask = OrderRequest::new(Side::Ask, 2, OrderType::limit(100));
+        let bid = OrderRequest::new(Side::Bid, 2, OrderType::limit(100));
+        book.add_order(ask);
+        let (_, executions) = book.add_order(bid);
+        (bid.id(), ask.id(), executions[0].clone())
+    }
+
+    fn funded() -> Ledger {
+        let mut ledger = Ledger::new();
+        ledger.deposit(1, "USDT", Decimal::from(1000)).unwrap();
+        ledger.deposit(2, "BTC", Decimal::from(2)).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn settle_pays_out_of_reservations() {
+        let (bid, ask, execution) = trade();
+        let mut ledger = funded();
+        ledger.reserve(1, bid, "USDT", Decimal::from(200)).unwrap();
+        ledger.reserve(2, ask, "BTC", Decimal::from(2)).unwrap();
+
+        ledger.settle(&pair(), &execution).unwrap();
+        assert_eq!(ledger.balance(1, "BTC").free, Decimal::from(2));
+        assert_eq!(ledger.balance(1, "USDT").total(), Decimal::from(800));
+        assert_eq!(ledger.balance(2, "USDT").free, Decimal::from(200));
+    }
+
+    #[test]
+    fn settle_fails_without_enough_reserved_and_changes_nothing() {
+        let (bid, ask, execution) = trade();
+        let mut ledger = funded();
+        ledger.reserve(1, bid, "USDT", Decimal::from(150)).unwrap();
+        ledger.reserve(2, ask, "BTC", Decimal::from(2)).unwrap();
+
+        let err = ledger.settle(&pair(), &execution).unwrap_err();
+        assert_eq!(err, format!("order {} spends 200 out of 150 reserved", bid));
+        assert_eq!(ledger.balance(1, "USDT").reserved, Decimal::from(150));
+        assert_eq!(ledger.balance(2, "BTC").reserved, Decimal::from(2));
+        assert_eq!(ledger.balance(1, "BTC"), Balance::default());
+
+        ledger.release_to(ask, Decimal::ZERO);
+        ledger.reserve(1, bid, "USDT", Decimal::from(50)).unwrap();
+        let err = ledger.settle(&pair(), &execution).unwrap_err();
+        assert_eq!(err, format!("order {} has no reservation", ask));
+        assert_eq!(ledger.balance(1, "USDT").reserved, Decimal::from(200));
+    }
+}
@@ -1,17 +1,56 @@
+mod actor;
+mod binance;
 mod engine;
 mod errors;
+mod fees;
+mod ffi;
+mod fix;
+mod gateway;
+mod http;
+mod itch;
+mod ledger;
 mod notifications;
 mod orderbook;
+mod positions;
+#[cfg(feature = "python")]
+mod python;
+mod replay;
+mod risk;
+mod tape;
+mod trading_state;
 mod tui;
+mod ws;
 
-pub use engine::{MatchingEngine, TradingPair};
+pub use actor::{ActorEngine, Command, EngineHandle, MarketHandle, Pending};
+pub use binance::{BinanceError, BinanceFacade};
+pub use engine::{EngineReader, MatchingEngine, OrderError, OrderFilter, TradingPair};
 pub use errors::Result;
+pub use fees::{FeeSchedule, FeeTier, Fees};
+pub use fix::{FixGateway, message as fix_message};
+pub use gateway::{GatewayResult, OrderGateway};
+pub use http::{ApiError, BookResponse, HttpApi, OrderResponse, PlaceOrder};
+pub use itch::{
+    ItchBody, ItchEncoder, ItchMessage, MarketState, MessageRef, SystemEvent, message_len,
+};
+pub use ledger::{AccountId, Balance, FEE_ACCOUNT, Ledger, Reservation};
 pub use notifications::{Notification, NotificationHandler};
 
 pub use orderbook::{
-    HalfBook, OrderBook, OrderBookState, OrderId, OrderRequest, OrderResult, OrderStatus,
-    OrderType, Price, Quantity, Side, TradeExecution, TradeOrder,
+    BookChange, BookReader, BookSnapshot, Fee, Fill, HalfBook, Indicative, Liquidity, OrderBook,
+    OrderBookState, OrderId, OrderRequest, OrderResult, OrderStatus, OrderType, Price, Quantity,
+    Side, Simulation, TradeCost, TradeExecution, TradeOrder,
 };
 
-use tracing_subscriber::fmt::format::FmtSpan;
-pub use tui::App;
+pub use positions::{MarkSource, PnlMethod, Position, PositionKeeper};
+pub use replay::{
+    Action, LatencyStats, OrderEvent, OrderOutcome, ReplayReport, ReplayedExecution, event_markets,
+    events_from_frame, load_events, read_frame, replay_events, write_frame,
+};
+pub use risk::{
+    DailyLossLimit, MaxNotional, MaxOpenOrders, MaxOrderQty, MaxPosition, PriceCollar, RiskChain,
+    RiskCheck, RiskContext, RiskRejection,
+};
+pub use tape::{Bar, BarBuilder, BarSpec, TradeTape, aggregate_bars, bars_frame, trades_frame};
+pub use trading_state::{CircuitBreaker, TradingState};
+pub use tui::{App, OwnOrder, View};
+pub use ws::{WsGateway, protocol};
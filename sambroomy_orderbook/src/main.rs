@@ -1,12 +1,114 @@
-use orderbooklib::{OrderBook, OrderRequest, OrderStatus, OrderType, Side};
-use polars::{frame::row::Row, prelude::*};
-use std::time::Instant;
-use tracing::debug;
-
-fn main() -> Result<(),Box<dyn std::error::Error>>{
-    let mut order_book = OrderBook::default();
-    let schema = Schema::from_iter(vec![
-        Field::new("Time".into(), DataType::Float64),
-        Field::new(name, dtype)
-    ])
+use orderbooklib::{
+    MatchingEngine, OrderBook, OrderGateway, TradingPair, event_markets, load_events,
+    replay_events, write_frame,
+};
+use std::{path::PathBuf, time::Instant};
+
+const USAGE: &str = "usage: orderbook-bin <events.csv|events.parquet> [--engine] [--market BASE_QUOTE] [--out DIR] [--format csv|parquet]";
+
+struct Args {
+    input: PathBuf,
+    engine: bool,
+    market: Option<TradingPair>,
+    out_dir: PathBuf,
+    format: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut input = None;
+    let mut engine = false;
+    let mut market = None;
+    let mut out_dir = PathBuf::from(".");
+    let mut format = "parquet".to_string();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--engine" => engine = true,
+            "--market" => market = Some(value()?.parse()?),
+            "--out" => out_dir = PathBuf::from(value()?),
+            "--format" => format = value()?,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument '{arg}'\n{USAGE}")),
+        }
+    }
+    if !matches!(format.as_str(), "csv" | "parquet") {
+        return Err(format!("unknown format '{format}'\n{USAGE}"));
+    }
+    Ok(Args {
+        input: input.ok_or(USAGE)?,
+        engine: engine || market.is_some(),
+        market,
+        out_dir,
+        format,
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args()?;
+
+    let load_start = Instant::now();
+    let mut events = load_events(&args.input)?;
+    println!(
+        "Loaded {} events from {} in {:?}",
+        events.len(),
+        args.input.display(),
+        load_start.elapsed()
+    );
+
+    let mut gateway: Box<dyn OrderGateway> = if args.engine {
+        let default_market = args
+            .market
+            .clone()
+            .unwrap_or_else(|| TradingPair::new("BASE".to_string(), "QUOTE".to_string()));
+        for event in events.iter_mut() {
+            event.market.get_or_insert_with(|| default_market.clone());
+        }
+        let mut engine = MatchingEngine::new();
+        for pair in event_markets(&events) {
+            engine.add_market(pair)?;
+        }
+        Box::new(engine)
+    } else if event_markets(&events).len() > 1 {
+        return Err(format!(
+            "{} holds several markets; replay it with --engine\n{USAGE}",
+            args.input.display()
+        )
+        .into());
+    } else {
+        Box::new(OrderBook::default())
+    };
+
+    let report = replay_events(gateway.as_mut(), &events);
+
+    println!(
+        "Replayed {} events in {:?} ({:.0} events/s)",
+        report.outcomes.len(),
+        report.elapsed,
+        report.throughput()
+    );
+    println!("Latency: {}", report.latency_stats());
+    println!(
+        "Executions: {} trades, {} traded",
+        report.executions.len(),
+        report.traded_volume()
+    );
+    let mut counts: Vec<_> = report.status_counts().into_iter().collect();
+    counts.sort();
+    for (status, count) in counts {
+        println!("  {status:<16} {count}");
+    }
+
+    std::fs::create_dir_all(&args.out_dir)?;
+    let executions_path = args.out_dir.join(format!("executions.{}", args.format));
+    let outcomes_path = args.out_dir.join(format!("orders.{}", args.format));
+    write_frame(&mut report.executions_frame()?, &executions_path)?;
+    write_frame(&mut report.outcomes_frame()?, &outcomes_path)?;
+    println!(
+        "Wrote {} and {}",
+        executions_path.display(),
+        outcomes_path.display()
+    );
+    Ok(())
 }
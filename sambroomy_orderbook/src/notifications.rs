@@ -1,5 +1,5 @@
 use crossbeam_channel::{Receiver, Sender, unbounded};
-use std::{collections::HashMap, hash::Hash};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{Side, orderbook::TradeExecution};
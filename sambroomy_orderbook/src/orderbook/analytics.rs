@@ -0,0 +1,124 @@
+use rust_decimal::Decimal;
+
+use super::book::{HalfBook, OrderBook};
+use super::types::*;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+/// Result of walking one side of the book for a given quantity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeCost {
+    pub filled_qty: Quantity,
+    pub avg_price: Price,
+    pub worst_price: Price,
+    /// Average price relative to mid, positive when it costs the taker.
+    pub slippage_bps: Decimal,
+}
+
+impl TradeCost {
+    pub fn is_complete(&self, qty: Quantity) -> bool {
+        self.filled_qty >= qty
+    }
+}
+
+fn top_n(book: &HalfBook, levels: usize) -> (Quantity, Decimal) {
+    book.levels()
+        .take(levels)
+        .fold((Decimal::ZERO, Decimal::ZERO), |(qty, notional), (p, q)| {
+            (qty + q, notional + p * q)
+        })
+}
+
+impl OrderBook {
+    pub fn mid_price(&self) -> Option<Price> {
+        Some((self.best_bid()? + self.best_ask()?) / Decimal::TWO)
+    }
+
+    /// Top-of-book mid weighted by the opposite side's size:
+    /// `(ask * bid_qty + bid * ask_qty) / (bid_qty + ask_qty)`.
+    pub fn microprice(&self) -> Option<Price> {
+        self.weighted_mid(1)
+    }
+
+    /// `(bid_qty - ask_qty) / (bid_qty + ask_qty)` over the best `levels`
+    /// levels of each side, in `[-1, 1]`.
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let (bid_qty, _) = top_n(&self.bids, levels);
+        let (ask_qty, _) = top_n(&self.asks, levels);
+        let total = bid_qty + ask_qty;
+        (total > Decimal::ZERO).then(|| (bid_qty - ask_qty) / total)
+    }
+
+    /// Microprice generalised to `levels` levels: each side's VWAP weighted
+    /// by the quantity resting on the other side.
+    pub fn weighted_mid(&self, levels: usize) -> Option<Price> {
+        let (bid_qty, bid_notional) = top_n(&self.bids, levels);
+        let (ask_qty, ask_notional) = top_n(&self.asks, levels);
+        if bid_qty == Decimal::ZERO || ask_qty == Decimal::ZERO {
+            return None;
+        }
+        let bid_vwap = bid_notional / bid_qty;
+        let ask_vwap = ask_notional / ask_qty;
+        Some((ask_vwap * bid_qty + bid_vwap * ask_qty) / (bid_qty + ask_qty))
+    }
+
+    /// Cost of a taker on `side` sweeping `qty` from the opposite side.
+    ///
+    /// `filled_qty` is less than `qty` when the book is too thin. Slippage is
+    /// measured against the mid, or against the touch on a one-sided book.
+    pub fn cost_to_trade(&self, side: Side, qty: impl Into<Quantity>) -> Option<TradeCost> {
+        let qty = qty.into();
+        let book = match side {
+            Side::Bid => &self.asks,
+            Side::Ask => &self.bids,
+        };
+        let reference = self.mid_price().or_else(|| book.best_price())?;
+
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        let mut worst = reference;
+        for (price, level_qty) in book.levels() {
+            if filled >= qty {
+                break;
+            }
+            let take = level_qty.min(qty - filled);
+            filled += take;
+            notional += take * price;
+            worst = price;
+        }
+        if filled == Decimal::ZERO {
+            return None;
+        }
+
+        let avg_price = notional / filled;
+        let signed = match side {
+            Side::Bid => avg_price - reference,
+            Side::Ask => reference - avg_price,
+        };
+        Some(TradeCost {
+            filled_qty: filled,
+            avg_price,
+            worst_price: worst,
+            slippage_bps: signed / reference * BPS,
+        })
+    }
+
+    /// Resting (bid, ask) quantity priced within `bps` basis points of mid.
+    pub fn liquidity_within_bps(&self, bps: impl Into<Decimal>) -> Option<(Quantity, Quantity)> {
+        let mid = self.mid_price()?;
+        let band = mid * bps.into() / BPS;
+        let bid_qty = self
+            .bids
+            .levels()
+            .take_while(|(p, _)| *p >= mid - band)
+            .map(|(_, q)| q)
+            .sum();
+        let ask_qty = self
+            .asks
+            .levels()
+            .take_while(|(p, _)| *p <= mid + band)
+            .map(|(_, q)| q)
+            .sum();
+        Some((bid_qty, ask_qty))
+    }
+}
@@ -0,0 +1,181 @@
+//! Call auctions: orders collect without matching, then uncross together
+//! at one equilibrium price.
+//!
+//! The equilibrium is the price that executes the most volume. Ties go to
+//! the smallest surplus, then to market pressure (the highest price when
+//! every candidate leaves surplus demand, the lowest when every one leaves
+//! surplus supply), and finally to the reference price, clamped to the
+//! remaining candidates, or their midpoint without one.
+
+use rust_decimal::Decimal;
+
+use super::book::OrderBook;
+use super::changes::BookChange;
+use super::orders::*;
+use super::types::*;
+
+/// What an uncross would do right now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Indicative {
+    /// `None` while no bid crosses an ask.
+    pub price: Option<Price>,
+    pub matched_qty: Quantity,
+    /// Quantity left unmatched at `price` on `imbalance_side`.
+    pub imbalance: Quantity,
+    pub imbalance_side: Option<Side>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct CallPhase {
+    reference: Option<Price>,
+}
+
+impl OrderBook {
+    /// Starts a call phase: orders rest without matching until
+    /// [`uncross`](Self::uncross). `reference`, usually the last trade,
+    /// settles ties between equilibrium prices.
+    pub fn start_auction(&mut self, reference: Option<Price>) {
+        self.auction = Some(CallPhase { reference });
+    }
+
+    pub fn in_auction(&self) -> bool {
+        self.auction.is_some()
+    }
+
+    /// The indicative uncross, or `None` outside a call phase.
+    pub fn indicative(&self) -> Option<Indicative> {
+        let phase = self.auction?;
+        Some(self.equilibrium(phase.reference))
+    }
+
+    /// During the call phase limit orders rest as they are, even when they
+    /// cross, while orders that cannot rest are cancelled unfilled.
+    pub(super) fn add_to_auction(
+        &mut self,
+        order: OrderRequest,
+        trade_order: TradeOrder,
+    ) -> OrderResult {
+        match order.order_type {
+            OrderType::Limit(price) => self.add_limit_order(order.side, price, trade_order.clone()),
+            OrderType::SystemLevel(price) => {
+                self.add_system_order(order.side, price, trade_order.clone())
+            }
+            OrderType::Market | OrderType::IOC(_) | OrderType::FOK(_) => {
+                self.retire(&OrderResult::from(trade_order.clone()))
+            }
+        }
+        OrderResult::from(trade_order)
+    }
+
+    /// Ends the call phase and executes every crossing order at the
+    /// equilibrium price, best price first and in time priority within a
+    /// level. Of each matched pair the later arrival counts as the taker.
+    pub fn uncross(&mut self) -> Vec<TradeExecution> {
+        let Some(indicative) = self.indicative() else {
+            return Vec::new();
+        };
+        self.auction = None;
+        let Some(price) = indicative.price else {
+            return Vec::new();
+        };
+        let mut executions = Vec::new();
+        let mut remaining = indicative.matched_qty;
+        while remaining > Decimal::ZERO {
+            let (Some(bid_price), Some(ask_price)) = (self.best_bid(), self.best_ask()) else {
+                break;
+            };
+            let (Some(bid), Some(ask)) = (
+                self.bids.front_mut(&bid_price),
+                self.asks.front_mut(&ask_price),
+            ) else {
+                break;
+            };
+            let (taker, maker) = if ask.created_at() > bid.created_at() {
+                (ask, bid)
+            } else {
+                (bid, ask)
+            };
+            let qty = maker.filled_by(taker, price);
+            remaining -= qty;
+            let execution = TradeExecution::new(qty, price, taker, maker, taker.side);
+            self.bids
+                .record(|| BookChange::Uncrossed(execution.clone()));
+            for (side, level) in [(Side::Bid, bid_price), (Side::Ask, ask_price)] {
+                if let Some(order) = self.get_mut_book(&side).pop_filled(&level) {
+                    self.order_loc.remove(&order.id);
+                    self.retire(&OrderResult::from(order));
+                }
+            }
+            executions.push(execution);
+        }
+        executions
+    }
+
+    fn equilibrium(&self, reference: Option<Price>) -> Indicative {
+        let bids: Vec<_> = self.bids.levels().collect();
+        let asks: Vec<_> = self.asks.levels().collect();
+        let demand = |p: Price| -> Quantity {
+            bids.iter()
+                .take_while(|(b, _)| *b >= p)
+                .map(|(_, q)| *q)
+                .sum()
+        };
+        let supply = |p: Price| -> Quantity {
+            asks.iter()
+                .take_while(|(a, _)| *a <= p)
+                .map(|(_, q)| *q)
+                .sum()
+        };
+        let mut prices: Vec<Price> = bids.iter().chain(&asks).map(|(p, _)| *p).collect();
+        prices.sort();
+        prices.dedup();
+
+        // (price, executable, demand minus supply)
+        let mut candidates: Vec<_> = prices
+            .into_iter()
+            .map(|p| {
+                let (demand, supply) = (demand(p), supply(p));
+                (p, demand.min(supply), demand - supply)
+            })
+            .collect();
+        let best = candidates.iter().map(|c| c.1).max().unwrap_or_default();
+        if best.is_zero() {
+            return Indicative {
+                price: None,
+                matched_qty: Decimal::ZERO,
+                imbalance: Decimal::ZERO,
+                imbalance_side: None,
+            };
+        }
+        candidates.retain(|c| c.1 == best);
+        let least = candidates
+            .iter()
+            .map(|c| c.2.abs())
+            .min()
+            .unwrap_or_default();
+        candidates.retain(|c| c.2.abs() == least);
+
+        let (low, high) = (candidates[0], candidates[candidates.len() - 1]);
+        let price = if candidates.iter().all(|c| c.2 > Decimal::ZERO) {
+            high.0
+        } else if candidates.iter().all(|c| c.2 < Decimal::ZERO) {
+            low.0
+        } else {
+            match reference {
+                Some(reference) => reference.clamp(low.0, high.0),
+                None => (low.0 + high.0) / Decimal::TWO,
+            }
+        };
+        let surplus = demand(price) - supply(price);
+        Indicative {
+            price: Some(price),
+            matched_qty: best,
+            imbalance: surplus.abs(),
+            imbalance_side: match surplus.cmp(&Decimal::ZERO) {
+                std::cmp::Ordering::Greater => Some(Side::Bid),
+                std::cmp::Ordering::Less => Some(Side::Ask),
+                std::cmp::Ordering::Equal => None,
+            },
+        }
+    }
+}
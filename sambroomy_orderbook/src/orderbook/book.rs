@@ -1,23 +1,28 @@
-use binance_spot_connector_rust::wallet::trade_fee;
-use dashmap::iter;
 use rust_decimal::Decimal;
 
 use tracing::{info, warn};
 
+use super::auction::CallPhase;
+use super::changes::BookChange;
+use super::history::OrderHistory;
+use super::orders::*;
 use super::price_levels::SparseVec;
+use super::snapshot::SnapshotPublisher;
 use super::types::*;
-use super::{orders::*, price_levels};
 
-use std::{
-    collections::{BTreeSet, HashMap, VecDeque},
-    net::Incoming,
-};
+use crossbeam_channel::Sender;
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
 
 #[derive(Debug)]
 pub struct HalfBook {
     s: Side,
     price_set: BTreeSet<Price>,
     price_levels: SparseVec<Price, PriceLevel>,
+    pub(super) changes: Option<Sender<BookChange>>,
+    /// Makers filled completely, kept for [`OrderBook::retire_filled`]
+    /// while the book retains terminal orders.
+    pub(super) filled: Option<Vec<TradeOrder>>,
 }
 
 impl HalfBook {
@@ -26,11 +31,27 @@ impl HalfBook {
             s,
             price_set: BTreeSet::new(),
             price_levels: SparseVec::with_capacity(10_000),
+            changes: None,
+            filled: None,
+        }
+    }
+
+    /// Reports `change` to the subscriber set up by
+    /// [`OrderBook::subscribe_changes`], if any.
+    pub fn record(&self, change: impl FnOnce() -> BookChange) {
+        if let Some(changes) = &self.changes {
+            let _ = changes.send(change());
         }
     }
 
     pub fn add_order(&mut self, price: impl Into<Price>, order: TradeOrder) {
         let price = price.into();
+        self.record(|| BookChange::Added {
+            order_id: order.id,
+            side: self.s,
+            price,
+            qty: order.remaining_qty,
+        });
         if let Some(level) = self.price_levels.get_mut(&price) {
             level.push_back(order);
         } else {
@@ -49,6 +70,11 @@ impl HalfBook {
             self.price_levels.remove(price);
             self.price_set.remove(price);
         }
+        self.record(|| BookChange::Deleted {
+            order_id,
+            side: self.s,
+            price: *price,
+        });
         removed_order
     }
 
@@ -63,15 +89,21 @@ impl HalfBook {
             while !price_level.is_empty() && incoming_order.remaining_qty > Decimal::ZERO {
                 if let Some(mut existing_order) = price_level.pop_front() {
                     let fill_qty = existing_order.filled_by(incoming_order, price);
-                    executions.push(TradeExecution::new(
+                    let execution = TradeExecution::new(
                         fill_qty,
                         price,
                         incoming_order,
                         &existing_order,
                         self.s.opposite(),
-                    ));
+                    );
+                    if let Some(changes) = &self.changes {
+                        let _ = changes.send(BookChange::Executed(execution.clone()));
+                    }
+                    executions.push(execution);
                     if existing_order.remaining_qty > Decimal::ZERO {
                         price_level.push_front(existing_order);
+                    } else if let Some(filled) = self.filled.as_mut() {
+                        filled.push(existing_order);
                     }
                 }
             }
@@ -83,6 +115,49 @@ impl HalfBook {
         executions
     }
 
+    /// Matches `incoming_order` against copies of the resting orders, in the
+    /// same order and with the same fill arithmetic as [`match_order`], but
+    /// leaves this side untouched.
+    ///
+    /// [`match_order`]: HalfBook::match_order
+    pub fn simulate_match(
+        &self,
+        incoming_order: &mut TradeOrder,
+        limit: Option<Price>,
+    ) -> Vec<TradeExecution> {
+        let mut executions = Vec::new();
+        for price in self.iter_prices().take_while(|&p| self.crosses(limit, p)) {
+            let Some(price_level) = self.price_levels.get(&price) else {
+                continue;
+            };
+            for resting in price_level {
+                if incoming_order.remaining_qty == Decimal::ZERO {
+                    return executions;
+                }
+                let mut existing_order = resting.clone();
+                let fill_qty = existing_order.filled_by(incoming_order, price);
+                executions.push(TradeExecution::new(
+                    fill_qty,
+                    price,
+                    incoming_order,
+                    &existing_order,
+                    self.s.opposite(),
+                ));
+            }
+        }
+        executions
+    }
+
+    /// Whether an incoming order limited at `limit` (`None` for market
+    /// orders) trades with resting orders at `price` on this side.
+    pub fn crosses(&self, limit: Option<Price>, price: Price) -> bool {
+        match (self.s, limit) {
+            (_, None) => true,
+            (Side::Ask, Some(limit)) => price <= limit,
+            (Side::Bid, Some(limit)) => price >= limit,
+        }
+    }
+
     pub fn best_price(&self) -> Option<Price> {
         match self.s {
             Side::Ask => self.price_levels.min_index(),
@@ -112,6 +187,20 @@ impl HalfBook {
         }
     }
 
+    /// Levels from the best price outwards, summed straight from the
+    /// resting orders without collecting prices first.
+    pub fn levels(&self) -> impl Iterator<Item = (Price, Quantity)> + '_ {
+        let prices: Box<dyn Iterator<Item = &Price>> = match self.s {
+            Side::Ask => Box::new(self.price_set.iter()),
+            Side::Bid => Box::new(self.price_set.iter().rev()),
+        };
+        prices.map(|price| (*price, self.get_total_qty(price).unwrap_or(Decimal::ZERO)))
+    }
+
+    pub fn side(&self) -> Side {
+        self.s
+    }
+
     pub fn show_depth(&self) {
         let prices: Vec<_> = match self.s {
             Side::Ask => self.price_set.iter().rev().cloned().collect(),
@@ -210,23 +299,115 @@ impl HalfBook {
         self.price_levels.iter().map(|(_, level)| level.len()).sum()
     }
 
+    pub(super) fn front_mut(&mut self, price: &Price) -> Option<&mut TradeOrder> {
+        self.price_levels.get_mut(price)?.front_mut()
+    }
+
+    /// Removes the first order at `price` if it has nothing left, along
+    /// with the level once empty, and returns it.
+    pub(super) fn pop_filled(&mut self, price: &Price) -> Option<TradeOrder> {
+        let level = self.price_levels.get_mut(price)?;
+        if level.front()?.remaining_qty > Decimal::ZERO {
+            return None;
+        }
+        let order = level.pop_front()?;
+        if level.is_empty() {
+            self.price_levels.remove(price);
+            self.price_set.remove(price);
+        }
+        Some(order)
+    }
+
+    /// Checks that `price_set` holds exactly the non-empty levels and that
+    /// every resting order is live and on this side, returning each order's
+    /// id and price.
+    fn check_invariants(&self) -> Result<Vec<(OrderId, Price)>, String> {
+        let mut resting = Vec::new();
+        let mut level_count = 0;
+        for (price, level) in self.price_levels.iter() {
+            level_count += 1;
+            if level.is_empty() {
+                return Err(format!("{:?} level at {} is empty", self.s, price));
+            }
+            if !self.price_set.contains(price) {
+                return Err(format!(
+                    "{:?} level at {} missing from price_set",
+                    self.s, price
+                ));
+            }
+            for order in level {
+                if order.side != self.s {
+                    return Err(format!("order {} rests on the wrong side", order.id));
+                }
+                if order.remaining_qty <= Decimal::ZERO {
+                    return Err(format!("order {} rests with nothing left", order.id));
+                }
+                resting.push((order.id, *price));
+            }
+        }
+        if level_count != self.price_set.len() {
+            return Err(format!(
+                "{:?} price_set has {} prices for {} levels",
+                self.s,
+                self.price_set.len(),
+                level_count
+            ));
+        }
+        Ok(resting)
+    }
+
     pub fn clear(&mut self) {
+        for price in self.price_set.iter() {
+            for order in self.price_levels.get(price).into_iter().flatten() {
+                self.record(|| BookChange::Deleted {
+                    order_id: order.id,
+                    side: self.s,
+                    price: *price,
+                });
+            }
+        }
         self.price_set.clear();
         self.price_levels = SparseVec::with_capacity(10_000);
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct OrderBookState {
     pub asks: Vec<(Price, Quantity)>,
     pub bids: Vec<(Price, Quantity)>,
 }
 
+/// Outcome of [`OrderBook::simulate`].
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub executions: Vec<TradeExecution>,
+    pub avg_price: Option<Price>,
+    pub remaining_qty: Quantity,
+    pub status: OrderStatus,
+    pub result: OrderResult,
+}
+
+impl Simulation {
+    fn new(result: OrderResult, executions: Vec<TradeExecution>) -> Self {
+        let filled = result.filled_qty();
+        Self {
+            avg_price: (filled > Decimal::ZERO).then(|| result.avr_fill_price()),
+            remaining_qty: result.remaining_qty,
+            status: result.status,
+            executions,
+            result,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OrderBook {
     pub asks: HalfBook,
     pub bids: HalfBook,
     pub order_loc: HashMap<OrderId, (Side, Price)>,
+    pub(super) publisher: SnapshotPublisher,
+    pub(super) auction: Option<CallPhase>,
+    pub(super) history: Option<OrderHistory>,
 }
 
 impl Default for OrderBook {
@@ -235,6 +416,9 @@ impl Default for OrderBook {
             asks: HalfBook::new(Side::Ask),
             bids: HalfBook::new(Side::Bid),
             order_loc: HashMap::with_capacity(10_000),
+            publisher: SnapshotPublisher::default(),
+            auction: None,
+            history: None,
         }
     }
 }
@@ -288,40 +472,166 @@ impl OrderBook {
         let (side, price) = self.order_loc.remove(&order_id)?;
         let book = self.get_mut_book(&side);
         let order = book.remove_order(&price, order_id)?;
-        Some(OrderResult::cancelled(order))
+        let result = OrderResult::cancelled(order);
+        self.retire(&result);
+        Some(result)
     }
 
+    /// Takes `qty` off a resting order, keeping its queue priority, and
+    /// deletes it once nothing is left. A negative `qty` is refused.
     pub fn cancel_order(
         &mut self,
         order_id: OrderId,
         qty: impl Into<Quantity>,
     ) -> Option<OrderResult> {
+        let qty = qty.into();
+        if qty < Decimal::ZERO {
+            return None;
+        }
         let trade_order = self.get_order_mut(&order_id)?;
+        let before = trade_order.remaining_qty;
         trade_order.cancel(qty);
         if trade_order.remaining_qty == Decimal::ZERO {
             return self.delete_order(order_id);
         }
-        Some(OrderResult::from(trade_order.clone()))
+        let result = OrderResult::from(trade_order.clone());
+        let reduced = before - result.remaining_qty;
+        let (side, price) = self.order_loc[&order_id];
+        self.get_book(&side).record(|| BookChange::Reduced {
+            order_id,
+            side,
+            price,
+            qty: reduced,
+        });
+        Some(result)
+    }
+
+    fn fok_rejected(opposite_book: &HalfBook, order: &OrderRequest) -> bool {
+        let OrderType::FOK(price) = order.order_type else {
+            return false;
+        };
+        let available_qty = opposite_book.get_available_quantity(price);
+        info!("Available qty: {}", available_qty);
+        info!("Order qty: {}", order.qty);
+        if available_qty < order.qty {
+            warn!("FOK order failed");
+            return true;
+        }
+        false
     }
 
     pub fn add_order(&mut self, order: OrderRequest) -> (OrderResult, Vec<TradeExecution>) {
+        self.enter(order, TradeOrder::from(order))
+    }
+
+    /// Matches `trade_order`, the book's side of `order`, and rests whatever
+    /// the order type lets rest.
+    fn enter(
+        &mut self,
+        order: OrderRequest,
+        mut trade_order: TradeOrder,
+    ) -> (OrderResult, Vec<TradeExecution>) {
+        if self.auction.is_some() {
+            return (self.add_to_auction(order, trade_order), Vec::new());
+        }
         let opposite_book = self.get_mut_opposite_book(&order.side);
         let mut executions = Vec::new();
-        if let OrderType::FOK(price) = order.order_type {
-            let available_qty = opposite_book.get_available_quantity(price);
-            info!("Available qty: {}", available_qty);
-            info!("Order qty: {}", order.qty);
-            if available_qty < order.qty {
-                warn!("FOK order failed");
-                return (OrderResult::from(order), executions);
+        if Self::fok_rejected(opposite_book, &order) {
+            let result = OrderResult::from(trade_order);
+            self.retire(&result);
+            return (result, executions);
+        }
+
+        let crossing_prices: Vec<Price> = opposite_book
+            .iter_prices()
+            .take_while(|&p| opposite_book.crosses(order.price(), p))
+            .collect();
+        for price in crossing_prices {
+            if trade_order.remaining_qty == Decimal::ZERO {
+                break;
             }
-        };
+            executions.extend(opposite_book.match_order(&mut trade_order, price));
+        }
+
+        for execution in &executions {
+            if self.get_order(execution.maker_order_id).is_none() {
+                self.order_loc.remove(&execution.maker_order_id);
+            }
+        }
+        self.retire_filled(order.side.opposite());
 
-        let mut trade_order = TradeOrder::from(order);
+        if trade_order.remaining_qty > Decimal::ZERO {
+            match order.order_type {
+                OrderType::Limit(price) => {
+                    self.add_limit_order(order.side, price, trade_order.clone())
+                }
+                OrderType::SystemLevel(price) => {
+                    self.add_system_order(order.side, price, trade_order.clone())
+                }
+                OrderType::Market | OrderType::IOC(_) | OrderType::FOK(_) => {}
+            }
+        }
+
+        let result = OrderResult::from(trade_order);
+        if !self.order_loc.contains_key(&result.get_id()) {
+            self.retire(&result);
+        }
+        (result, executions)
+    }
 
-        
+    /// Changes the price and/or quantity of a resting order.
+    ///
+    /// Reducing the quantity at the same price keeps queue priority; any other
+    /// change re-enters the order at the back of the new level, where it may
+    /// match immediately. `qty` is the new open quantity; fills so far stay
+    /// on the order and count towards its initial quantity. A non-positive
+    /// `price` or `qty` is refused.
+    pub fn amend_order(
+        &mut self,
+        order_id: OrderId,
+        price: impl Into<Price>,
+        qty: impl Into<Quantity>,
+    ) -> Option<(OrderResult, Vec<TradeExecution>)> {
+        let (price, qty) = (price.into(), qty.into());
+        if price <= Decimal::ZERO || qty <= Decimal::ZERO {
+            return None;
+        }
+        let (side, current_price) = *self.order_loc.get(&order_id)?;
+        let order = self.get_order(order_id)?;
+        let order_type = match order.order_type {
+            OrderType::Limit(_) => OrderType::Limit(price),
+            _ => return None,
+        };
+        if price == current_price && qty <= order.remaining_qty {
+            let reduce_by = order.remaining_qty - qty;
+            return self
+                .cancel_order(order_id, reduce_by)
+                .map(|r| (r, Vec::new()));
+        }
+        let request = OrderRequest::new_with_id(order_id, side, qty, order_type);
+        let amended = order.amended(&request);
+        self.delete_order(order_id)?;
+        Some(self.enter(request, amended))
+    }
 
-        
+    /// Runs `order` through the same FOK check and matching rules as
+    /// [`add_order`] against copies of the resting orders, without changing
+    /// the book or `order_loc`. During a call phase nothing trades, as in
+    /// [`add_order`], so Market, IOC and FOK orders come back cancelled.
+    ///
+    /// [`add_order`]: OrderBook::add_order
+    pub fn simulate(&self, order: &OrderRequest) -> Simulation {
+        if self.auction.is_some() {
+            return Simulation::new(OrderResult::from(TradeOrder::from(*order)), Vec::new());
+        }
+        let opposite_book = self.get_book(&order.side.opposite());
+        let mut trade_order = TradeOrder::from(*order);
+        let executions = if Self::fok_rejected(opposite_book, order) {
+            Vec::new()
+        } else {
+            opposite_book.simulate_match(&mut trade_order, order.price())
+        };
+        Simulation::new(OrderResult::from(trade_order), executions)
     }
 
     pub fn add_limit_order(&mut self, side: Side, price: impl Into<Price>, order: TradeOrder) {
@@ -335,6 +645,13 @@ impl OrderBook {
         match self.get_order_mut(&order.id) {
             Some(existing_order) => {
                 assert_eq!(existing_order.merage(order), None);
+                let (order_id, qty) = (existing_order.id, existing_order.remaining_qty);
+                self.get_book(&side).record(|| BookChange::Replaced {
+                    order_id,
+                    side,
+                    price,
+                    qty,
+                });
             }
             None => {
                 self.order_loc.insert(order.id, (side, price));
@@ -361,7 +678,7 @@ impl OrderBook {
         }
     }
 
-    fn get_mut_book(&mut self, side: &Side) -> &mut HalfBook {
+    pub(super) fn get_mut_book(&mut self, side: &Side) -> &mut HalfBook {
         match side {
             Side::Ask => &mut self.asks,
             Side::Bid => &mut self.bids,
@@ -421,8 +738,334 @@ impl OrderBook {
     }
 
     pub fn clear(&mut self) {
+        if self.history.is_some() {
+            let resting: Vec<_> = self.open_orders(None, ..).into_iter().cloned().collect();
+            for order in resting {
+                self.retire(&OrderResult::cancelled(order));
+            }
+        }
         self.asks.clear();
         self.bids.clear();
         self.order_loc.clear();
     }
+
+    /// Checks the book's internal consistency: outside a call auction the
+    /// best bid is below the best ask, each side's `price_set` matches its
+    /// non-empty levels, and `order_loc` indexes exactly the resting orders.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.auction.is_none()
+            && let (Some(bid), Some(ask)) = self.best_prices()
+            && bid >= ask
+        {
+            return Err(format!("book is crossed: bid {} >= ask {}", bid, ask));
+        }
+        let mut resting = 0;
+        for book in [&self.bids, &self.asks] {
+            for (order_id, price) in book.check_invariants()? {
+                resting += 1;
+                if self.order_loc.get(&order_id) != Some(&(book.side(), price)) {
+                    return Err(format!(
+                        "order {} rests at {:?} {} but order_loc has {:?}",
+                        order_id,
+                        book.side(),
+                        price,
+                        self.order_loc.get(&order_id)
+                    ));
+                }
+            }
+        }
+        if resting != self.order_loc.len() {
+            return Err(format!(
+                "order_loc has {} entries for {} resting orders",
+                self.order_loc.len(),
+                resting
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn seeded_book() -> OrderBook {
+        let mut book = OrderBook::default();
+        for (side, qty, price) in [
+            (Side::Ask, 5, 101),
+            (Side::Ask, 3, 101),
+            (Side::Ask, 7, 102),
+            (Side::Ask, 10, 105),
+            (Side::Bid, 4, 99),
+            (Side::Bid, 6, 98),
+            (Side::Bid, 8, 95),
+        ] {
+            book.add_order(OrderRequest::new(side, qty, OrderType::limit(price)));
+        }
+        book
+    }
+
+    fn assert_agree(sim: &Simulation, result: &OrderResult, executions: &[TradeExecution]) {
+        let key = |e: &TradeExecution| {
+            (
+                e.qty,
+                e.price,
+                e.taker_order_id,
+                e.maker_order_id,
+                e.take_side,
+            )
+        };
+        assert_eq!(
+            sim.executions.iter().map(key).collect::<Vec<_>>(),
+            executions.iter().map(key).collect::<Vec<_>>()
+        );
+        assert_eq!(sim.status, result.status);
+        assert_eq!(sim.remaining_qty, result.remaining_qty);
+        assert_eq!(sim.result.filled_qty(), result.filled_qty());
+        if result.filled_qty() > Decimal::ZERO {
+            assert_eq!(sim.avg_price, Some(result.avr_fill_price()));
+        } else {
+            assert_eq!(sim.avg_price, None);
+        }
+    }
+
+    #[test]
+    fn reduce_and_amend_refuse_non_positive_values() {
+        let mut book = seeded_book();
+        let id = book.get_orders_at_price(Side::Ask, 101).unwrap()[0].id;
+        let state = book.get_order_book_state();
+
+        assert!(book.cancel_order(id, -5).is_none());
+        assert!(book.amend_order(id, 0, 5).is_none());
+        assert!(book.amend_order(id, 103, 0).is_none());
+        assert!(book.amend_order(id, 103, -1).is_none());
+        assert_eq!(book.get_order_book_state(), state);
+        assert_eq!(book.get_order(id).unwrap().remaining_qty, Decimal::from(5));
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn simulate_leaves_book_untouched() {
+        let book = seeded_book();
+        let state = book.get_order_book_state();
+        let order_loc = book.order_loc.clone();
+
+        let sim = book.simulate(&OrderRequest::new(Side::Bid, 100, OrderType::Market));
+
+        assert_eq!(sim.executions.len(), 4);
+        assert_eq!(book.get_order_book_state(), state);
+        assert_eq!(book.order_loc, order_loc);
+        assert_eq!(
+            book.get_orders_at_price(Side::Ask, 101).unwrap()[0].remaining_qty,
+            Decimal::from(5)
+        );
+    }
+
+    #[test]
+    fn simulate_agrees_with_add_order_for_each_order_type() {
+        let requests = [
+            OrderRequest::new(Side::Bid, 6, OrderType::Market),
+            OrderRequest::new(Side::Ask, 50, OrderType::Market),
+            OrderRequest::new(Side::Bid, 12, OrderType::limit(102)),
+            OrderRequest::new(Side::Bid, 2, OrderType::limit(100)),
+            OrderRequest::new(Side::Ask, 7, OrderType::ioc(98)),
+            OrderRequest::new(Side::Ask, 20, OrderType::ioc(98)),
+            OrderRequest::new(Side::Bid, 15, OrderType::fok(102)),
+            OrderRequest::new(Side::Bid, 16, OrderType::fok(102)),
+            OrderRequest::new(Side::Ask, 3, OrderType::system_level(99)),
+        ];
+        for request in requests {
+            let mut book = seeded_book();
+            let sim = book.simulate(&request);
+            let (result, executions) = book.add_order(request);
+            assert_agree(&sim, &result, &executions);
+        }
+    }
+
+    #[test]
+    fn simulate_agrees_with_add_order_during_an_auction() {
+        let requests = [
+            OrderRequest::new(Side::Bid, 6, OrderType::Market),
+            OrderRequest::new(Side::Bid, 12, OrderType::limit(102)),
+            OrderRequest::new(Side::Ask, 7, OrderType::ioc(98)),
+            OrderRequest::new(Side::Bid, 15, OrderType::fok(102)),
+            OrderRequest::new(Side::Ask, 3, OrderType::system_level(99)),
+        ];
+        for request in requests {
+            let mut book = seeded_book();
+            book.start_auction(None);
+            let sim = book.simulate(&request);
+            let (result, executions) = book.add_order(request);
+            assert_agree(&sim, &result, &executions);
+            assert!(sim.executions.is_empty());
+        }
+    }
+
+    #[test]
+    fn simulate_agrees_with_add_order_on_random_flow() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut book = OrderBook::default();
+        for _ in 0..5_000 {
+            let side = if rng.random_bool(0.5) {
+                Side::Bid
+            } else {
+                Side::Ask
+            };
+            let price = Decimal::from(rng.random_range(90..=110));
+            let order_type = match rng.random_range(0..10) {
+                0 => OrderType::Market,
+                1 => OrderType::ioc(price),
+                2 => OrderType::fok(price),
+                _ => OrderType::limit(price),
+            };
+            let request = OrderRequest::new(side, rng.random_range(1..=20), order_type);
+
+            let state = book.get_order_book_state();
+            let sim = book.simulate(&request);
+            assert_eq!(book.get_order_book_state(), state);
+
+            let (result, executions) = book.add_order(request);
+            assert_agree(&sim, &result, &executions);
+        }
+    }
+
+    #[test]
+    fn amending_a_partly_filled_order_keeps_its_fills() {
+        let mut book = seeded_book();
+        book.set_order_retention(Some(std::time::Duration::from_secs(60)));
+        let maker = book.get_orders_at_price(Side::Ask, 101).unwrap()[0].id;
+        book.add_order(OrderRequest::new(Side::Bid, 2, OrderType::Market));
+
+        // A larger quantity at the same price, then a new price.
+        for (price, qty, initial) in [(101, 4, 6), (103, 3, 5)] {
+            let (result, executions) = book.amend_order(maker, price, qty).unwrap();
+            assert!(executions.is_empty());
+            assert_eq!(result.status, OrderStatus::PartiallyFilled);
+            assert_eq!(result.remaining_qty, Decimal::from(qty));
+            assert_eq!(result.initial_qty(), Decimal::from(initial));
+            assert_eq!(result.fills().len(), 1);
+        }
+
+        book.add_order(OrderRequest::new(Side::Bid, 20, OrderType::limit(103)));
+        let filled = book.order_status(maker).unwrap();
+        assert_eq!(filled.status, OrderStatus::Filled);
+        assert_eq!(filled.filled_qty(), Decimal::from(5));
+        let prices: Vec<_> = filled.fills().iter().map(|f| f.price).collect();
+        assert_eq!(prices, [Decimal::from(101), Decimal::from(103)]);
+    }
+
+    #[test]
+    fn check_invariants_reports_corruption() {
+        let mut book = seeded_book();
+        book.check_invariants().unwrap();
+
+        let (id, loc) = book
+            .order_loc
+            .iter()
+            .map(|(id, loc)| (*id, *loc))
+            .next()
+            .unwrap();
+        book.order_loc.insert(id, (loc.0.opposite(), loc.1));
+        assert!(book.check_invariants().is_err());
+        book.order_loc.remove(&id);
+        assert!(book.check_invariants().is_err());
+
+        let mut book = seeded_book();
+        let order = TradeOrder::from(OrderRequest::new(Side::Bid, 1, OrderType::limit(101)));
+        book.add_limit_order(Side::Bid, 101, order);
+        assert!(book.check_invariants().unwrap_err().contains("crossed"));
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    // seeded_book: asks 8 @ 101, 7 @ 102, 10 @ 105; bids 4 @ 99, 6 @ 98, 8 @ 95.
+
+    #[test]
+    fn mid_microprice_and_weighted_mid() {
+        let book = seeded_book();
+        assert_eq!(book.mid_price(), Some(dec("100")));
+        // (101 * 4 + 99 * 8) / 12
+        assert_eq!(
+            book.microprice().map(|p| p.round_dp(4)),
+            Some(dec("99.6667"))
+        );
+        assert_eq!(book.microprice(), book.weighted_mid(1));
+        // Bid VWAP 984 / 10, ask VWAP 1522 / 15: (1522 / 15 * 10 + 98.4 * 15) / 25
+        assert_eq!(
+            book.weighted_mid(2).map(|p| p.round_dp(4)),
+            Some(dec("99.6267"))
+        );
+        assert_eq!(book.weighted_mid(0), None);
+    }
+
+    #[test]
+    fn imbalance_over_levels() {
+        let book = seeded_book();
+        assert_eq!(
+            book.imbalance(1).map(|i| i.round_dp(4)),
+            Some(dec("-0.3333"))
+        );
+        assert_eq!(book.imbalance(2), Some(dec("-0.2")));
+        assert_eq!(book.imbalance(10), book.imbalance(3));
+        assert_eq!(book.imbalance(0), None);
+    }
+
+    #[test]
+    fn cost_to_trade_walks_the_opposite_side() {
+        let book = seeded_book();
+        let cost = book.cost_to_trade(Side::Bid, 10).unwrap();
+        assert_eq!(cost.filled_qty, dec("10"));
+        // (8 * 101 + 2 * 102) / 10 against a mid of 100.
+        assert_eq!(cost.avg_price, dec("101.2"));
+        assert_eq!(cost.worst_price, dec("102"));
+        assert_eq!(cost.slippage_bps, dec("120"));
+
+        let cost = book.cost_to_trade(Side::Ask, 5).unwrap();
+        assert_eq!(
+            (cost.avg_price, cost.worst_price, cost.slippage_bps),
+            (dec("98.8"), dec("98"), dec("120"))
+        );
+
+        let cost = book.cost_to_trade(Side::Bid, 100).unwrap();
+        assert_eq!(cost.filled_qty, dec("25"));
+        assert_eq!(cost.avg_price, dec("102.88"));
+        assert_eq!(cost.worst_price, dec("105"));
+        assert!(!cost.is_complete(dec("100")));
+        assert_eq!(book.cost_to_trade(Side::Bid, 0), None);
+    }
+
+    #[test]
+    fn liquidity_within_bps_of_mid() {
+        let book = seeded_book();
+        assert_eq!(book.liquidity_within_bps(0), Some((dec("0"), dec("0"))));
+        assert_eq!(book.liquidity_within_bps(100), Some((dec("4"), dec("8"))));
+        assert_eq!(book.liquidity_within_bps(300), Some((dec("10"), dec("15"))));
+        assert_eq!(
+            book.liquidity_within_bps(1000),
+            Some((dec("18"), dec("25")))
+        );
+    }
+
+    #[test]
+    fn analytics_on_empty_and_one_sided_books() {
+        let mut book = OrderBook::default();
+        assert_eq!(book.mid_price(), None);
+        assert_eq!(book.microprice(), None);
+        assert_eq!(book.imbalance(5), None);
+        assert_eq!(book.cost_to_trade(Side::Bid, 1), None);
+        assert_eq!(book.liquidity_within_bps(100), None);
+
+        book.add_order(OrderRequest::new(Side::Ask, 8, OrderType::limit(101)));
+        assert_eq!(book.mid_price(), None);
+        assert_eq!(book.weighted_mid(1), None);
+        assert_eq!(book.imbalance(1), Some(dec("-1")));
+        assert_eq!(book.liquidity_within_bps(100), None);
+        // Slippage falls back to the touch without a mid.
+        let cost = book.cost_to_trade(Side::Bid, 2).unwrap();
+        assert_eq!((cost.avg_price, cost.slippage_bps), (dec("101"), dec("0")));
+        assert_eq!(book.cost_to_trade(Side::Ask, 1), None);
+    }
 }
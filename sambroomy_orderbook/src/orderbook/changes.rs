@@ -0,0 +1,61 @@
+//! Visible book mutations, recorded where [`HalfBook`] changes so that
+//! order-by-order feeds can be rebuilt without diffing snapshots.
+//!
+//! [`HalfBook`]: super::HalfBook
+
+use crossbeam_channel::{Receiver, unbounded};
+
+use super::{OrderBook, OrderId, Price, Quantity, Side, TradeExecution};
+
+#[derive(Debug, Clone)]
+pub enum BookChange {
+    /// An order started resting.
+    Added {
+        order_id: OrderId,
+        side: Side,
+        price: Price,
+        qty: Quantity,
+    },
+    /// A resting order traded with an incoming one; the maker loses
+    /// `execution.qty` and leaves the book once nothing remains.
+    Executed(TradeExecution),
+    /// Two resting orders traded when an auction uncrossed; both lose
+    /// `execution.qty` and leave the book once nothing remains.
+    Uncrossed(TradeExecution),
+    /// A resting order lost `qty` without trading and keeps its place.
+    Reduced {
+        order_id: OrderId,
+        side: Side,
+        price: Price,
+        qty: Quantity,
+    },
+    /// A resting order now has `qty` in total and keeps its place.
+    Replaced {
+        order_id: OrderId,
+        side: Side,
+        price: Price,
+        qty: Quantity,
+    },
+    /// A resting order left the book without trading.
+    Deleted {
+        order_id: OrderId,
+        side: Side,
+        price: Price,
+    },
+}
+
+impl OrderBook {
+    /// Starts sending every [`BookChange`] of this book, in the order the
+    /// mutations happen. Replaces any previous subscriber.
+    pub fn subscribe_changes(&mut self) -> Receiver<BookChange> {
+        let (sender, receiver) = unbounded();
+        self.bids.changes = Some(sender.clone());
+        self.asks.changes = Some(sender);
+        receiver
+    }
+
+    pub fn unsubscribe_changes(&mut self) {
+        self.bids.changes = None;
+        self.asks.changes = None;
+    }
+}
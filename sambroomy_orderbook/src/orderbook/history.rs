@@ -0,0 +1,117 @@
+//! Order status queries: open orders straight from the book, and terminal
+//! orders kept for a while after they leave it.
+
+use std::collections::{HashMap, VecDeque};
+use std::ops::RangeBounds;
+use std::time::Duration;
+
+use super::book::OrderBook;
+use super::orders::*;
+use super::types::*;
+
+/// Orders that have left the book, filled or cancelled, kept until
+/// `retention` has passed since they did.
+#[derive(Debug)]
+pub(super) struct OrderHistory {
+    retention: Duration,
+    orders: HashMap<OrderId, (Timestamp, OrderResult)>,
+    expiry: VecDeque<(Timestamp, OrderId)>,
+}
+
+impl OrderHistory {
+    fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            orders: HashMap::new(),
+            expiry: VecDeque::new(),
+        }
+    }
+
+    fn expired(&self, retired: Timestamp, now: Timestamp) -> bool {
+        now.duration_since(retired)
+            .is_ok_and(|age| age > self.retention)
+    }
+
+    fn retire(&mut self, result: OrderResult) {
+        let now = timestamp();
+        while let Some(&(retired, id)) = self.expiry.front()
+            && self.expired(retired, now)
+        {
+            self.expiry.pop_front();
+            // An amended order can retire more than once; only its latest
+            // entry counts.
+            if self.orders.get(&id).is_some_and(|(at, _)| *at == retired) {
+                self.orders.remove(&id);
+            }
+        }
+        self.expiry.push_back((now, result.get_id()));
+        self.orders.insert(result.get_id(), (now, result));
+    }
+
+    fn get(&self, order_id: OrderId) -> Option<&OrderResult> {
+        let (retired, result) = self.orders.get(&order_id)?;
+        (!self.expired(*retired, timestamp())).then_some(result)
+    }
+}
+
+impl OrderBook {
+    /// Keeps orders that leave the book answering [`order_status`] for
+    /// `retention`, or forgets them at once with `None`, the default.
+    ///
+    /// [`order_status`]: OrderBook::order_status
+    pub fn set_order_retention(&mut self, retention: Option<Duration>) {
+        self.history = retention.map(OrderHistory::new);
+        let filled = retention.map(|_| Vec::new());
+        self.bids.filled = filled.clone();
+        self.asks.filled = filled;
+    }
+
+    pub fn order_retention(&self) -> Option<Duration> {
+        self.history.as_ref().map(|h| h.retention)
+    }
+
+    /// The order's current state while it rests, otherwise its final state
+    /// if it left the book within the retention period.
+    pub fn order_status(&self, order_id: OrderId) -> Option<OrderResult> {
+        if let Some(order) = self.get_order(order_id) {
+            return Some(OrderResult::from(order.clone()));
+        }
+        self.history.as_ref()?.get(order_id).cloned()
+    }
+
+    /// Resting orders on `side`, or both sides with bids first, priced
+    /// within `prices`. Each side runs from the best price outwards and
+    /// each level in time priority.
+    pub fn open_orders(
+        &self,
+        side: Option<Side>,
+        prices: impl RangeBounds<Price>,
+    ) -> Vec<&TradeOrder> {
+        [&self.bids, &self.asks]
+            .into_iter()
+            .filter(|book| side.is_none_or(|s| s == book.side()))
+            .flat_map(|book| {
+                book.iter_prices()
+                    .filter(|price| prices.contains(price))
+                    .flat_map(|price| book.get_orders_at_price(price).unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// Records the final state of an order that has left the book.
+    pub(super) fn retire(&mut self, result: &OrderResult) {
+        if let Some(history) = self.history.as_mut() {
+            history.retire(result.clone());
+        }
+    }
+
+    /// Retires the makers `side` filled completely since the last call.
+    pub(super) fn retire_filled(&mut self, side: Side) {
+        let Some(filled) = self.get_mut_book(&side).filled.as_mut() else {
+            return;
+        };
+        for order in std::mem::take(filled) {
+            self.retire(&OrderResult::from(order));
+        }
+    }
+}
@@ -1,8 +1,18 @@
+mod analytics;
+mod auction;
 mod book;
+mod changes;
+mod history;
 mod orders;
 mod price_levels;
+mod snapshot;
 mod types;
 
+pub use analytics::TradeCost;
+pub use auction::Indicative;
 pub use book::*;
+pub use changes::BookChange;
 pub use orders::*;
+pub(crate) use snapshot::DEFAULT_SNAPSHOT_DEPTH;
+pub use snapshot::{BookReader, BookSnapshot};
 pub use types::*;
@@ -1,4 +1,4 @@
-use std::fmt::{Display, write};
+use std::fmt::Display;
 
 use log::warn;
 use rust_decimal::Decimal;
@@ -147,7 +147,7 @@ impl OrderRequest {
     }
 }
 
-#[derive(Debug,Clone,PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TradeOrder {
     pub id: OrderId,
     pub side: Side,
@@ -211,10 +211,29 @@ impl TradeOrder {
         fill_qty
     }
 
+    pub fn created_at(&self) -> Timestamp {
+        self.creation_timestamp
+    }
+
     pub fn filled_quantity(&self) -> Quantity {
         self.initial_qty - self.remaining_qty
     }
 
+    /// The order as `request` re-enters it: fills and creation time carry
+    /// over, and the initial quantity grows by whatever was already filled.
+    pub(super) fn amended(&self, request: &OrderRequest) -> Self {
+        Self {
+            id: self.id,
+            side: self.side,
+            remaining_qty: request.qty,
+            initial_qty: self.filled_quantity() + request.qty,
+            fills: self.fills.clone(),
+            order_type: request.order_type,
+            creation_timestamp: self.creation_timestamp,
+            last_modified_timestamp: timestamp(),
+        }
+    }
+
     pub fn cancel(&mut self, qty: impl Into<Quantity>) {
         let qty = qty.into();
         let qty = qty.min(self.remaining_qty);
@@ -309,6 +328,64 @@ impl OrderResult {
     pub fn get_id(&self) -> OrderId {
         self.traid_id
     }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    pub fn initial_qty(&self) -> Quantity {
+        self.initial_qty
+    }
+
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    pub fn filled_qty(&self) -> Quantity {
+        self.fills.iter().map(|fill| fill.qty).sum()
+    }
+
+    /// Applies `execution` the way the book applied it to the resting order,
+    /// if this order was its maker. Returns whether it was.
+    pub fn record_maker_fill(&mut self, execution: &TradeExecution) -> bool {
+        if execution.maker_order_id != self.traid_id {
+            return false;
+        }
+        let qty = execution.qty.min(self.remaining_qty);
+        self.remaining_qty -= qty;
+        self.fills.push(Fill {
+            qty,
+            price: execution.price,
+            timestamp: execution.timestamp,
+            order_id: execution.taker_order_id,
+        });
+        self.status = if self.remaining_qty == Decimal::ZERO {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        true
+    }
+}
+
+/// Whether a party to a fill added liquidity to the book or took it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// What one side of a fill paid, in the asset it received. Negative amounts
+/// are rebates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fee {
+    pub amount: Decimal,
+    pub asset: String,
+    pub liquidity: Liquidity,
 }
 
 #[derive(Debug, Clone)]
@@ -319,6 +396,10 @@ pub struct TradeExecution {
     pub maker_order_id: OrderId,
     pub take_side: Side,
     pub timestamp: Timestamp,
+    /// Set by the engine when a fee schedule covers the maker.
+    pub maker_fee: Option<Fee>,
+    /// Set by the engine when a fee schedule covers the taker.
+    pub taker_fee: Option<Fee>,
 }
 
 impl TradeExecution {
@@ -336,6 +417,8 @@ impl TradeExecution {
             maker_order_id: maker_order.id,
             take_side: taker_side,
             timestamp: timestamp(),
+            maker_fee: None,
+            taker_fee: None,
         }
     }
 }
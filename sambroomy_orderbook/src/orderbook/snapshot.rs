@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use rust_decimal::Decimal;
+
+use super::auction::Indicative;
+use super::book::OrderBook;
+use super::types::*;
+
+pub(crate) const DEFAULT_SNAPSHOT_DEPTH: usize = 20;
+
+/// Immutable top-of-book and top-N depth published by an [`OrderBook`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookSnapshot {
+    /// Incremented on every publish; 0 is the empty book before the first one.
+    pub version: u64,
+    pub timestamp: Timestamp,
+    /// Best-first levels, at most the publisher's depth on each side.
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+    /// The indicative uncross while the book is in a call phase.
+    pub indicative: Option<Indicative>,
+}
+
+impl Default for BookSnapshot {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            timestamp: timestamp(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            indicative: None,
+        }
+    }
+}
+
+impl BookSnapshot {
+    pub fn best_bid(&self) -> Option<(Price, Quantity)> {
+        self.bids.first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<(Price, Quantity)> {
+        self.asks.first().copied()
+    }
+
+    pub fn best_prices(&self) -> (Option<Price>, Option<Price>) {
+        (
+            self.best_bid().map(|(p, _)| p),
+            self.best_ask().map(|(p, _)| p),
+        )
+    }
+
+    pub fn spread(&self) -> Option<Price> {
+        Some(self.best_ask()?.0 - self.best_bid()?.0)
+    }
+
+    pub fn mid_price(&self) -> Option<Price> {
+        Some((self.best_ask()?.0 + self.best_bid()?.0) / Decimal::TWO)
+    }
+}
+
+/// Cheap, cloneable read side of a book's published snapshots.
+///
+/// Loading never blocks the writer: publishing swaps an `Arc` pointer and
+/// readers keep whatever version they loaded for as long as they hold it.
+#[derive(Debug, Clone)]
+pub struct BookReader {
+    cell: Arc<ArcSwap<BookSnapshot>>,
+}
+
+impl BookReader {
+    pub fn load(&self) -> Arc<BookSnapshot> {
+        self.cell.load_full()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.cell.load().version
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct SnapshotPublisher {
+    cell: Arc<ArcSwap<BookSnapshot>>,
+    version: u64,
+    depth: usize,
+}
+
+impl Default for SnapshotPublisher {
+    fn default() -> Self {
+        Self {
+            cell: Arc::new(ArcSwap::from_pointee(BookSnapshot::default())),
+            version: 0,
+            depth: DEFAULT_SNAPSHOT_DEPTH,
+        }
+    }
+}
+
+impl OrderBook {
+    /// Publishes the current top `snapshot_depth` levels to every
+    /// [`BookReader`]. Call once per batch of mutations.
+    pub fn publish(&mut self) -> u64 {
+        let indicative = self.indicative();
+        let publisher = &mut self.publisher;
+        publisher.version += 1;
+        let snapshot = BookSnapshot {
+            version: publisher.version,
+            timestamp: timestamp(),
+            bids: self.bids.levels().take(publisher.depth).collect(),
+            asks: self.asks.levels().take(publisher.depth).collect(),
+            indicative,
+        };
+        publisher.cell.store(Arc::new(snapshot));
+        publisher.version
+    }
+
+    pub fn reader(&self) -> BookReader {
+        BookReader {
+            cell: self.publisher.cell.clone(),
+        }
+    }
+
+    /// The most recently published snapshot, which may lag the live book.
+    pub fn snapshot(&self) -> Arc<BookSnapshot> {
+        self.publisher.cell.load_full()
+    }
+
+    pub fn snapshot_depth(&self) -> usize {
+        self.publisher.depth
+    }
+
+    /// Levels per side included in later snapshots.
+    pub fn set_snapshot_depth(&mut self, depth: usize) {
+        self.publisher.depth = depth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::{
+        MatchingEngine, MaxOrderQty, OrderRequest, OrderType, RiskChain, Side, TradingPair,
+    };
+
+    #[test]
+    fn each_operation_publishes_once() {
+        let pair = TradingPair::new("BASE".to_string(), "QUOTE".to_string());
+        let mut engine = MatchingEngine::new();
+        engine.add_market(pair.clone()).unwrap();
+        let chain = RiskChain::new().with(MaxOrderQty(100.into()));
+        engine.set_risk_chain(&pair, None, chain).unwrap();
+        let reader = engine.reader();
+        let version = || reader.snapshot(&pair).unwrap().version;
+
+        for price in [101, 102, 103] {
+            let ask = OrderRequest::new(Side::Ask, 1, OrderType::limit(price));
+            engine.place_order(&pair, ask).unwrap();
+        }
+        assert_eq!(version(), 3);
+
+        // One version for a sweep across every level.
+        let sweep = OrderRequest::new(Side::Bid, 3, OrderType::Market);
+        let (_, executions) = engine.place_order(&pair, sweep).unwrap();
+        assert_eq!((executions.len(), version()), (3, 4));
+
+        let bid = OrderRequest::new(Side::Bid, 1, OrderType::limit(99));
+        engine.place_order(&pair, bid).unwrap();
+        engine
+            .amend_order(&pair, bid.id(), 98.into(), 2.into())
+            .unwrap();
+        engine.cancel_order(&pair, bid.id()).unwrap();
+        assert_eq!(version(), 7);
+
+        let rejected = OrderRequest::new(Side::Bid, 101, OrderType::limit(99));
+        assert!(engine.place_order(&pair, rejected).is_err());
+        assert_eq!(version(), 7);
+    }
+
+    #[test]
+    fn readers_see_consistent_snapshots_while_matching() {
+        let pair = TradingPair::new("BASE".to_string(), "QUOTE".to_string());
+        let mut engine = MatchingEngine::new();
+        engine.add_market(pair.clone()).unwrap();
+        let reader = engine.reader();
+        assert_eq!(reader.snapshot(&pair).unwrap().version, 0);
+
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                let mut last = 0;
+                while !done.load(Ordering::Acquire) {
+                    let snapshot = reader.snapshot(&pair).unwrap();
+                    assert!(snapshot.version >= last);
+                    last = snapshot.version;
+                    if let (Some(bid), Some(ask)) = snapshot.best_prices() {
+                        assert!(bid < ask, "crossed snapshot {:?}", snapshot);
+                    }
+                    assert!(snapshot.bids.windows(2).all(|w| w[0].0 > w[1].0));
+                    assert!(snapshot.asks.windows(2).all(|w| w[0].0 < w[1].0));
+                }
+            });
+
+            let mut rng = StdRng::seed_from_u64(34);
+            for _ in 0..5000 {
+                let side = if rng.random_bool(0.5) {
+                    Side::Bid
+                } else {
+                    Side::Ask
+                };
+                let price = rng.random_range(90..=110).into();
+                let order =
+                    OrderRequest::new(side, rng.random_range(1..=10), OrderType::Limit(price));
+                engine.place_order(&pair, order).unwrap();
+            }
+            done.store(true, Ordering::Release);
+        });
+
+        let snapshot = reader.snapshot(&pair).unwrap();
+        let state = engine.get_order_book_state(&pair).unwrap();
+        assert_eq!(snapshot.version, 5000);
+        assert_eq!(snapshot.bids.len(), state.bids.len().min(20));
+        assert_eq!(
+            reader.get_depth(&pair, 5).unwrap().asks.len(),
+            state.asks.len().min(5)
+        );
+        assert_eq!(
+            snapshot.best_prices(),
+            engine.get_best_bid_ask(&pair).unwrap()
+        );
+    }
+}
@@ -25,4 +25,4 @@ pub fn create_order_id() -> OrderId {
 
 pub fn create_id_from_bytes(bytes: impl AsRef<[u8]>) -> OrderId {
     Uuid::new_v5(&Uuid::NAMESPACE_DNS, bytes.as_ref())
-}
\ No newline at end of file
+}
@@ -11,4 +11,4 @@ impl Side {
             Side::Bid => Side::Ask,
         }
     }
-}
\ No newline at end of file
+}
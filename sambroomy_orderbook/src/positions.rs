@@ -0,0 +1,262 @@
+//! Net positions and PnL per account and market, built from fills.
+//!
+//! Realized PnL is tracked under both [`PnlMethod`]s at once: average cost
+//! keeps one blended entry price, FIFO closes the oldest open lots first.
+//! Fees are accumulated separately in quote terms and are not taken out of
+//! either PnL figure.
+
+use std::collections::{HashMap, VecDeque};
+
+use polars::prelude::*;
+use rust_decimal::{Decimal, prelude::Signed};
+
+use crate::{AccountId, Fee, Price, Quantity, Side, TradeExecution, TradingPair, tape::to_f64};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PnlMethod {
+    AverageCost,
+    Fifo,
+}
+
+/// What open positions are marked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkSource {
+    Mid,
+    LastTrade,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Position {
+    /// Positive when long, negative when short.
+    pub qty: Quantity,
+    /// Average-cost entry of the open position; zero when flat.
+    pub avg_price: Price,
+    pub realized_avg: Decimal,
+    pub realized_fifo: Decimal,
+    /// Fees paid in quote terms, with base fees valued at the fill price.
+    pub fees: Decimal,
+    pub traded_qty: Quantity,
+    /// Open lots, oldest first, with signed quantities.
+    lots: VecDeque<(Quantity, Price)>,
+}
+
+impl Position {
+    /// Applies a fill of `qty` at `price`, bought if `side` is a bid.
+    pub fn apply(&mut self, side: Side, qty: Quantity, price: Price) {
+        let signed = match side {
+            Side::Bid => qty,
+            Side::Ask => -qty,
+        };
+        self.traded_qty += qty;
+        self.apply_average_cost(signed, price);
+        self.apply_fifo(signed, price);
+    }
+
+    fn apply_average_cost(&mut self, signed: Quantity, price: Price) {
+        let before = self.qty;
+        let after = before + signed;
+        if before.is_zero() || before.is_sign_positive() == signed.is_sign_positive() {
+            self.avg_price = (before * self.avg_price + signed * price) / after;
+        } else {
+            let closed = signed.abs().min(before.abs());
+            self.realized_avg += closed * (price - self.avg_price) * before.signum();
+            if after.is_zero() {
+                self.avg_price = Decimal::ZERO;
+            } else if after.is_sign_positive() != before.is_sign_positive() {
+                self.avg_price = price;
+            }
+        }
+        self.qty = after;
+    }
+
+    fn apply_fifo(&mut self, mut signed: Quantity, price: Price) {
+        while !signed.is_zero()
+            && let Some((lot_qty, lot_price)) = self.lots.front_mut()
+            && lot_qty.is_sign_positive() != signed.is_sign_positive()
+        {
+            let closed = signed.abs().min(lot_qty.abs());
+            self.realized_fifo += closed * (price - *lot_price) * lot_qty.signum();
+            *lot_qty -= closed * lot_qty.signum();
+            signed -= closed * signed.signum();
+            if lot_qty.is_zero() {
+                self.lots.pop_front();
+            }
+        }
+        if !signed.is_zero() {
+            self.lots.push_back((signed, price));
+        }
+    }
+
+    /// Entry price of the open position under `method`.
+    pub fn entry_price(&self, method: PnlMethod) -> Option<Price> {
+        if self.qty.is_zero() {
+            return None;
+        }
+        Some(match method {
+            PnlMethod::AverageCost => self.avg_price,
+            PnlMethod::Fifo => {
+                let cost: Decimal = self.lots.iter().map(|(q, p)| q * p).sum();
+                cost / self.qty
+            }
+        })
+    }
+
+    pub fn realized(&self, method: PnlMethod) -> Decimal {
+        match method {
+            PnlMethod::AverageCost => self.realized_avg,
+            PnlMethod::Fifo => self.realized_fifo,
+        }
+    }
+
+    pub fn unrealized(&self, method: PnlMethod, mark: Price) -> Decimal {
+        match method {
+            PnlMethod::AverageCost => self.qty * (mark - self.avg_price),
+            PnlMethod::Fifo => self.lots.iter().map(|(q, p)| q * (mark - p)).sum(),
+        }
+    }
+
+    pub fn lots(&self) -> impl Iterator<Item = (Quantity, Price)> + '_ {
+        self.lots.iter().copied()
+    }
+}
+
+/// Positions of every account that traded, keyed by account and market.
+#[derive(Debug, Default)]
+pub struct PositionKeeper {
+    positions: HashMap<(AccountId, TradingPair), Position>,
+    last_prices: HashMap<TradingPair, Price>,
+}
+
+impl PositionKeeper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Books both sides of `execution`; a side without an account is only
+    /// counted towards the last trade price.
+    pub fn record(
+        &mut self,
+        pair: &TradingPair,
+        execution: &TradeExecution,
+        maker: Option<AccountId>,
+        taker: Option<AccountId>,
+    ) {
+        self.last_prices.insert(pair.clone(), execution.price);
+        let sides = [
+            (maker, execution.take_side.opposite(), &execution.maker_fee),
+            (taker, execution.take_side, &execution.taker_fee),
+        ];
+        for (account, side, fee) in sides {
+            let Some(account) = account else { continue };
+            let position = self.positions.entry((account, pair.clone())).or_default();
+            position.apply(side, execution.qty, execution.price);
+            position.fees += fee
+                .as_ref()
+                .map_or(Decimal::ZERO, |f| quote_value(pair, f, execution.price));
+        }
+    }
+
+    pub fn position(&self, account: AccountId, pair: &TradingPair) -> Option<&Position> {
+        self.positions.get(&(account, pair.clone()))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (AccountId, &TradingPair, &Position)> {
+        self.positions
+            .iter()
+            .map(|((account, pair), position)| (*account, pair, position))
+    }
+
+    pub fn last_price(&self, pair: &TradingPair) -> Option<Price> {
+        self.last_prices.get(pair).copied()
+    }
+
+    /// One row per account and market, sorted by both, with unrealized PnL
+    /// against `marks`; markets without a mark get nulls.
+    pub fn frame(&self, marks: &HashMap<TradingPair, Price>) -> PolarsResult<DataFrame> {
+        let mut rows: Vec<_> = self.positions().collect();
+        rows.sort_by_key(|(account, pair, _)| (*account, pair.to_string()));
+        let mark = |pair: &TradingPair| marks.get(pair).copied();
+        let unrealized = |method| {
+            rows.iter()
+                .map(|(_, pair, p)| mark(pair).map(|m| to_f64(p.unrealized(method, m))))
+                .collect::<Vec<_>>()
+        };
+        let entry = |method| {
+            rows.iter()
+                .map(|(_, _, p)| p.entry_price(method).map(to_f64))
+                .collect::<Vec<_>>()
+        };
+        df!(
+            "account" => rows.iter().map(|(a, _, _)| *a).collect::<Vec<_>>(),
+            "market" => rows.iter().map(|(_, pair, _)| pair.to_string()).collect::<Vec<_>>(),
+            "qty" => rows.iter().map(|(_, _, p)| to_f64(p.qty)).collect::<Vec<_>>(),
+            "avg_entry" => entry(PnlMethod::AverageCost),
+            "fifo_entry" => entry(PnlMethod::Fifo),
+            "realized_avg" => rows.iter().map(|(_, _, p)| to_f64(p.realized_avg)).collect::<Vec<_>>(),
+            "realized_fifo" => rows.iter().map(|(_, _, p)| to_f64(p.realized_fifo)).collect::<Vec<_>>(),
+            "mark" => rows.iter().map(|(_, pair, _)| mark(pair).map(to_f64)).collect::<Vec<_>>(),
+            "unrealized_avg" => unrealized(PnlMethod::AverageCost),
+            "unrealized_fifo" => unrealized(PnlMethod::Fifo),
+            "fees" => rows.iter().map(|(_, _, p)| to_f64(p.fees)).collect::<Vec<_>>(),
+            "traded_qty" => rows.iter().map(|(_, _, p)| to_f64(p.traded_qty)).collect::<Vec<_>>(),
+        )
+    }
+}
+
+fn quote_value(pair: &TradingPair, fee: &Fee, price: Price) -> Decimal {
+    if fee.asset == pair.base() {
+        fee.amount * price
+    } else {
+        fee.amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn fifo_and_average_cost_disagree_on_partial_closes() {
+        let mut position = Position::default();
+        position.apply(Side::Bid, dec("1"), dec("100"));
+        position.apply(Side::Bid, dec("1"), dec("110"));
+        position.apply(Side::Ask, dec("1"), dec("120"));
+
+        assert_eq!(position.qty, dec("1"));
+        assert_eq!(position.realized(PnlMethod::AverageCost), dec("15"));
+        assert_eq!(position.realized(PnlMethod::Fifo), dec("20"));
+        assert_eq!(
+            position.entry_price(PnlMethod::AverageCost),
+            Some(dec("105"))
+        );
+        assert_eq!(position.entry_price(PnlMethod::Fifo), Some(dec("110")));
+        assert_eq!(
+            position.unrealized(PnlMethod::AverageCost, dec("100")),
+            dec("-5")
+        );
+        assert_eq!(position.unrealized(PnlMethod::Fifo, dec("100")), dec("-10"));
+
+        // Flipping short closes the long and opens at the fill price.
+        position.apply(Side::Ask, dec("3"), dec("90"));
+        assert_eq!(position.qty, dec("-2"));
+        assert_eq!(position.realized(PnlMethod::AverageCost), dec("0"));
+        assert_eq!(position.realized(PnlMethod::Fifo), dec("0"));
+        assert_eq!(
+            position.entry_price(PnlMethod::AverageCost),
+            Some(dec("90"))
+        );
+        assert_eq!(position.entry_price(PnlMethod::Fifo), Some(dec("90")));
+        assert_eq!(position.unrealized(PnlMethod::Fifo, dec("80")), dec("20"));
+
+        position.apply(Side::Bid, dec("2"), dec("95"));
+        assert_eq!(position.qty, Decimal::ZERO);
+        assert_eq!(position.entry_price(PnlMethod::Fifo), None);
+        assert_eq!(position.realized(PnlMethod::AverageCost), dec("-10"));
+        assert_eq!(position.realized(PnlMethod::Fifo), dec("-10"));
+        assert_eq!(position.lots().count(), 0);
+    }
+}
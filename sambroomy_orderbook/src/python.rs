@@ -0,0 +1,521 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! Sides and order types are plain strings as accepted by the replay files
+//! (`"bid"`/`"ask"`, `"limit"`/`"market"`/`"ioc"`/`"fok"`/`"system"`),
+//! quantities and prices are `decimal.Decimal` and order ids `uuid.UUID`.
+//! Books convert to pandas or polars frames with one row per price level,
+//! top of the ladder first; neither library is needed until then.
+
+use std::time::UNIX_EPOCH;
+
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyTuple},
+};
+use rust_decimal::Decimal;
+
+use crate::{
+    MatchingEngine, OrderBook, OrderBookState, OrderId, OrderRequest, OrderResult, OrderStatus,
+    OrderType, Price, Quantity, Side, TradeExecution, TradingPair,
+    replay::{parse_order_type, parse_side},
+    tape::to_f64,
+};
+
+fn value_error(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "bid",
+        Side::Ask => "ask",
+    }
+}
+
+fn order_type_name(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "market",
+        OrderType::Limit(_) => "limit",
+        OrderType::IOC(_) => "ioc",
+        OrderType::FOK(_) => "fok",
+        OrderType::SystemLevel(_) => "system",
+    }
+}
+
+fn pair(market: &str) -> PyResult<TradingPair> {
+    market.parse().map_err(value_error)
+}
+
+#[pyclass(name = "OrderRequest", module = "orderbooklib", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyOrderRequest(OrderRequest);
+
+#[pymethods]
+impl PyOrderRequest {
+    /// `id` makes the order id deterministic, derived from the string as in
+    /// replayed events.
+    #[new]
+    #[pyo3(signature = (side, qty, price = None, order_type = "limit", id = None))]
+    fn new(
+        side: &str,
+        qty: Quantity,
+        price: Option<Price>,
+        order_type: &str,
+        id: Option<&str>,
+    ) -> PyResult<Self> {
+        let side = parse_side(side).map_err(value_error)?;
+        let order_type = parse_order_type(order_type, price).map_err(value_error)?;
+        if qty <= Decimal::ZERO {
+            return Err(value_error(format!("invalid quantity {}", qty)));
+        }
+        Ok(Self(match id {
+            Some(id) => OrderRequest::new_with_other_id(id, side, qty, order_type),
+            None => OrderRequest::new(side, qty, order_type),
+        }))
+    }
+
+    #[getter]
+    fn id(&self) -> OrderId {
+        self.0.id()
+    }
+
+    #[getter]
+    fn side(&self) -> &'static str {
+        side_name(self.0.side)
+    }
+
+    #[getter]
+    fn qty(&self) -> Quantity {
+        self.0.qty
+    }
+
+    #[getter]
+    fn price(&self) -> Option<Price> {
+        self.0.price()
+    }
+
+    #[getter]
+    fn order_type(&self) -> &'static str {
+        order_type_name(self.0.order_type)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderRequest(side='{}', qty={}, price={}, order_type='{}')",
+            self.side(),
+            self.0.qty,
+            self.0.price().map_or("None".to_string(), |p| p.to_string()),
+            self.order_type()
+        )
+    }
+}
+
+#[pyclass(name = "OrderResult", module = "orderbooklib", frozen)]
+pub struct PyOrderResult(OrderResult);
+
+#[pymethods]
+impl PyOrderResult {
+    #[getter]
+    fn id(&self) -> OrderId {
+        self.0.get_id()
+    }
+
+    #[getter]
+    fn side(&self) -> &'static str {
+        side_name(self.0.side())
+    }
+
+    #[getter]
+    fn order_type(&self) -> &'static str {
+        order_type_name(self.0.order_type())
+    }
+
+    #[getter]
+    fn price(&self) -> Option<Price> {
+        self.0.order_type().price()
+    }
+
+    #[getter]
+    fn initial_qty(&self) -> Quantity {
+        self.0.initial_qty()
+    }
+
+    #[getter]
+    fn remaining_qty(&self) -> Quantity {
+        self.0.remaining_qty
+    }
+
+    #[getter]
+    fn filled_qty(&self) -> Quantity {
+        self.0.filled_qty()
+    }
+
+    /// `None` until the order has traded.
+    #[getter]
+    fn avg_fill_price(&self) -> Option<Price> {
+        (!self.0.fills().is_empty()).then(|| self.0.avr_fill_price())
+    }
+
+    /// `(qty, price, counterparty order id)` per fill.
+    #[getter]
+    fn fills(&self) -> Vec<(Quantity, Price, OrderId)> {
+        self.0
+            .fills()
+            .iter()
+            .map(|f| (f.qty, f.price, f.order_id))
+            .collect()
+    }
+
+    #[getter]
+    fn status(&self) -> &'static str {
+        match self.0.status {
+            OrderStatus::Open => "open",
+            OrderStatus::Filled => "filled",
+            OrderStatus::PartiallyFilled => "partially_filled",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderResult(id={}, status='{}', filled_qty={}, remaining_qty={})",
+            self.id(),
+            self.status(),
+            self.filled_qty(),
+            self.0.remaining_qty
+        )
+    }
+}
+
+#[pyclass(name = "TradeExecution", module = "orderbooklib", frozen)]
+pub struct PyTradeExecution(TradeExecution);
+
+#[pymethods]
+impl PyTradeExecution {
+    #[getter]
+    fn qty(&self) -> Quantity {
+        self.0.qty
+    }
+
+    #[getter]
+    fn price(&self) -> Price {
+        self.0.price
+    }
+
+    #[getter]
+    fn taker_order_id(&self) -> OrderId {
+        self.0.taker_order_id
+    }
+
+    #[getter]
+    fn maker_order_id(&self) -> OrderId {
+        self.0.maker_order_id
+    }
+
+    #[getter]
+    fn take_side(&self) -> &'static str {
+        side_name(self.0.take_side)
+    }
+
+    /// Nanoseconds since the Unix epoch.
+    #[getter]
+    fn timestamp_ns(&self) -> u128 {
+        self.0
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos())
+    }
+
+    /// `(amount, asset)` paid by the maker, when a fee schedule applied.
+    #[getter]
+    fn maker_fee(&self) -> Option<(Decimal, String)> {
+        self.0
+            .maker_fee
+            .as_ref()
+            .map(|f| (f.amount, f.asset.clone()))
+    }
+
+    #[getter]
+    fn taker_fee(&self) -> Option<(Decimal, String)> {
+        self.0
+            .taker_fee
+            .as_ref()
+            .map(|f| (f.amount, f.asset.clone()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TradeExecution(qty={}, price={}, take_side='{}')",
+            self.0.qty,
+            self.0.price,
+            self.take_side()
+        )
+    }
+}
+
+#[pyclass(name = "OrderBookState", module = "orderbooklib", frozen)]
+pub struct PyOrderBookState(OrderBookState);
+
+#[pymethods]
+impl PyOrderBookState {
+    /// `(price, qty)` levels from the highest price down.
+    #[getter]
+    fn asks(&self) -> Vec<(Price, Quantity)> {
+        self.0.asks.clone()
+    }
+
+    /// `(price, qty)` levels from the best bid down.
+    #[getter]
+    fn bids(&self) -> Vec<(Price, Quantity)> {
+        self.0.bids.clone()
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let rows = || {
+            let asks = self.0.asks.iter().map(|l| (Side::Ask, l));
+            asks.chain(self.0.bids.iter().map(|l| (Side::Bid, l)))
+        };
+        let columns = PyDict::new(py);
+        columns.set_item(
+            "side",
+            rows().map(|(s, _)| side_name(s)).collect::<Vec<_>>(),
+        )?;
+        columns.set_item(
+            "price",
+            rows().map(|(_, (p, _))| to_f64(*p)).collect::<Vec<_>>(),
+        )?;
+        columns.set_item(
+            "qty",
+            rows().map(|(_, (_, q))| to_f64(*q)).collect::<Vec<_>>(),
+        )?;
+        Ok(columns)
+    }
+
+    fn to_pandas<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        data_frame(py, "pandas", self.to_dict(py)?)
+    }
+
+    fn to_polars<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        data_frame(py, "polars", self.to_dict(py)?)
+    }
+}
+
+fn data_frame<'py>(
+    py: Python<'py>,
+    library: &str,
+    columns: Bound<'py, PyDict>,
+) -> PyResult<Bound<'py, PyAny>> {
+    py.import(library)?.getattr("DataFrame")?.call1((columns,))
+}
+
+fn outcome(
+    py: Python<'_>,
+    (result, executions): (OrderResult, Vec<TradeExecution>),
+) -> PyResult<Bound<'_, PyTuple>> {
+    let executions: Vec<_> = executions.into_iter().map(PyTradeExecution).collect();
+    (PyOrderResult(result), executions).into_pyobject(py)
+}
+
+#[pyclass(name = "OrderBook", module = "orderbooklib")]
+#[derive(Default)]
+pub struct PyOrderBook(OrderBook);
+
+#[pymethods]
+impl PyOrderBook {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the order's result and its executions. Raises `ValueError`
+    /// if an order with the same id is still resting.
+    fn add_order<'py>(
+        &mut self,
+        py: Python<'py>,
+        order: PyOrderRequest,
+    ) -> PyResult<Bound<'py, PyTuple>> {
+        if self.0.order_loc.contains_key(&order.0.id()) {
+            return Err(value_error("duplicate order id"));
+        }
+        outcome(py, self.0.add_order(order.0))
+    }
+
+    fn delete_order(&mut self, order_id: OrderId) -> Option<PyOrderResult> {
+        self.0.delete_order(order_id).map(PyOrderResult)
+    }
+
+    /// Reduces a resting order by `qty`, which must be positive.
+    fn cancel_order(
+        &mut self,
+        order_id: OrderId,
+        qty: Quantity,
+    ) -> PyResult<Option<PyOrderResult>> {
+        if qty <= Decimal::ZERO {
+            return Err(value_error(format!("invalid quantity {}", qty)));
+        }
+        Ok(self.0.cancel_order(order_id, qty).map(PyOrderResult))
+    }
+
+    fn amend_order<'py>(
+        &mut self,
+        py: Python<'py>,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> PyResult<Option<Bound<'py, PyTuple>>> {
+        self.0
+            .amend_order(order_id, price, qty)
+            .map(|amended| outcome(py, amended))
+            .transpose()
+    }
+
+    fn get_order(&self, order_id: OrderId) -> Option<PyOrderResult> {
+        self.0
+            .get_order(order_id)
+            .cloned()
+            .map(|o| PyOrderResult(o.into()))
+    }
+
+    fn get_order_book_state(&self) -> PyOrderBookState {
+        PyOrderBookState(self.0.get_order_book_state())
+    }
+
+    fn best_bid(&self) -> Option<Price> {
+        self.0.best_bid()
+    }
+
+    fn best_ask(&self) -> Option<Price> {
+        self.0.best_ask()
+    }
+
+    fn spread(&self) -> Option<Price> {
+        self.0.spread()
+    }
+
+    fn mid_price(&self) -> Option<Price> {
+        self.0.mid_price()
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.get_order_count()
+    }
+
+    fn to_pandas<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.get_order_book_state().to_pandas(py)
+    }
+
+    fn to_polars<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.get_order_book_state().to_polars(py)
+    }
+}
+
+/// Markets are named like `"BTC_USDT"`. Errors from the engine are raised
+/// as `ValueError`.
+#[pyclass(name = "MatchingEngine", module = "orderbooklib", unsendable)]
+#[derive(Default)]
+pub struct PyMatchingEngine(MatchingEngine);
+
+#[pymethods]
+impl PyMatchingEngine {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_market(&mut self, market: &str) -> PyResult<()> {
+        self.0.add_market(pair(market)?).map_err(value_error)
+    }
+
+    fn remove_market(&mut self, market: &str) -> PyResult<()> {
+        self.0.remove_market(&pair(market)?).map_err(value_error)
+    }
+
+    fn get_markets(&self) -> Vec<String> {
+        let mut markets: Vec<_> = self.0.get_markets().iter().map(|p| p.to_string()).collect();
+        markets.sort();
+        markets
+    }
+
+    fn place_order<'py>(
+        &mut self,
+        py: Python<'py>,
+        market: &str,
+        order: PyOrderRequest,
+    ) -> PyResult<Bound<'py, PyTuple>> {
+        let placed = self
+            .0
+            .place_order(&pair(market)?, order.0)
+            .map_err(value_error)?;
+        outcome(py, placed)
+    }
+
+    fn cancel_order(&mut self, market: &str, order_id: OrderId) -> PyResult<Option<PyOrderResult>> {
+        let cancelled = self
+            .0
+            .cancel_order(&pair(market)?, order_id)
+            .map_err(value_error)?;
+        Ok(cancelled.map(PyOrderResult))
+    }
+
+    fn amend_order<'py>(
+        &mut self,
+        py: Python<'py>,
+        market: &str,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    ) -> PyResult<Option<Bound<'py, PyTuple>>> {
+        self.0
+            .amend_order(&pair(market)?, order_id, price, qty)
+            .map_err(value_error)?
+            .map(|amended| outcome(py, amended))
+            .transpose()
+    }
+
+    fn get_order(&self, market: &str, order_id: OrderId) -> PyResult<Option<PyOrderResult>> {
+        let order = self
+            .0
+            .get_order(&pair(market)?, order_id)
+            .map_err(value_error)?;
+        Ok(order.map(PyOrderResult))
+    }
+
+    fn get_order_book_state(&self, market: &str) -> PyResult<PyOrderBookState> {
+        self.0
+            .get_order_book_state(&pair(market)?)
+            .map(PyOrderBookState)
+            .map_err(value_error)
+    }
+
+    fn get_best_bid_ask(&self, market: &str) -> PyResult<(Option<Price>, Option<Price>)> {
+        self.0.get_best_bid_ask(&pair(market)?).map_err(value_error)
+    }
+
+    fn get_spread(&self, market: &str) -> PyResult<Option<Price>> {
+        self.0.get_spread(&pair(market)?).map_err(value_error)
+    }
+
+    fn get_mid_price(&self, market: &str) -> PyResult<Option<Price>> {
+        self.0.get_mid_price(&pair(market)?).map_err(value_error)
+    }
+
+    fn to_pandas<'py>(&self, py: Python<'py>, market: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.get_order_book_state(market)?.to_pandas(py)
+    }
+
+    fn to_polars<'py>(&self, py: Python<'py>, market: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.get_order_book_state(market)?.to_polars(py)
+    }
+}
+
+#[pymodule]
+fn orderbooklib(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOrderRequest>()?;
+    m.add_class::<PyOrderResult>()?;
+    m.add_class::<PyTradeExecution>()?;
+    m.add_class::<PyOrderBookState>()?;
+    m.add_class::<PyOrderBook>()?;
+    m.add_class::<PyMatchingEngine>()?;
+    Ok(())
+}
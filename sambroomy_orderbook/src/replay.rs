@@ -0,0 +1,643 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs::File,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use polars::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::{
+    OrderId, OrderRequest, OrderResult, OrderStatus, OrderType, Price, Quantity, Side,
+    TradeExecution, TradingPair, gateway::OrderGateway, orderbook::create_id_from_bytes,
+    tape::to_f64,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Add,
+    Cancel,
+    Amend,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "add" | "new" | "place" => Ok(Action::Add),
+            "cancel" | "delete" => Ok(Action::Cancel),
+            "amend" | "modify" | "replace" => Ok(Action::Amend),
+            other => Err(format!("unknown action '{other}'")),
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Add => write!(f, "add"),
+            Action::Cancel => write!(f, "cancel"),
+            Action::Amend => write!(f, "amend"),
+        }
+    }
+}
+
+/// One row of an order event file.
+#[derive(Debug, Clone)]
+pub struct OrderEvent {
+    pub time: f64,
+    pub market: Option<TradingPair>,
+    pub id: String,
+    pub action: Action,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub qty: Quantity,
+}
+
+impl OrderEvent {
+    pub fn order_id(&self) -> OrderId {
+        create_id_from_bytes(&self.id)
+    }
+}
+
+pub(crate) fn parse_side(s: &str) -> Result<Side, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "bid" | "buy" | "b" => Ok(Side::Bid),
+        "ask" | "sell" | "s" | "a" => Ok(Side::Ask),
+        other => Err(format!("unknown side '{other}'")),
+    }
+}
+
+pub(crate) fn parse_order_type(s: &str, price: Option<Price>) -> Result<OrderType, String> {
+    let kind = s.to_ascii_lowercase();
+    if kind == "market" {
+        return Ok(OrderType::Market);
+    }
+    let price = price.ok_or_else(|| format!("{kind} order without a price"))?;
+    match kind.as_str() {
+        "limit" => Ok(OrderType::limit(price)),
+        "ioc" => Ok(OrderType::ioc(price)),
+        "fok" => Ok(OrderType::fok(price)),
+        "system" | "systemlevel" => Ok(OrderType::system_level(price)),
+        other => Err(format!("unknown order type '{other}'")),
+    }
+}
+
+/// Reads a CSV or Parquet file (by extension) with lower-cased column names.
+///
+/// CSV columns are all read as strings so that prices and quantities reach
+/// [`events_from_frame`] exactly as written.
+pub fn read_frame(path: &Path) -> crate::Result<DataFrame> {
+    let mut df = match path.extension().and_then(|e| e.to_str()) {
+        Some("parquet") => ParquetReader::new(File::open(path)?).finish()?,
+        _ => CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(Some(0))
+            .try_into_reader_with_file_path(Some(path.into()))?
+            .finish()?,
+    };
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| name.to_lowercase())
+        .collect();
+    df.set_column_names(names)?;
+    Ok(df)
+}
+
+/// Writes `df` as Parquet or CSV depending on the extension of `path`.
+pub fn write_frame(df: &mut DataFrame, path: &Path) -> crate::Result<()> {
+    let file = File::create(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("parquet") => {
+            ParquetWriter::new(file).finish(df)?;
+        }
+        _ => CsvWriter::new(file).finish(df)?,
+    }
+    Ok(())
+}
+
+/// Parses a price or quantity as written, accepting scientific notation.
+fn parse_decimal(s: &str) -> Option<Decimal> {
+    let s = s.trim();
+    Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()
+}
+
+/// Parses the `time, side, type, price, qty, id, action` columns (plus an
+/// optional `market` column) into events. `price` and `qty` are read as
+/// decimal strings, so ticks such as 0.1 or 1e-8 survive exactly. Adds and
+/// amends need a positive `qty`; only cancels may leave it empty, meaning
+/// the whole order.
+pub fn events_from_frame(df: &DataFrame) -> crate::Result<Vec<OrderEvent>> {
+    let f64_col = |name: &str| -> PolarsResult<Float64Chunked> {
+        Ok(df.column(name)?.cast(&DataType::Float64)?.f64()?.clone())
+    };
+    let str_col = |name: &str| -> PolarsResult<StringChunked> {
+        Ok(df.column(name)?.cast(&DataType::String)?.str()?.clone())
+    };
+    let time = f64_col("time")?;
+    let price = str_col("price")?;
+    let qty = str_col("qty")?;
+    let side = str_col("side")?;
+    let kind = str_col("type")?;
+    let id = str_col("id")?;
+    let action = str_col("action")?;
+    let market = df
+        .get_column_names()
+        .iter()
+        .any(|name| name.as_str() == "market")
+        .then(|| str_col("market"))
+        .transpose()?;
+
+    let mut events = Vec::with_capacity(df.height());
+    for row in 0..df.height() {
+        let err = |msg: String| format!("row {row}: {msg}");
+        let text = |col: &StringChunked, name: &str| {
+            col.get(row)
+                .map(str::to_string)
+                .ok_or_else(|| err(format!("missing {name}")))
+        };
+        let decimal = |col: &StringChunked, name: &str| {
+            col.get(row)
+                .map(|s| parse_decimal(s).ok_or_else(|| err(format!("invalid {name} '{s}'"))))
+                .transpose()
+        };
+        let action = Action::from_str(&text(&action, "action")?).map_err(err)?;
+        let price = match action {
+            Action::Cancel => decimal(&price, "price").ok().flatten(),
+            Action::Add | Action::Amend => decimal(&price, "price")?,
+        };
+        let qty = match (decimal(&qty, "qty")?, action) {
+            (Some(qty), _) if qty < Quantity::ZERO => {
+                return Err(err(format!("non-positive qty {qty}")).into());
+            }
+            (Some(qty), Action::Add | Action::Amend) if qty.is_zero() => {
+                return Err(err(format!("non-positive qty {qty}")).into());
+            }
+            (Some(qty), _) => qty,
+            (None, Action::Cancel) => Quantity::ZERO,
+            (None, _) => return Err(err("missing qty".to_string()).into()),
+        };
+        // Cancels only need the id; side and type are informational.
+        let (side, order_type) = match action {
+            Action::Cancel => (
+                side.get(row)
+                    .and_then(|s| parse_side(s).ok())
+                    .unwrap_or(Side::Bid),
+                kind.get(row)
+                    .and_then(|k| parse_order_type(k, price).ok())
+                    .unwrap_or(OrderType::Market),
+            ),
+            Action::Add | Action::Amend => (
+                parse_side(&text(&side, "side")?).map_err(err)?,
+                parse_order_type(&text(&kind, "type")?, price).map_err(err)?,
+            ),
+        };
+        events.push(OrderEvent {
+            time: time.get(row).unwrap_or_default(),
+            market: market
+                .as_ref()
+                .and_then(|col| col.get(row))
+                .map(TradingPair::from_str)
+                .transpose()
+                .map_err(err)?,
+            id: text(&id, "id")?,
+            action,
+            side,
+            order_type,
+            qty,
+        });
+    }
+    Ok(events)
+}
+
+pub fn load_events(path: &Path) -> crate::Result<Vec<OrderEvent>> {
+    events_from_frame(&read_frame(path)?)
+}
+
+/// Distinct markets referenced by `events`, in first-seen order.
+pub fn event_markets(events: &[OrderEvent]) -> Vec<TradingPair> {
+    let mut seen = HashSet::new();
+    events
+        .iter()
+        .filter_map(|e| e.market.clone())
+        .filter(|m| seen.insert(m.clone()))
+        .collect()
+}
+
+/// What happened to a single event.
+#[derive(Debug, Clone)]
+pub struct OrderOutcome {
+    pub time: f64,
+    pub market: Option<TradingPair>,
+    pub id: String,
+    pub action: Action,
+    pub result: Result<Option<OrderResult>, String>,
+    pub latency: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayedExecution {
+    pub time: f64,
+    pub market: Option<TradingPair>,
+    pub execution: TradeExecution,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub executions: Vec<ReplayedExecution>,
+    pub outcomes: Vec<OrderOutcome>,
+    pub elapsed: Duration,
+    ids: HashMap<OrderId, String>,
+}
+
+/// Feeds `events` through `gateway` in file order, timing every call.
+///
+/// An add whose id is still live is recorded as an error for its row rather
+/// than entered a second time.
+pub fn replay_events(gateway: &mut dyn OrderGateway, events: &[OrderEvent]) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let started = Instant::now();
+    for (row, event) in events.iter().enumerate() {
+        let order_id = event.order_id();
+        report
+            .ids
+            .entry(order_id)
+            .or_insert_with(|| event.id.clone());
+        let market = event.market.as_ref();
+        let begin = Instant::now();
+        let result = match event.action {
+            Action::Add if is_live(gateway, market, order_id) => {
+                Err(format!("row {row}: duplicate order id"))
+            }
+            Action::Add => gateway
+                .place(
+                    market,
+                    OrderRequest::new_with_id(order_id, event.side, event.qty, event.order_type),
+                )
+                .map(|(r, ex)| (Some(r), ex)),
+            Action::Cancel if event.qty > Quantity::ZERO => gateway
+                .reduce(market, order_id, event.qty)
+                .map(|r| (r, Vec::new())),
+            Action::Cancel => gateway.cancel(market, order_id).map(|r| (r, Vec::new())),
+            Action::Amend => match event.order_type.price() {
+                Some(price) => gateway
+                    .amend(market, order_id, price, event.qty)
+                    .map(|r| r.map_or((None, Vec::new()), |(r, ex)| (Some(r), ex))),
+                None => Err("amend without a price".to_string()),
+            },
+        };
+        let latency = begin.elapsed();
+        let result = result.map(|(r, executions)| {
+            report
+                .executions
+                .extend(executions.into_iter().map(|execution| ReplayedExecution {
+                    time: event.time,
+                    market: event.market.clone(),
+                    execution,
+                }));
+            r
+        });
+        report.outcomes.push(OrderOutcome {
+            time: event.time,
+            market: event.market.clone(),
+            id: event.id.clone(),
+            action: event.action,
+            result,
+            latency,
+        });
+    }
+    report.elapsed = started.elapsed();
+    report
+}
+
+fn is_live(gateway: &dyn OrderGateway, market: Option<&TradingPair>, order_id: OrderId) -> bool {
+    matches!(
+        gateway.order(market, order_id).map(|r| r.map(|r| r.status)),
+        Ok(Some(OrderStatus::Open | OrderStatus::PartiallyFilled))
+    )
+}
+
+fn side_str(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "bid",
+        Side::Ask => "ask",
+    }
+}
+
+impl ReplayReport {
+    fn external_id(&self, id: &OrderId) -> String {
+        self.ids.get(id).cloned().unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn executions_frame(&self) -> PolarsResult<DataFrame> {
+        let rows = &self.executions;
+        df!(
+            "time" => rows.iter().map(|r| r.time).collect::<Vec<_>>(),
+            "market" => rows.iter().map(|r| r.market.as_ref().map(|m| m.to_string())).collect::<Vec<_>>(),
+            "taker_id" => rows.iter().map(|r| self.external_id(&r.execution.taker_order_id)).collect::<Vec<_>>(),
+            "maker_id" => rows.iter().map(|r| self.external_id(&r.execution.maker_order_id)).collect::<Vec<_>>(),
+            "take_side" => rows.iter().map(|r| side_str(r.execution.take_side)).collect::<Vec<_>>(),
+            "price" => rows.iter().map(|r| to_f64(r.execution.price)).collect::<Vec<_>>(),
+            "qty" => rows.iter().map(|r| to_f64(r.execution.qty)).collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn outcomes_frame(&self) -> PolarsResult<DataFrame> {
+        let rows = &self.outcomes;
+        let result = OrderOutcome::order_result;
+        df!(
+            "time" => rows.iter().map(|r| r.time).collect::<Vec<_>>(),
+            "market" => rows.iter().map(|r| r.market.as_ref().map(|m| m.to_string())).collect::<Vec<_>>(),
+            "id" => rows.iter().map(|r| r.id.clone()).collect::<Vec<_>>(),
+            "action" => rows.iter().map(|r| r.action.to_string()).collect::<Vec<_>>(),
+            "status" => rows.iter().map(|r| result(r).map(|res| format!("{:?}", res.status))).collect::<Vec<_>>(),
+            "filled_qty" => rows.iter().map(|r| result(r).map(|res| to_f64(res.filled_qty()))).collect::<Vec<_>>(),
+            "remaining_qty" => rows.iter().map(|r| result(r).map(|res| to_f64(res.remaining_qty))).collect::<Vec<_>>(),
+            "avg_price" => rows.iter().map(|r| {
+                result(r)
+                    .filter(|res| res.filled_qty() > Quantity::ZERO)
+                    .map(|res| to_f64(res.avr_fill_price()))
+            }).collect::<Vec<_>>(),
+            "error" => rows.iter().map(|r| r.result.as_ref().err().cloned()).collect::<Vec<_>>(),
+            "latency_ns" => rows.iter().map(|r| r.latency.as_nanos() as u64).collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn traded_volume(&self) -> Quantity {
+        self.executions.iter().map(|r| r.execution.qty).sum()
+    }
+
+    pub fn status_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for outcome in &self.outcomes {
+            let key = match &outcome.result {
+                Ok(Some(r)) => format!("{:?}", r.status),
+                Ok(None) => "NotFound".to_string(),
+                Err(_) => "Error".to_string(),
+            };
+            *counts.entry(key).or_default() += 1;
+        }
+        counts
+    }
+
+    pub fn latency_stats(&self) -> LatencyStats {
+        LatencyStats::from_durations(self.outcomes.iter().map(|o| o.latency))
+    }
+
+    pub fn throughput(&self) -> f64 {
+        self.outcomes.len() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Order statistics over per-call latencies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    pub fn from_durations(durations: impl Iterator<Item = Duration>) -> Self {
+        let mut sorted: Vec<Duration> = durations.collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_unstable();
+        let pct = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        Self {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p50: pct(0.50),
+            p90: pct(0.90),
+            p99: pct(0.99),
+            p999: pct(0.999),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+impl Display for LatencyStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "n={} mean={:?} p50={:?} p90={:?} p99={:?} p99.9={:?} max={:?}",
+            self.count, self.mean, self.p50, self.p90, self.p99, self.p999, self.max
+        )
+    }
+}
+
+impl OrderOutcome {
+    pub fn order_result(&self) -> Option<&OrderResult> {
+        self.result.as_ref().ok()?.as_ref()
+    }
+
+    pub fn status(&self) -> Option<OrderStatus> {
+        self.order_result().map(|r| r.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderBook;
+
+    fn row(side: &str, kind: &str, qty: Option<f64>, action: &str) -> DataFrame {
+        df!(
+            "time" => [1.5],
+            "side" => [side],
+            "type" => [kind],
+            "price" => [Some(100.0)],
+            "qty" => [qty],
+            "id" => ["a"],
+            "action" => [action],
+        )
+        .unwrap()
+    }
+
+    fn parse_error(df: &DataFrame) -> String {
+        events_from_frame(df).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn rows_parse_into_events() {
+        let events = events_from_frame(&row("buy", "ioc", Some(2.5), "new")).unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.time, 1.5);
+        assert_eq!(event.market, None);
+        assert_eq!(event.action, Action::Add);
+        assert_eq!(event.side, Side::Bid);
+        assert_eq!(event.order_type, OrderType::ioc(100u32));
+        assert_eq!(event.qty, "2.5".parse().unwrap());
+    }
+
+    #[test]
+    fn malformed_rows_are_rejected() {
+        let mut df = row("buy", "limit", Some(1.0), "add");
+        df.drop_in_place("action").unwrap();
+        assert!(events_from_frame(&df).is_err());
+
+        assert_eq!(
+            parse_error(&row("buy", "limit", None, "add")),
+            "row 0: missing qty"
+        );
+        assert_eq!(
+            parse_error(&row("buy", "limit", None, "amend")),
+            "row 0: missing qty"
+        );
+        assert_eq!(
+            parse_error(&row("up", "limit", Some(1.0), "add")),
+            "row 0: unknown side 'up'"
+        );
+        assert_eq!(
+            parse_error(&row("buy", "stop", Some(1.0), "add")),
+            "row 0: unknown order type 'stop'"
+        );
+        assert_eq!(
+            parse_error(&row("buy", "limit", Some(1.0), "shout")),
+            "row 0: unknown action 'shout'"
+        );
+    }
+
+    #[test]
+    fn prices_and_quantities_parse_exactly() {
+        let df = df!(
+            "time" => ["1"],
+            "side" => ["buy"],
+            "type" => ["limit"],
+            "price" => ["0.1"],
+            "qty" => ["1e-8"],
+            "id" => ["a"],
+            "action" => ["add"],
+        )
+        .unwrap();
+        let event = &events_from_frame(&df).unwrap()[0];
+        assert_eq!(
+            event.order_type,
+            OrderType::limit("0.1".parse::<Price>().unwrap())
+        );
+        assert_eq!(event.qty, "0.00000001".parse().unwrap());
+    }
+
+    #[test]
+    fn non_positive_quantities_are_rejected() {
+        assert_eq!(
+            parse_error(&row("buy", "limit", Some(0.0), "add")),
+            "row 0: non-positive qty 0.0"
+        );
+        assert_eq!(
+            parse_error(&row("buy", "limit", Some(-1.0), "amend")),
+            "row 0: non-positive qty -1.0"
+        );
+        assert_eq!(
+            parse_error(&row("buy", "limit", Some(-1.0), "cancel")),
+            "row 0: non-positive qty -1.0"
+        );
+    }
+
+    #[test]
+    fn duplicate_live_ids_are_row_errors() {
+        let df = df!(
+            "time" => [1.0, 2.0, 3.0],
+            "side" => ["sell", "sell", "buy"],
+            "type" => ["limit", "limit", "limit"],
+            "price" => [100.0, 101.0, 100.0],
+            "qty" => [5.0, 1.0, 5.0],
+            "id" => ["a", "a", "b"],
+            "action" => ["add", "add", "add"],
+        )
+        .unwrap();
+        let report = replay_events(&mut OrderBook::default(), &events_from_frame(&df).unwrap());
+        assert_eq!(
+            report.outcomes[1].result.as_ref().unwrap_err(),
+            "row 1: duplicate order id"
+        );
+        assert_eq!(report.outcomes[2].status(), Some(OrderStatus::Filled));
+        assert_eq!(report.traded_volume(), Quantity::from(5));
+    }
+
+    #[test]
+    fn cancels_only_need_an_id() {
+        let events = events_from_frame(&row("?", "?", None, "cancel")).unwrap();
+        assert_eq!(events[0].action, Action::Cancel);
+        assert_eq!(events[0].qty, Quantity::ZERO);
+    }
+
+    #[test]
+    fn events_replay_through_a_book() {
+        let df = df!(
+            "time" => [1.0, 2.0, 3.0, 4.0],
+            "side" => [Some("sell"), Some("buy"), None, Some("buy")],
+            "type" => [Some("limit"), Some("limit"), None, Some("limit")],
+            "price" => [Some(100.0), Some(101.0), None, Some(99.0)],
+            "qty" => [Some(5.0), Some(3.0), None, Some(1.0)],
+            "id" => ["a", "b", "a", "c"],
+            "action" => ["add", "add", "cancel", "amend"],
+        )
+        .unwrap();
+        let events = events_from_frame(&df).unwrap();
+        let mut book = OrderBook::default();
+        let report = replay_events(&mut book, &events);
+
+        assert_eq!(report.outcomes.len(), 4);
+        let statuses: Vec<_> = report.outcomes.iter().map(OrderOutcome::status).collect();
+        assert_eq!(
+            statuses,
+            [
+                Some(OrderStatus::Open),
+                Some(OrderStatus::Filled),
+                Some(OrderStatus::Cancelled),
+                None,
+            ]
+        );
+        assert_eq!(report.traded_volume(), Quantity::from(3));
+        assert_eq!(report.status_counts()["NotFound"], 1);
+
+        let executions = report.executions_frame().unwrap();
+        assert_eq!(executions.height(), 1);
+        let id = |name: &str| {
+            executions
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(0)
+                .map(str::to_string)
+        };
+        assert_eq!(id("taker_id").as_deref(), Some("b"));
+        assert_eq!(id("maker_id").as_deref(), Some("a"));
+        assert_eq!(
+            executions.column("price").unwrap().f64().unwrap().get(0),
+            Some(100.0)
+        );
+        assert_eq!(report.outcomes_frame().unwrap().height(), 4);
+    }
+
+    #[test]
+    fn latency_stats_pick_nearest_rank_percentiles() {
+        let stats = LatencyStats::from_durations((1..=100).map(Duration::from_millis));
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(stats.p50, Duration::from_millis(51));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.p999, Duration::from_millis(100));
+        assert_eq!(stats.max, Duration::from_millis(100));
+
+        let empty = LatencyStats::from_durations(std::iter::empty());
+        assert_eq!(empty.count, 0);
+        assert_eq!(empty.max, Duration::ZERO);
+    }
+}
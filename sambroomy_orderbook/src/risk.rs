@@ -0,0 +1,278 @@
+//! Pre-trade risk checks.
+//!
+//! A [`RiskChain`] runs its [`RiskCheck`]s in order against every order
+//! before it reaches the book and stops at the first rejection. Checks see
+//! the order and a [`RiskContext`] of what the engine already knows, so each
+//! is a handful of comparisons rather than a walk over the book.
+
+use std::{collections::HashMap, fmt::Display};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    AccountId, MarkSource, OrderBook, OrderId, OrderRequest, PnlMethod, Position, Price, Quantity,
+    Side, TradingPair, orderbook::Timestamp,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskRejection {
+    /// Name of the check that rejected the order.
+    pub check: &'static str,
+    pub reason: String,
+}
+
+impl RiskRejection {
+    pub fn new(check: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            check,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for RiskRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} rejected the order: {}", self.check, self.reason)
+    }
+}
+
+/// What a check may look at besides the order.
+pub struct RiskContext<'a> {
+    pub pair: &'a TradingPair,
+    /// `None` for orders placed without an account.
+    pub account: Option<AccountId>,
+    pub book: &'a OrderBook,
+    pub last_price: Option<Price>,
+    /// The account's position, when positions are enabled.
+    pub position: Option<&'a Position>,
+    /// The account's resting orders in every market, when the ledger is
+    /// enabled.
+    pub open_orders: usize,
+    /// Set when the order amends this resting order rather than adding one.
+    pub amends: Option<OrderId>,
+    pub now: Timestamp,
+}
+
+impl RiskContext<'_> {
+    pub fn reference_price(&self, source: MarkSource) -> Option<Price> {
+        match source {
+            MarkSource::Mid => self.book.mid_price(),
+            MarkSource::LastTrade => self.last_price,
+        }
+    }
+
+    /// The order's limit, or the best opposite price for a market order.
+    pub fn order_price(&self, order: &OrderRequest) -> Option<Price> {
+        order.price().or(match order.side {
+            Side::Bid => self.book.best_ask(),
+            Side::Ask => self.book.best_bid(),
+        })
+    }
+}
+
+pub trait RiskCheck: Send {
+    fn name(&self) -> &'static str;
+
+    fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection>;
+}
+
+#[derive(Default)]
+pub struct RiskChain {
+    checks: Vec<Box<dyn RiskCheck>>,
+}
+
+impl RiskChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, check: impl RiskCheck + 'static) -> Self {
+        self.push(check);
+        self
+    }
+
+    pub fn push(&mut self, check: impl RiskCheck + 'static) {
+        self.checks.push(Box::new(check));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.checks.iter().map(|c| c.name())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    pub fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        self.checks.iter_mut().try_for_each(|c| c.check(ctx, order))
+    }
+}
+
+pub struct MaxOrderQty(pub Quantity);
+
+impl RiskCheck for MaxOrderQty {
+    fn name(&self) -> &'static str {
+        "max order quantity"
+    }
+
+    fn check(&mut self, _: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        if order.qty > self.0 {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("quantity {} exceeds {}", order.qty, self.0),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Caps quantity times price. Market orders are valued at the best opposite
+/// price and pass when that side is empty, as they cannot fill.
+pub struct MaxNotional(pub Decimal);
+
+impl RiskCheck for MaxNotional {
+    fn name(&self) -> &'static str {
+        "max notional"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        let Some(price) = ctx.order_price(order) else {
+            return Ok(());
+        };
+        let notional = price * order.qty;
+        if notional > self.0 {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("notional {} exceeds {}", notional, self.0),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rejects limits more than `band` (a fraction, e.g. `0.05` for 5%) away
+/// from the reference price. Market orders are checked at the price they
+/// would first fill; orders pass while there is no reference.
+pub struct PriceCollar {
+    pub band: Decimal,
+    pub reference: MarkSource,
+}
+
+impl RiskCheck for PriceCollar {
+    fn name(&self) -> &'static str {
+        "price collar"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        let (Some(reference), Some(price)) =
+            (ctx.reference_price(self.reference), ctx.order_price(order))
+        else {
+            return Ok(());
+        };
+        let (low, high) = (
+            reference * (Decimal::ONE - self.band),
+            reference * (Decimal::ONE + self.band),
+        );
+        if price < low || price > high {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("price {} is outside {} to {}", price, low, high),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Caps the account's resting orders; amendments are not new orders.
+pub struct MaxOpenOrders(pub usize);
+
+impl RiskCheck for MaxOpenOrders {
+    fn name(&self) -> &'static str {
+        "max open orders"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, _: &OrderRequest) -> Result<(), RiskRejection> {
+        if ctx.amends.is_none() && ctx.open_orders >= self.0 {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("{} orders already open", ctx.open_orders),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Caps the absolute position the account would hold if the order filled
+/// completely. Without positions every account counts as flat.
+pub struct MaxPosition(pub Quantity);
+
+impl RiskCheck for MaxPosition {
+    fn name(&self) -> &'static str {
+        "max position"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        let current = ctx.position.map_or(Decimal::ZERO, |p| p.qty);
+        let after = match order.side {
+            Side::Bid => current + order.qty,
+            Side::Ask => current - order.qty,
+        };
+        if after.abs() > self.0 && after.abs() > current.abs() {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("position would reach {}, limit {}", after, self.0),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Stops an account from trading a market once its PnL there, net of fees
+/// and marked at the last trade, has fallen `limit` below where it stood at
+/// its first order of the UTC day. Needs positions to see any PnL.
+pub struct DailyLossLimit {
+    pub limit: Decimal,
+    baselines: HashMap<(AccountId, TradingPair), (NaiveDate, Decimal)>,
+}
+
+impl DailyLossLimit {
+    pub fn new(limit: Decimal) -> Self {
+        Self {
+            limit,
+            baselines: HashMap::new(),
+        }
+    }
+}
+
+impl RiskCheck for DailyLossLimit {
+    fn name(&self) -> &'static str {
+        "daily loss limit"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, _: &OrderRequest) -> Result<(), RiskRejection> {
+        let (Some(account), Some(position)) = (ctx.account, ctx.position) else {
+            return Ok(());
+        };
+        let mark = ctx.last_price.unwrap_or(position.avg_price);
+        let pnl = position.realized(PnlMethod::AverageCost)
+            + position.unrealized(PnlMethod::AverageCost, mark)
+            - position.fees;
+        let today = DateTime::<Utc>::from(ctx.now).date_naive();
+        let (day, baseline) = self
+            .baselines
+            .entry((account, ctx.pair.clone()))
+            .or_insert((today, pnl));
+        if *day != today {
+            (*day, *baseline) = (today, pnl);
+        }
+        let loss = *baseline - pnl;
+        if loss >= self.limit {
+            return Err(RiskRejection::new(
+                self.name(),
+                format!("lost {} today, limit {}", loss, self.limit),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use polars::prelude::*;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+use crate::{
+    Price, Quantity, Side, TradeExecution, TradingPair, orderbook::Timestamp, write_frame,
+};
+
+/// How trades are grouped into bars.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarSpec {
+    /// Fixed wall-clock buckets aligned to multiples of the interval since
+    /// the Unix epoch.
+    Time(Duration),
+    /// A bar every `n` trades.
+    Tick(usize),
+    /// A bar once traded quantity reaches the threshold. Trades are not
+    /// split, so a bar can overshoot by up to one trade.
+    Volume(Quantity),
+}
+
+impl BarSpec {
+    pub fn seconds(secs: u64) -> Self {
+        BarSpec::Time(Duration::from_secs(secs))
+    }
+
+    pub fn minutes(mins: u64) -> Self {
+        BarSpec::Time(Duration::from_secs(mins * 60))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    pub volume: Quantity,
+    pub notional: Decimal,
+    pub trade_count: usize,
+    /// Quantity from trades where the taker was buying.
+    pub buy_volume: Quantity,
+    pub sell_volume: Quantity,
+    pub buy_count: usize,
+    pub sell_count: usize,
+}
+
+impl Bar {
+    fn open_with(trade: &TradeExecution, start: Timestamp) -> Self {
+        let mut bar = Self {
+            start,
+            end: trade.timestamp,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: Quantity::ZERO,
+            notional: Decimal::ZERO,
+            trade_count: 0,
+            buy_volume: Quantity::ZERO,
+            sell_volume: Quantity::ZERO,
+            buy_count: 0,
+            sell_count: 0,
+        };
+        bar.update(trade);
+        bar
+    }
+
+    fn update(&mut self, trade: &TradeExecution) {
+        self.end = self.end.max(trade.timestamp);
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.qty;
+        self.notional += trade.qty * trade.price;
+        self.trade_count += 1;
+        match trade.take_side {
+            Side::Bid => {
+                self.buy_volume += trade.qty;
+                self.buy_count += 1;
+            }
+            Side::Ask => {
+                self.sell_volume += trade.qty;
+                self.sell_count += 1;
+            }
+        }
+    }
+
+    pub fn vwap(&self) -> Option<Price> {
+        (self.volume > Quantity::ZERO).then(|| self.notional / self.volume)
+    }
+}
+
+/// Streams trades into bars of a single [`BarSpec`].
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    spec: BarSpec,
+    current: Option<Bar>,
+}
+
+impl BarBuilder {
+    pub fn new(spec: BarSpec) -> Self {
+        Self {
+            spec,
+            current: None,
+        }
+    }
+
+    pub fn spec(&self) -> BarSpec {
+        self.spec
+    }
+
+    /// The bar still being built, if any.
+    pub fn current(&self) -> Option<&Bar> {
+        self.current.as_ref()
+    }
+
+    /// Adds a trade and returns the bar it completed, if any.
+    pub fn push(&mut self, trade: &TradeExecution) -> Option<Bar> {
+        let mut completed = None;
+        if let (BarSpec::Time(interval), Some(bar)) = (self.spec, &self.current)
+            && bucket_start(trade.timestamp, interval) != bar.start
+        {
+            completed = self.current.take();
+        }
+
+        match self.current.as_mut() {
+            Some(bar) => bar.update(trade),
+            None => {
+                let start = match self.spec {
+                    BarSpec::Time(interval) => bucket_start(trade.timestamp, interval),
+                    BarSpec::Tick(_) | BarSpec::Volume(_) => trade.timestamp,
+                };
+                self.current = Some(Bar::open_with(trade, start));
+            }
+        }
+
+        let full = self.current.as_ref().is_some_and(|bar| match self.spec {
+            BarSpec::Time(_) => false,
+            BarSpec::Tick(n) => bar.trade_count >= n.max(1),
+            BarSpec::Volume(threshold) => bar.volume >= threshold,
+        });
+        if full {
+            completed = self.current.take();
+        }
+        completed
+    }
+
+    /// Closes and returns the bar in progress.
+    pub fn flush(&mut self) -> Option<Bar> {
+        self.current.take()
+    }
+}
+
+fn bucket_start(ts: Timestamp, interval: Duration) -> Timestamp {
+    let since_epoch = ts.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let interval = interval.as_nanos().max(1);
+    UNIX_EPOCH + Duration::from_nanos((since_epoch - since_epoch % interval) as u64)
+}
+
+/// Aggregates `trades` (in execution order) into bars, including the last,
+/// possibly partial, bar.
+pub fn aggregate_bars<'a>(
+    trades: impl IntoIterator<Item = &'a TradeExecution>,
+    spec: BarSpec,
+) -> Vec<Bar> {
+    let mut builder = BarBuilder::new(spec);
+    let mut bars: Vec<Bar> = trades
+        .into_iter()
+        .filter_map(|trade| builder.push(trade))
+        .collect();
+    bars.extend(builder.flush());
+    bars
+}
+
+/// Per-market history of every [`TradeExecution`].
+#[derive(Debug, Default)]
+pub struct TradeTape {
+    trades: HashMap<TradingPair, Vec<TradeExecution>>,
+}
+
+impl TradeTape {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, pair: &TradingPair, executions: &[TradeExecution]) {
+        if executions.is_empty() {
+            return;
+        }
+        self.trades
+            .entry(pair.clone())
+            .or_default()
+            .extend_from_slice(executions);
+    }
+
+    pub fn trades(&self, pair: &TradingPair) -> &[TradeExecution] {
+        self.trades.get(pair).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn markets(&self) -> impl Iterator<Item = &TradingPair> {
+        self.trades.keys()
+    }
+
+    pub fn bars(&self, pair: &TradingPair, spec: BarSpec) -> Vec<Bar> {
+        aggregate_bars(self.trades(pair), spec)
+    }
+
+    pub fn clear(&mut self) {
+        self.trades.clear();
+    }
+
+    pub fn trades_frame(&self, pair: &TradingPair) -> PolarsResult<DataFrame> {
+        trades_frame(self.trades(pair))
+    }
+
+    pub fn bars_frame(&self, pair: &TradingPair, spec: BarSpec) -> PolarsResult<DataFrame> {
+        bars_frame(&self.bars(pair, spec))
+    }
+
+    /// Writes the raw trades of `pair` to `path` (Parquet or CSV by extension).
+    pub fn write_trades(&self, pair: &TradingPair, path: &Path) -> crate::Result<()> {
+        write_frame(&mut self.trades_frame(pair)?, path)
+    }
+
+    pub fn write_bars(&self, pair: &TradingPair, spec: BarSpec, path: &Path) -> crate::Result<()> {
+        write_frame(&mut self.bars_frame(pair, spec)?, path)
+    }
+}
+
+fn epoch_nanos(ts: Timestamp) -> i64 {
+    ts.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as i64
+}
+
+/// Lossy conversion for frames; out-of-range values become NaN.
+pub(crate) fn to_f64(d: Decimal) -> f64 {
+    d.to_f64().unwrap_or(f64::NAN)
+}
+
+pub fn trades_frame(trades: &[TradeExecution]) -> PolarsResult<DataFrame> {
+    df!(
+        "timestamp_ns" => trades.iter().map(|t| epoch_nanos(t.timestamp)).collect::<Vec<_>>(),
+        "price" => trades.iter().map(|t| to_f64(t.price)).collect::<Vec<_>>(),
+        "qty" => trades.iter().map(|t| to_f64(t.qty)).collect::<Vec<_>>(),
+        "take_side" => trades.iter().map(|t| match t.take_side {
+            Side::Bid => "buy",
+            Side::Ask => "sell",
+        }).collect::<Vec<_>>(),
+        "taker_order_id" => trades.iter().map(|t| t.taker_order_id.to_string()).collect::<Vec<_>>(),
+        "maker_order_id" => trades.iter().map(|t| t.maker_order_id.to_string()).collect::<Vec<_>>(),
+    )
+}
+
+pub fn bars_frame(bars: &[Bar]) -> PolarsResult<DataFrame> {
+    df!(
+        "start_ns" => bars.iter().map(|b| epoch_nanos(b.start)).collect::<Vec<_>>(),
+        "end_ns" => bars.iter().map(|b| epoch_nanos(b.end)).collect::<Vec<_>>(),
+        "open" => bars.iter().map(|b| to_f64(b.open)).collect::<Vec<_>>(),
+        "high" => bars.iter().map(|b| to_f64(b.high)).collect::<Vec<_>>(),
+        "low" => bars.iter().map(|b| to_f64(b.low)).collect::<Vec<_>>(),
+        "close" => bars.iter().map(|b| to_f64(b.close)).collect::<Vec<_>>(),
+        "volume" => bars.iter().map(|b| to_f64(b.volume)).collect::<Vec<_>>(),
+        "vwap" => bars.iter().map(|b| b.vwap().map(to_f64)).collect::<Vec<_>>(),
+        "trade_count" => bars.iter().map(|b| b.trade_count as u64).collect::<Vec<_>>(),
+        "buy_volume" => bars.iter().map(|b| to_f64(b.buy_volume)).collect::<Vec<_>>(),
+        "sell_volume" => bars.iter().map(|b| to_f64(b.sell_volume)).collect::<Vec<_>>(),
+        "buy_count" => bars.iter().map(|b| b.buy_count as u64).collect::<Vec<_>>(),
+        "sell_count" => bars.iter().map(|b| b.sell_count as u64).collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn trade(millis: u64, price: u32, qty: u32, take_side: Side) -> TradeExecution {
+        TradeExecution {
+            qty: qty.into(),
+            price: price.into(),
+            taker_order_id: Uuid::nil(),
+            maker_order_id: Uuid::nil(),
+            take_side,
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            maker_fee: None,
+            taker_fee: None,
+        }
+    }
+
+    fn at(millis: u64) -> Timestamp {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn time_bars_split_on_interval_boundaries() {
+        let trades = [
+            trade(1_000, 100, 1, Side::Bid),
+            trade(1_500, 103, 2, Side::Ask),
+            trade(1_999, 99, 1, Side::Bid),
+            trade(2_000, 101, 4, Side::Bid),
+        ];
+        let mut builder = BarBuilder::new(BarSpec::seconds(1));
+        let completed: Vec<_> = trades.iter().map(|t| builder.push(t)).collect();
+        assert!(completed[..3].iter().all(Option::is_none));
+
+        let first = completed[3].clone().unwrap();
+        assert_eq!((first.start, first.end), (at(1_000), at(1_999)));
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (100.into(), 103.into(), 99.into(), 99.into())
+        );
+        assert_eq!(first.volume, Quantity::from(4));
+        assert_eq!(first.vwap(), Some("101.25".parse().unwrap()));
+        assert_eq!(
+            (first.trade_count, first.buy_count, first.sell_count),
+            (3, 2, 1)
+        );
+        assert_eq!(first.buy_volume, Quantity::from(2));
+        assert_eq!(first.sell_volume, Quantity::from(2));
+
+        let second = builder.flush().unwrap();
+        assert_eq!((second.start, second.end), (at(2_000), at(2_000)));
+        assert_eq!((second.open, second.close), (101.into(), 101.into()));
+        assert_eq!(second.volume, Quantity::from(4));
+        assert_eq!(builder.flush(), None);
+    }
+
+    #[test]
+    fn tick_bars_close_every_n_trades() {
+        let trades = [
+            trade(5, 100, 1, Side::Bid),
+            trade(7, 102, 1, Side::Ask),
+            trade(9, 101, 1, Side::Bid),
+        ];
+        let bars = aggregate_bars(&trades, BarSpec::Tick(2));
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].start, bars[0].end), (at(5), at(7)));
+        assert_eq!((bars[0].open, bars[0].close), (100.into(), 102.into()));
+        assert_eq!(bars[0].trade_count, 2);
+        assert_eq!((bars[1].start, bars[1].trade_count), (at(9), 1));
+    }
+
+    #[test]
+    fn volume_bars_keep_the_trade_that_overflows_them() {
+        let trades = [
+            trade(1, 100, 2, Side::Bid),
+            trade(2, 101, 2, Side::Bid),
+            trade(3, 102, 3, Side::Ask),
+            trade(4, 103, 1, Side::Ask),
+        ];
+        let mut builder = BarBuilder::new(BarSpec::Volume(5.into()));
+        assert_eq!(builder.push(&trades[0]), None);
+        assert_eq!(builder.push(&trades[1]), None);
+        let full = builder.push(&trades[2]).unwrap();
+        assert_eq!(full.volume, Quantity::from(7));
+        assert_eq!(
+            (full.high, full.low, full.close),
+            (102.into(), 100.into(), 102.into())
+        );
+        assert_eq!(full.sell_volume, Quantity::from(3));
+        assert_eq!(builder.current(), None);
+
+        assert_eq!(builder.push(&trades[3]), None);
+        assert_eq!(builder.current().unwrap().volume, Quantity::from(1));
+    }
+
+    #[test]
+    fn bars_frame_has_a_row_per_bar() {
+        let trades = [trade(1, 100, 1, Side::Bid), trade(2, 102, 3, Side::Ask)];
+        let df = bars_frame(&aggregate_bars(&trades, BarSpec::Tick(10))).unwrap();
+        assert_eq!(df.height(), 1);
+        let value = |name: &str| df.column(name).unwrap().f64().unwrap().get(0);
+        assert_eq!(value("high"), Some(102.0));
+        assert_eq!(value("vwap"), Some(101.5));
+    }
+}
@@ -0,0 +1,114 @@
+//! Per-market trading states and volatility circuit breakers.
+//!
+//! Every market has a [`TradingState`] that decides which requests it
+//! accepts. Admins move markets between states; a [`CircuitBreaker`] halts
+//! a market on its own, refusing the order that would trade too far too
+//! fast.
+
+use std::{collections::VecDeque, fmt::Display, time::Duration};
+
+use rust_decimal::Decimal;
+
+use crate::{Price, orderbook::Timestamp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TradingState {
+    /// Accepts everything.
+    #[default]
+    Open,
+    /// Accepts orders that rest without trading, and cancels.
+    PostOnly,
+    /// Accepts cancels and reductions only.
+    CancelOnly,
+    /// Accepts nothing; resting orders stay on the book.
+    Halted,
+    /// Accepts nothing; resting orders are cancelled on closing.
+    Closed,
+}
+
+impl TradingState {
+    pub fn accepts_orders(self) -> bool {
+        matches!(self, TradingState::Open | TradingState::PostOnly)
+    }
+
+    pub fn accepts_cancels(self) -> bool {
+        matches!(
+            self,
+            TradingState::Open | TradingState::PostOnly | TradingState::CancelOnly
+        )
+    }
+}
+
+impl Display for TradingState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TradingState::Open => "open",
+            TradingState::PostOnly => "post-only",
+            TradingState::CancelOnly => "cancel-only",
+            TradingState::Halted => "halted",
+            TradingState::Closed => "closed",
+        })
+    }
+}
+
+/// Trips when a trade prints more than `band` (a fraction, e.g. `0.1` for
+/// 10%) away from any trade within the preceding `window`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreaker {
+    pub band: Decimal,
+    pub window: Duration,
+    trades: VecDeque<(Timestamp, Price)>,
+}
+
+impl CircuitBreaker {
+    pub fn new(band: Decimal, window: Duration) -> Result<Self, String> {
+        if band <= Decimal::ZERO {
+            return Err(format!("invalid circuit breaker band {}", band));
+        }
+        Ok(Self {
+            band,
+            window,
+            trades: VecDeque::new(),
+        })
+    }
+
+    /// Records a trade and returns why the breaker tripped, if it did.
+    pub fn observe(&mut self, at: Timestamp, price: Price) -> Option<String> {
+        while self
+            .trades
+            .front()
+            .is_some_and(|(then, _)| at.duration_since(*then).is_ok_and(|age| age > self.window))
+        {
+            self.trades.pop_front();
+        }
+        let tripped = self
+            .trades
+            .iter()
+            .map(|(_, then)| *then)
+            .find(|then| (price - then).abs() > self.band * then)
+            .map(|then| {
+                format!(
+                    "circuit breaker tripped: {} moved more than {}% from {} within {:?}",
+                    price,
+                    (self.band * Decimal::ONE_HUNDRED).normalize(),
+                    then,
+                    self.window
+                )
+            });
+        self.trades.push_back((at, price));
+        tripped
+    }
+
+    /// Forgets past trades, so a resumed market is measured afresh.
+    pub fn reset(&mut self) {
+        self.trades.clear();
+    }
+}
+
+/// A market's state, why it was last halted, and its breaker.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MarketControl {
+    pub state: TradingState,
+    pub reason: Option<String>,
+    pub breaker: Option<CircuitBreaker>,
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::{OrderBookState, Price, Quantity};
+
+const LABEL_WIDTH: u16 = 12;
+
+/// Cumulative bid/ask depth within `window` (a fraction of mid, e.g. 0.01)
+/// on either side of `mid`.
+pub fn render_depth(f: &mut Frame, area: Rect, state: &OrderBookState, mid: Price, window: f64) {
+    let mid = mid.to_f64().unwrap_or(0.0);
+    let (lo, hi) = (mid * (1.0 - window), mid * (1.0 + window));
+
+    // OrderBookState keeps asks highest first and bids best first.
+    let asks = cumulative(state.asks.iter().rev(), |p| p <= hi);
+    let bids = cumulative(state.bids.iter(), |p| p >= lo);
+    let max_qty = asks
+        .last()
+        .into_iter()
+        .chain(bids.last())
+        .map(|&(_, q)| q)
+        .fold(0.0, f64::max)
+        .max(1.0);
+
+    let datasets = vec![
+        Dataset::default()
+            .name("bids")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Green))
+            .data(&bids),
+        Dataset::default()
+            .name("asks")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Red))
+            .data(&asks),
+    ];
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Cumulative depth ±{:.2}%", window * 100.0)),
+        )
+        .x_axis(Axis::default().bounds([lo, hi]).labels(vec![
+            format!("{lo:.2}"),
+            format!("{mid:.2}"),
+            format!("{hi:.2}"),
+        ]))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, max_qty])
+                .labels(vec!["0".to_string(), format!("{max_qty:.2}")]),
+        );
+    f.render_widget(chart, area);
+}
+
+fn cumulative<'a>(
+    levels: impl Iterator<Item = &'a (Price, Quantity)>,
+    in_window: impl Fn(f64) -> bool,
+) -> Vec<(f64, f64)> {
+    let mut total = 0.0;
+    levels
+        .map(|(p, q)| (p.to_f64().unwrap_or(0.0), q.to_f64().unwrap_or(0.0)))
+        .take_while(|&(p, _)| in_window(p))
+        .flat_map(|(p, q)| {
+            // Two points per level draw the curve as a step.
+            let before = total;
+            total += q;
+            [(p, before), (p, total)]
+        })
+        .collect()
+}
+
+struct HeatmapSample {
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+}
+
+/// Rolling history of resting quantity per price, sampled from
+/// [`OrderBookState`]s at a fixed interval.
+pub struct Heatmap {
+    samples: VecDeque<HeatmapSample>,
+    capacity: usize,
+    interval: Duration,
+    last_sample: Option<Instant>,
+}
+
+impl Heatmap {
+    pub fn new(capacity: usize, interval: Duration) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            interval,
+            last_sample: None,
+        }
+    }
+
+    /// Records `state` if at least one interval passed since the last sample.
+    pub fn maybe_sample(&mut self, state: &OrderBookState) -> bool {
+        if self
+            .last_sample
+            .is_some_and(|last| last.elapsed() < self.interval)
+        {
+            return false;
+        }
+        self.sample(state);
+        true
+    }
+
+    pub fn sample(&mut self, state: &OrderBookState) {
+        let to_f64 = |levels: &[(Price, Quantity)]| {
+            levels
+                .iter()
+                .map(|(p, q)| (p.to_f64().unwrap_or(0.0), q.to_f64().unwrap_or(0.0)))
+                .collect()
+        };
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(HeatmapSample {
+            bids: to_f64(&state.bids),
+            asks: to_f64(&state.asks),
+        });
+        self.last_sample = Some(Instant::now());
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.last_sample = None;
+    }
+
+    /// Draws one column per sample (newest on the right) and one row per
+    /// price bucket covering `window` on either side of `center`.
+    pub fn render(&self, f: &mut Frame, area: Rect, center: Price, window: f64) {
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Liquidity heatmap ±{:.2}% ({} samples)",
+            window * 100.0,
+            self.samples.len()
+        ));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let rows = inner.height as usize;
+        let cols = inner.width.saturating_sub(LABEL_WIDTH) as usize;
+        if rows == 0 || cols == 0 {
+            return;
+        }
+
+        let center = center.to_f64().unwrap_or(0.0);
+        let lo = center * (1.0 - window);
+        let bucket = (center * 2.0 * window / rows as f64).max(f64::EPSILON);
+        let columns: Vec<_> = self
+            .samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(cols))
+            .map(|sample| {
+                (
+                    bucketize(&sample.bids, lo, bucket, rows),
+                    bucketize(&sample.asks, lo, bucket, rows),
+                )
+            })
+            .collect();
+        let max_qty = columns
+            .iter()
+            .flat_map(|(b, a)| b.iter().chain(a.iter()))
+            .fold(0.0, |m: f64, &q| m.max(q));
+
+        let lines: Vec<Line> = (0..rows)
+            .rev()
+            .map(|row| {
+                let price = lo + (row as f64 + 0.5) * bucket;
+                let mut spans = vec![Span::raw(format!(
+                    "{:>width$.2} ",
+                    price,
+                    width = LABEL_WIDTH as usize - 1
+                ))];
+                spans.extend(columns.iter().map(|(bids, asks)| {
+                    let color = match (bids[row], asks[row]) {
+                        (b, _) if b > 0.0 => shade(b / max_qty, Color::Rgb(0, 255, 0)),
+                        (_, a) if a > 0.0 => shade(a / max_qty, Color::Rgb(255, 0, 0)),
+                        _ => Color::Reset,
+                    };
+                    Span::styled(" ", Style::default().bg(color))
+                }));
+                Line::from(spans)
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+fn bucketize(levels: &[(f64, f64)], lo: f64, bucket: f64, rows: usize) -> Vec<f64> {
+    let mut buckets = vec![0.0; rows];
+    for &(price, qty) in levels {
+        let idx = ((price - lo) / bucket).floor();
+        if idx >= 0.0 && (idx as usize) < rows {
+            buckets[idx as usize] += qty;
+        }
+    }
+    buckets
+}
+
+fn shade(intensity: f64, full: Color) -> Color {
+    let Color::Rgb(r, g, b) = full else {
+        return full;
+    };
+    // Keep a floor so thin levels stay visible against the background.
+    let k = 0.2 + 0.8 * intensity.clamp(0.0, 1.0);
+    Color::Rgb(
+        (r as f64 * k) as u8,
+        (g as f64 * k) as u8,
+        (b as f64 * k) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucketize_sums_quantity_per_price_bucket() {
+        let levels = [
+            (98.9, 6.0),
+            (99.0, 1.0),
+            (99.4, 2.0),
+            (100.0, 3.0),
+            (101.9, 4.0),
+            (102.0, 5.0),
+        ];
+        // Buckets [99, 100), [100, 101), [101, 102); the rest fall outside.
+        assert_eq!(bucketize(&levels, 99.0, 1.0, 3), [3.0, 3.0, 4.0]);
+        assert_eq!(bucketize(&[], 99.0, 1.0, 2), [0.0, 0.0]);
+        assert!(bucketize(&levels, 99.0, 1.0, 0).is_empty());
+    }
+}
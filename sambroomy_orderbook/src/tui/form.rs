@@ -0,0 +1,288 @@
+use std::str::FromStr;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{OrderId, OrderType, Price, Quantity, Side};
+
+const ORDER_TYPES: [&str; 5] = ["Limit", "Market", "IOC", "FOK", "SystemLevel"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormKind {
+    New,
+    Reduce(OrderId),
+    Amend(OrderId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Side,
+    Type,
+    Price,
+    Qty,
+}
+
+pub enum FormAction {
+    Submit,
+    Close,
+    Edit,
+}
+
+/// Keyboard form used for new orders, partial cancels and amends.
+#[derive(Debug, Clone)]
+pub struct OrderForm {
+    pub kind: FormKind,
+    side: Side,
+    type_idx: usize,
+    price: String,
+    qty: String,
+    focus: usize,
+    pub error: Option<String>,
+}
+
+impl OrderForm {
+    pub fn new_order(side: Side, price: Option<Price>) -> Self {
+        Self {
+            kind: FormKind::New,
+            side,
+            type_idx: 0,
+            price: price.map(|p| p.to_string()).unwrap_or_default(),
+            qty: String::new(),
+            focus: 0,
+            error: None,
+        }
+    }
+
+    pub fn reduce(order_id: OrderId) -> Self {
+        Self {
+            kind: FormKind::Reduce(order_id),
+            ..Self::new_order(Side::Bid, None)
+        }
+    }
+
+    pub fn amend(order_id: OrderId, side: Side, price: Price, qty: Quantity) -> Self {
+        Self {
+            kind: FormKind::Amend(order_id),
+            side,
+            price: price.to_string(),
+            qty: qty.to_string(),
+            ..Self::new_order(side, None)
+        }
+    }
+
+    fn fields(&self) -> &'static [Field] {
+        match self.kind {
+            FormKind::New if self.type_idx == 1 => &[Field::Side, Field::Type, Field::Qty],
+            FormKind::New => &[Field::Side, Field::Type, Field::Price, Field::Qty],
+            FormKind::Reduce(_) => &[Field::Qty],
+            FormKind::Amend(_) => &[Field::Price, Field::Qty],
+        }
+    }
+
+    fn focused(&self) -> Field {
+        let fields = self.fields();
+        fields[self.focus.min(fields.len() - 1)]
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> FormAction {
+        let field = self.focused();
+        match code {
+            KeyCode::Esc => return FormAction::Close,
+            KeyCode::Enter => return FormAction::Submit,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % self.fields().len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                let len = self.fields().len();
+                self.focus = (self.focus + len - 1) % len;
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if field == Field::Side => {
+                self.side = self.side.opposite();
+            }
+            KeyCode::Left if field == Field::Type => {
+                self.type_idx = (self.type_idx + ORDER_TYPES.len() - 1) % ORDER_TYPES.len();
+            }
+            KeyCode::Right | KeyCode::Char(' ') if field == Field::Type => {
+                self.type_idx = (self.type_idx + 1) % ORDER_TYPES.len();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => {
+                if let Some(input) = self.input_mut(field) {
+                    input.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = self.input_mut(field) {
+                    input.pop();
+                }
+            }
+            _ => {}
+        }
+        FormAction::Edit
+    }
+
+    fn input_mut(&mut self, field: Field) -> Option<&mut String> {
+        match field {
+            Field::Price => Some(&mut self.price),
+            Field::Qty => Some(&mut self.qty),
+            Field::Side | Field::Type => None,
+        }
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn price(&self) -> Result<Price, String> {
+        Price::from_str(&self.price).map_err(|_| format!("Invalid price '{}'", self.price))
+    }
+
+    pub fn qty(&self) -> Result<Quantity, String> {
+        let qty = Quantity::from_str(&self.qty)
+            .map_err(|_| format!("Invalid quantity '{}'", self.qty))?;
+        if qty <= Quantity::ZERO {
+            return Err("Quantity must be positive".to_string());
+        }
+        Ok(qty)
+    }
+
+    pub fn order_type(&self) -> Result<OrderType, String> {
+        Ok(match self.type_idx {
+            0 => OrderType::limit(self.price()?),
+            1 => OrderType::Market,
+            2 => OrderType::ioc(self.price()?),
+            3 => OrderType::fok(self.price()?),
+            _ => OrderType::system_level(self.price()?),
+        })
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let title = match self.kind {
+            FormKind::New => "New order",
+            FormKind::Reduce(_) => "Reduce order by",
+            FormKind::Amend(_) => "Amend order",
+        };
+        let focused = self.focused();
+        let mut lines: Vec<Line> = self
+            .fields()
+            .iter()
+            .map(|&field| {
+                let (label, value) = match field {
+                    Field::Side => (
+                        "Side ",
+                        match self.side {
+                            Side::Bid => "BUY".to_string(),
+                            Side::Ask => "SELL".to_string(),
+                        },
+                    ),
+                    Field::Type => ("Type ", ORDER_TYPES[self.type_idx].to_string()),
+                    Field::Price => ("Price", self.price.clone()),
+                    Field::Qty => ("Qty  ", self.qty.clone()),
+                };
+                let style = if field == focused {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![
+                    Span::raw(format!("{label}: ")),
+                    Span::styled(value, style),
+                ])
+            })
+            .collect();
+        lines.push(Line::from(""));
+        match &self.error {
+            Some(err) => lines.push(Line::styled(err.clone(), Style::default().fg(Color::Red))),
+            None => lines.push(Line::styled(
+                "[Tab] field [←/→] toggle [Enter] submit [Esc] close",
+                Style::default().fg(Color::DarkGray),
+            )),
+        }
+
+        let popup = centered(area, 56, lines.len() as u16 + 2);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+            popup,
+        );
+    }
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_keys(form: &mut OrderForm, keys: &str) {
+        for c in keys.chars() {
+            form.handle_key(KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn new_order_form_parses_its_fields() {
+        let mut form = OrderForm::new_order(Side::Bid, Some("101.5".parse().unwrap()));
+        assert_eq!(
+            form.order_type(),
+            Ok(OrderType::limit("101.5".parse::<Price>().unwrap()))
+        );
+        assert_eq!(form.qty(), Err("Invalid quantity ''".to_string()));
+
+        form.handle_key(KeyCode::Char(' '));
+        assert_eq!(form.side(), Side::Ask);
+        form.handle_key(KeyCode::Tab);
+        form.handle_key(KeyCode::Right);
+        assert_eq!(form.order_type(), Ok(OrderType::Market));
+        // Market orders skip the price, so the next field is the quantity.
+        form.handle_key(KeyCode::Tab);
+        type_keys(&mut form, "2x.5");
+        assert_eq!(form.qty(), Ok("2.5".parse().unwrap()));
+    }
+
+    #[test]
+    fn invalid_input_is_reported() {
+        let mut form = OrderForm::reduce(OrderId::nil());
+        type_keys(&mut form, "0");
+        assert_eq!(form.qty(), Err("Quantity must be positive".to_string()));
+        type_keys(&mut form, "..");
+        assert_eq!(form.qty(), Err("Invalid quantity '0..'".to_string()));
+        form.handle_key(KeyCode::Backspace);
+        form.handle_key(KeyCode::Backspace);
+        type_keys(&mut form, "7");
+        assert_eq!(form.qty(), Ok(7.into()));
+
+        let mut form = OrderForm::new_order(Side::Bid, None);
+        assert_eq!(form.price(), Err("Invalid price ''".to_string()));
+        form.handle_key(KeyCode::Tab);
+        form.handle_key(KeyCode::Left);
+        assert!(form.order_type().is_err());
+    }
+
+    #[test]
+    fn amend_form_starts_from_the_order() {
+        let mut form = OrderForm::amend(OrderId::nil(), Side::Ask, 100.into(), 3.into());
+        assert_eq!(form.kind, FormKind::Amend(OrderId::nil()));
+        assert_eq!(
+            (form.side(), form.price(), form.qty()),
+            (Side::Ask, Ok(100.into()), Ok(3.into()))
+        );
+        form.handle_key(KeyCode::Backspace);
+        type_keys(&mut form, "4");
+        assert_eq!(form.price(), Ok(104.into()));
+    }
+}
@@ -0,0 +1,869 @@
+mod charts;
+mod form;
+mod venue;
+
+use std::{
+    collections::VecDeque,
+    io,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use crossbeam_channel::{Receiver, TryRecvError};
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+use crate::{
+    BookChange, BookReader, BookSnapshot, OrderBookState, OrderRequest, OrderResult, OrderStatus,
+    OrderType, Price, Quantity, Side, TradeExecution, TradingPair, gateway::OrderGateway,
+};
+use charts::{Heatmap, render_depth};
+use form::{FormAction, FormKind, OrderForm};
+use venue::{Report, Submission, Venue};
+
+const TICK_RATE: Duration = Duration::from_millis(100);
+const MAX_TAPE_LEN: usize = 500;
+const BAR_WIDTH: usize = 24;
+const TOAST_TTL: Duration = Duration::from_secs(5);
+const MAX_TOASTS: usize = 4;
+const HEATMAP_SAMPLES: usize = 600;
+const HEATMAP_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An order entered through the TUI and the latest result seen for it.
+#[derive(Debug, Clone)]
+pub struct OwnOrder {
+    pub market: Option<TradingPair>,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub result: OrderResult,
+}
+
+impl OwnOrder {
+    pub fn is_live(&self) -> bool {
+        matches!(
+            self.result.status,
+            OrderStatus::Open | OrderStatus::PartiallyFilled
+        )
+    }
+}
+
+/// What the main panel shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum View {
+    Ladder,
+    Depth,
+    Heatmap,
+}
+
+impl View {
+    fn next(self) -> Self {
+        match self {
+            View::Ladder => View::Depth,
+            View::Depth => View::Heatmap,
+            View::Heatmap => View::Ladder,
+        }
+    }
+}
+
+struct Toast {
+    text: String,
+    color: Color,
+    created: Instant,
+}
+
+/// Terminal front end showing a price ladder and a time-and-sales tape.
+///
+/// The ladder is redrawn from a [`BookReader`] and the tape fed from the
+/// book's [`BookChange`]s, so whoever matches only publishes snapshots and
+/// never waits on rendering. With a gateway attached (see
+/// [`App::with_gateway`]) it instead acts as a manual order entry front end
+/// for a local [`crate::OrderBook`] or a [`crate::MatchingEngine`], which then
+/// matches on a thread of its own.
+pub struct App {
+    reader: BookReader,
+    /// `None` once trading through a gateway, whose reports feed the tape.
+    changes: Option<Receiver<BookChange>>,
+    state: OrderBookState,
+    best_prices: (Option<Price>, Option<Price>),
+    spread: Option<Price>,
+    tape: VecDeque<TradeExecution>,
+    depth: usize,
+    connected: bool,
+    should_quit: bool,
+    venue: Option<Venue>,
+    /// Version of the published snapshot the ladder shows.
+    shown: Option<u64>,
+    markets: Vec<TradingPair>,
+    market_idx: usize,
+    own_orders: Vec<OwnOrder>,
+    selected: usize,
+    form: Option<OrderForm>,
+    toasts: VecDeque<Toast>,
+    view: View,
+    window: f64,
+    heatmap: Heatmap,
+}
+
+impl App {
+    /// Watches the book behind `reader`; `changes` comes from
+    /// [`crate::OrderBook::subscribe_changes`] on the same book.
+    pub fn new(reader: BookReader, changes: Receiver<BookChange>) -> Self {
+        Self {
+            reader,
+            changes: Some(changes),
+            state: OrderBookState::default(),
+            best_prices: (None, None),
+            spread: None,
+            tape: VecDeque::with_capacity(MAX_TAPE_LEN),
+            depth: 15,
+            connected: true,
+            should_quit: false,
+            venue: None,
+            shown: None,
+            markets: Vec::new(),
+            market_idx: 0,
+            own_orders: Vec::new(),
+            selected: 0,
+            form: None,
+            toasts: VecDeque::with_capacity(MAX_TOASTS),
+            view: View::Ladder,
+            window: 0.01,
+            heatmap: Heatmap::new(HEATMAP_SAMPLES, HEATMAP_INTERVAL),
+        }
+    }
+
+    pub fn with_view(mut self, view: View) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Trades through `gateway`, which moves to a thread of its own. Orders
+    /// are sent without waiting and their results shown as they come back;
+    /// own orders pick up fills from the executions the venue reports, so
+    /// they end up filled even if the venue forgets them.
+    pub fn with_gateway(mut self, gateway: impl OrderGateway + Send + 'static) -> io::Result<Self> {
+        let venue = Venue::spawn(gateway)?;
+        self.markets = venue.markets().to_vec();
+        self.market_idx = 0;
+        self.venue = Some(venue);
+        self.changes = None;
+        self.shown = None;
+        self.refresh();
+        Ok(self)
+    }
+
+    pub fn market(&self) -> Option<&TradingPair> {
+        self.markets.get(self.market_idx)
+    }
+
+    pub fn own_orders(&self) -> &[OwnOrder] {
+        &self.own_orders
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+        let res = self.run_loop(&mut terminal);
+
+        disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        terminal.show_cursor()?;
+        res
+    }
+
+    fn run_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let mut last_tick = Instant::now();
+        while !self.should_quit {
+            self.drain_changes();
+            self.refresh();
+            self.heatmap.maybe_sample(&self.state);
+            terminal.draw(|f| self.draw(f))?;
+
+            let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
+            if event::poll(timeout)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key.code);
+            }
+            if last_tick.elapsed() >= TICK_RATE {
+                last_tick = Instant::now();
+                self.toasts.retain(|t| t.created.elapsed() < TOAST_TTL);
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the trades the watched book reported onto the tape.
+    pub fn drain_changes(&mut self) {
+        loop {
+            let Some(next) = self.changes.as_ref().map(Receiver::try_recv) else {
+                return;
+            };
+            match next {
+                Ok(BookChange::Executed(execution) | BookChange::Uncrossed(execution)) => {
+                    self.record_trade(execution)
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn show(&mut self, snapshot: &BookSnapshot) {
+        self.state = OrderBookState {
+            asks: snapshot.asks.iter().rev().copied().collect(),
+            bids: snapshot.bids.clone(),
+        };
+        self.best_prices = snapshot.best_prices();
+        self.spread = snapshot.spread();
+    }
+
+    fn record_trade(&mut self, execution: TradeExecution) {
+        if self.tape.len() == MAX_TAPE_LEN {
+            self.tape.pop_back();
+        }
+        self.tape.push_front(execution);
+    }
+
+    /// Handles the venue's reports and shows the selected market's latest
+    /// published snapshot, if it changed.
+    pub fn refresh(&mut self) {
+        self.drain_reports();
+        let reader = match self.venue.as_ref() {
+            Some(venue) => venue.reader(self.market_idx),
+            None => Some(&self.reader),
+        };
+        let snapshot = reader.map(BookReader::load);
+        let version = snapshot.as_ref().map(|s| s.version);
+        if self.shown.is_none() || version != self.shown {
+            self.shown = version;
+            self.show(&snapshot.unwrap_or_default());
+        }
+    }
+
+    fn drain_reports(&mut self) {
+        while let Some(report) = self.venue.as_mut().and_then(|v| v.try_report().ok()) {
+            self.handle_report(report);
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) {
+        if let Some(form) = self.form.as_mut() {
+            match form.handle_key(code) {
+                FormAction::Close => self.form = None,
+                FormAction::Submit => self.submit_form(),
+                FormAction::Edit => {}
+            }
+            return;
+        }
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('+') => self.depth = (self.depth + 1).min(100),
+            KeyCode::Char('-') => self.depth = self.depth.saturating_sub(1).max(1),
+            KeyCode::Char('c') => self.tape.clear(),
+            KeyCode::Char('v') => self.view = self.view.next(),
+            KeyCode::Char('[') => self.window = (self.window / 2.0).max(0.0005),
+            KeyCode::Char(']') => self.window = (self.window * 2.0).min(0.5),
+            _ if self.venue.is_none() => {}
+            KeyCode::Char('b') | KeyCode::Char('n') => {
+                self.form = Some(OrderForm::new_order(Side::Bid, self.best_prices.0));
+            }
+            KeyCode::Char('s') => {
+                self.form = Some(OrderForm::new_order(Side::Ask, self.best_prices.1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.own_orders.len().saturating_sub(1));
+            }
+            KeyCode::Char('x') => self.cancel_selected(),
+            KeyCode::Char('r') => {
+                if let Some(own) = self.selected_live() {
+                    self.form = Some(OrderForm::reduce(own.result.get_id()));
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(own) = self.selected_live() {
+                    match own.order_type {
+                        OrderType::Limit(price) => {
+                            self.form = Some(OrderForm::amend(
+                                own.result.get_id(),
+                                own.side,
+                                price,
+                                own.result.remaining_qty,
+                            ));
+                        }
+                        _ => self.toast("Only limit orders can be amended", Color::Red),
+                    }
+                }
+            }
+            KeyCode::Tab | KeyCode::Char('m') if !self.markets.is_empty() => {
+                self.market_idx = (self.market_idx + 1) % self.markets.len();
+                self.tape.clear();
+                self.heatmap.clear();
+                self.shown = None;
+                self.refresh();
+            }
+            _ => {}
+        }
+    }
+
+    fn selected_live(&self) -> Option<&OwnOrder> {
+        self.own_orders
+            .get(self.selected)
+            .filter(|own| own.is_live())
+    }
+
+    fn submit_form(&mut self) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        let kind = form.kind;
+        let outcome = match kind {
+            FormKind::New => form
+                .order_type()
+                .and_then(|order_type| Ok((order_type, form.qty()?)))
+                .map(|(order_type, qty)| {
+                    Submission::Place(OrderRequest::new(form.side(), qty, order_type))
+                }),
+            FormKind::Reduce(id) => form.qty().map(|qty| Submission::Reduce(id, qty)),
+            FormKind::Amend(id) => form
+                .price()
+                .and_then(|price| Ok(Submission::Amend(id, price, form.qty()?))),
+        };
+        match outcome {
+            Ok(submission) => {
+                self.form = None;
+                self.execute(submission);
+            }
+            Err(err) => form.error = Some(err),
+        }
+    }
+
+    fn execute(&mut self, submission: Submission) {
+        let market = self.markets.get(self.market_idx).cloned();
+        let Some(venue) = self.venue.as_mut() else {
+            return;
+        };
+        if !venue.submit(market, submission) {
+            self.toast("Venue has stopped", Color::Red);
+        }
+    }
+
+    fn handle_report(&mut self, report: Report) {
+        let Report {
+            market,
+            submission,
+            outcome,
+        } = report;
+        match (submission, outcome) {
+            (Submission::Place(order), Ok(Some((result, executions)))) => {
+                self.record_executions(executions);
+                self.report(&result);
+                self.own_orders.push(OwnOrder {
+                    market,
+                    side: order.side,
+                    order_type: order.order_type,
+                    result,
+                });
+                self.selected = self.own_orders.len() - 1;
+            }
+            (Submission::Place(_), Ok(None)) => {}
+            (Submission::Place(_), Err(err)) => self.toast(format!("Rejected: {err}"), Color::Red),
+            (Submission::Cancel(_), Ok(Some((result, _)))) => {
+                self.toast("Cancelled", Color::Yellow);
+                self.update_own(result);
+            }
+            (Submission::Reduce(_, qty), Ok(Some((result, _)))) => {
+                self.toast(format!("Reduced by {qty}"), Color::Yellow);
+                self.update_own(result);
+            }
+            (Submission::Amend(id, price, qty), Ok(Some((result, executions)))) => {
+                self.record_executions(executions);
+                self.toast(format!("Amended to {qty} @ {price}"), Color::Yellow);
+                if let Some(own) = self.own_orders.iter_mut().find(|o| o.result.get_id() == id) {
+                    own.order_type = OrderType::Limit(price);
+                }
+                self.update_own(result);
+            }
+            (Submission::Amend(..), Ok(None)) => self.toast("Order cannot be amended", Color::Red),
+            (_, Ok(None)) => self.toast("Order no longer in book", Color::Red),
+            (Submission::Cancel(_), Err(err)) => {
+                self.toast(format!("Cancel rejected: {err}"), Color::Red)
+            }
+            (Submission::Reduce(..), Err(err)) => {
+                self.toast(format!("Reduce rejected: {err}"), Color::Red)
+            }
+            (Submission::Amend(..), Err(err)) => {
+                self.toast(format!("Amend rejected: {err}"), Color::Red)
+            }
+        }
+    }
+
+    fn cancel_selected(&mut self) {
+        let Some(own) = self.selected_live() else {
+            return;
+        };
+        let id = own.result.get_id();
+        let market = own.market.clone();
+        if let Some(venue) = self.venue.as_mut()
+            && !venue.submit(market, Submission::Cancel(id))
+        {
+            self.toast("Venue has stopped", Color::Red);
+        }
+    }
+
+    fn update_own(&mut self, result: OrderResult) {
+        if let Some(own) = self
+            .own_orders
+            .iter_mut()
+            .find(|o| o.result.get_id() == result.get_id())
+        {
+            own.result = result;
+        }
+    }
+
+    /// Adds `executions` to the tape and fills the own orders they hit.
+    fn record_executions(&mut self, executions: Vec<TradeExecution>) {
+        for execution in executions {
+            for own in self.own_orders.iter_mut().filter(|o| o.is_live()) {
+                own.result.record_maker_fill(&execution);
+            }
+            self.record_trade(execution);
+        }
+    }
+
+    fn report(&mut self, result: &OrderResult) {
+        let filled = result.filled_qty();
+        match result.status {
+            OrderStatus::Filled => self.toast(
+                format!("Filled {filled} @ {}", result.avr_fill_price()),
+                Color::Green,
+            ),
+            OrderStatus::PartiallyFilled => self.toast(
+                format!(
+                    "Partially filled {filled} @ {}, {} left",
+                    result.avr_fill_price(),
+                    result.remaining_qty
+                ),
+                Color::Green,
+            ),
+            OrderStatus::Open => self.toast("Order resting", Color::Cyan),
+            OrderStatus::Cancelled if filled > Quantity::ZERO => self.toast(
+                format!(
+                    "Filled {filled} @ {}, rest cancelled",
+                    result.avr_fill_price()
+                ),
+                Color::Yellow,
+            ),
+            OrderStatus::Cancelled => self.toast("Rejected: no liquidity", Color::Red),
+        }
+    }
+
+    fn toast(&mut self, text: impl Into<String>, color: Color) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            text: text.into(),
+            color,
+            created: Instant::now(),
+        });
+    }
+
+    pub fn mid(&self) -> Option<Price> {
+        match self.best_prices {
+            (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+            _ => None,
+        }
+    }
+
+    /// Price the depth and heatmap views centre on; falls back to the only
+    /// populated side of a one-sided book.
+    fn center(&self) -> Option<Price> {
+        self.mid().or(self.best_prices.0).or(self.best_prices.1)
+    }
+
+    fn draw(&self, f: &mut Frame) {
+        let trading = self.venue.is_some();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(if trading { 10 } else { 0 }),
+                Constraint::Length(if trading { MAX_TOASTS as u16 + 2 } else { 0 }),
+            ])
+            .split(f.area());
+        self.draw_summary(f, chunks[0]);
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        match (self.view, self.center()) {
+            (View::Ladder, _) => self.draw_ladder(f, body[0]),
+            (View::Depth, Some(center)) => {
+                render_depth(f, body[0], &self.state, center, self.window)
+            }
+            (View::Heatmap, Some(center)) => self.heatmap.render(f, body[0], center, self.window),
+            (_, None) => f.render_widget(
+                Paragraph::new("Book is empty").block(Block::default().borders(Borders::ALL)),
+                body[0],
+            ),
+        }
+        self.draw_tape(f, body[1]);
+
+        if trading {
+            self.draw_orders(f, chunks[2]);
+            self.draw_toasts(f, chunks[3]);
+        }
+        if let Some(form) = &self.form {
+            form.render(f, f.area());
+        }
+    }
+
+    fn draw_summary(&self, f: &mut Frame, area: Rect) {
+        let (bid, ask) = self.best_prices;
+        let status = if self.connected {
+            Span::styled("LIVE", Style::default().fg(Color::Green))
+        } else {
+            Span::styled("DISCONNECTED", Style::default().fg(Color::Red))
+        };
+        let market = match self.market() {
+            Some(pair) => format!("{pair} "),
+            None => String::new(),
+        };
+        let mut line = Line::from(vec![
+            Span::styled(market, Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("Bid {} ", fmt_opt(bid)),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(
+                format!("Ask {} ", fmt_opt(ask)),
+                Style::default().fg(Color::Red),
+            ),
+            Span::raw(format!("Mid {} ", fmt_opt(self.mid()))),
+            Span::raw(format!("Spread {} ", fmt_opt(self.spread))),
+            status,
+        ]);
+        if let Some(venue) = self.venue.as_ref().filter(|v| v.pending() > 0) {
+            line.push_span(Span::styled(
+                format!(" {} pending", venue.pending()),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        let keys = if self.venue.is_some() {
+            "BBO  [q]uit [v]iew [+/-] depth [[/]] window [c]lear tape [b]uy [s]ell [x] cancel [r]educe [a]mend [m]arket"
+        } else {
+            "BBO  [q]uit [v]iew [+/-] depth [[/]] window [c]lear tape"
+        };
+        let paragraph =
+            Paragraph::new(line).block(Block::default().borders(Borders::ALL).title(keys));
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_ladder(&self, f: &mut Frame, area: Rect) {
+        let asks = &self.state.asks[self.state.asks.len().saturating_sub(self.depth)..];
+        let bids = &self.state.bids[..self.state.bids.len().min(self.depth)];
+        let max_qty = asks
+            .iter()
+            .chain(bids.iter())
+            .map(|(_, qty)| *qty)
+            .max()
+            .unwrap_or(Decimal::ZERO);
+
+        let ask_style = Style::default().fg(Color::Red);
+        let bid_style = Style::default().fg(Color::Green);
+        let rows = asks
+            .iter()
+            .map(|level| ladder_row(level, max_qty, ask_style))
+            .chain(std::iter::once(Row::new(vec![
+                Cell::from(""),
+                Cell::from(format!("-- {} --", fmt_opt(self.spread))),
+                Cell::from(""),
+            ])))
+            .chain(
+                bids.iter()
+                    .map(|level| ladder_row(level, max_qty, bid_style)),
+            );
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Min(BAR_WIDTH as u16),
+            ],
+        )
+        .header(
+            Row::new(vec!["Price", "Qty", "Depth"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Ladder"));
+        f.render_widget(table, area);
+    }
+
+    fn draw_tape(&self, f: &mut Frame, area: Rect) {
+        let visible = area.height.saturating_sub(3) as usize;
+        let rows = self.tape.iter().take(visible).map(|trade| {
+            let style = match trade.take_side {
+                Side::Bid => Style::default().fg(Color::Green),
+                Side::Ask => Style::default().fg(Color::Red),
+            };
+            let time: DateTime<Local> = trade.timestamp.into();
+            Row::new(vec![
+                Cell::from(time.format("%H:%M:%S%.3f").to_string()),
+                Cell::from(trade.price.to_string()),
+                Cell::from(trade.qty.to_string()),
+                Cell::from(match trade.take_side {
+                    Side::Bid => "BUY",
+                    Side::Ask => "SELL",
+                }),
+            ])
+            .style(style)
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(13),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(5),
+            ],
+        )
+        .header(
+            Row::new(vec!["Time", "Price", "Qty", "Side"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Time & Sales"));
+        f.render_widget(table, area);
+    }
+
+    fn draw_orders(&self, f: &mut Frame, area: Rect) {
+        let visible = area.height.saturating_sub(3) as usize;
+        let skip = (self.selected + 1).saturating_sub(visible);
+        let rows = self
+            .own_orders
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(visible)
+            .map(|(i, own)| {
+                let result = &own.result;
+                let filled = result.filled_qty();
+                let avg = if filled > Quantity::ZERO {
+                    result.avr_fill_price().round_dp(8).to_string()
+                } else {
+                    "-".to_string()
+                };
+                let mut style = match own.side {
+                    Side::Bid => Style::default().fg(Color::Green),
+                    Side::Ask => Style::default().fg(Color::Red),
+                };
+                if !own.is_live() {
+                    style = style.fg(Color::DarkGray);
+                }
+                if i == self.selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Row::new(vec![
+                    Cell::from(
+                        own.market
+                            .as_ref()
+                            .map(|m| m.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    Cell::from(own.result.get_id().to_string()[..8].to_string()),
+                    Cell::from(match own.side {
+                        Side::Bid => "BUY",
+                        Side::Ask => "SELL",
+                    }),
+                    Cell::from(own.order_type.to_string()),
+                    Cell::from(fmt_opt(own.order_type.price())),
+                    Cell::from(format!("{:?}", result.status)),
+                    Cell::from(filled.to_string()),
+                    Cell::from(result.remaining_qty.to_string()),
+                    Cell::from(avg),
+                ])
+                .style(style)
+            });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(9),
+                Constraint::Length(5),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(16),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Min(12),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "Market", "Id", "Side", "Type", "Price", "Status", "Filled", "Left", "Avg px",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("My orders"));
+        f.render_widget(table, area);
+    }
+
+    fn draw_toasts(&self, f: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self
+            .toasts
+            .iter()
+            .rev()
+            .map(|t| Line::styled(t.text.clone(), Style::default().fg(t.color)))
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Messages")),
+            area,
+        );
+    }
+}
+
+fn ladder_row(level: &(Price, Quantity), max_qty: Quantity, style: Style) -> Row<'static> {
+    let (price, qty) = level;
+    Row::new(vec![
+        Cell::from(price.to_string()),
+        Cell::from(qty.to_string()),
+        Cell::from(qty_bar(*qty, max_qty, BAR_WIDTH)),
+    ])
+    .style(style)
+}
+
+fn qty_bar(qty: Quantity, max_qty: Quantity, width: usize) -> String {
+    if max_qty <= Decimal::ZERO {
+        return String::new();
+    }
+    let ratio = (qty / max_qty).to_f64().unwrap_or(0.0);
+    "█".repeat((ratio * width as f64).round() as usize)
+}
+
+fn fmt_opt(value: Option<Decimal>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrderBook, OrderId};
+    use crossbeam_channel::unbounded;
+
+    fn app() -> App {
+        let (_, changes) = unbounded();
+        App::new(OrderBook::default().reader(), changes)
+    }
+
+    /// Handles the venue's next report, waiting for it if needed.
+    fn step(app: &mut App) {
+        let report = app.venue.as_mut().and_then(Venue::wait_report).unwrap();
+        app.handle_report(report);
+    }
+
+    fn place(app: &mut App, side: Side, qty: u32, price: u32) -> OrderId {
+        let order = OrderRequest::new(side, qty, OrderType::limit(price));
+        app.execute(Submission::Place(order));
+        step(app);
+        order.id()
+    }
+
+    fn own_status(app: &App, id: OrderId) -> Option<OrderStatus> {
+        app.own_orders()
+            .iter()
+            .find(|o| o.result.get_id() == id)
+            .map(|o| o.result.status)
+    }
+
+    #[test]
+    fn watches_published_snapshots_and_executions() {
+        let mut book = OrderBook::default();
+        let mut app = App::new(book.reader(), book.subscribe_changes());
+        book.add_order(OrderRequest::new(Side::Bid, 2, OrderType::limit(99)));
+        book.add_order(OrderRequest::new(Side::Ask, 3, OrderType::limit(101)));
+        app.refresh();
+        assert_eq!(app.best_prices, (None, None));
+
+        book.publish();
+        app.refresh();
+        assert_eq!(app.best_prices, (Some(99.into()), Some(101.into())));
+        assert_eq!(app.spread, Some(2.into()));
+        assert_eq!(app.mid(), Some(100.into()));
+        assert_eq!(app.state, book.get_order_book_state());
+
+        let (_, executions) = book.add_order(OrderRequest::new(Side::Bid, 1, OrderType::Market));
+        app.drain_changes();
+        assert_eq!(app.tape.len(), 1);
+        assert_eq!(app.tape[0].price, 101.into());
+
+        for _ in 0..MAX_TAPE_LEN {
+            app.record_trade(executions[0].clone());
+        }
+        assert_eq!(app.tape.len(), MAX_TAPE_LEN);
+
+        drop(book);
+        app.drain_changes();
+        assert!(!app.connected);
+    }
+
+    #[test]
+    fn own_orders_follow_the_venue_reports() {
+        let mut app = app().with_gateway(OrderBook::default()).unwrap();
+        let maker = place(&mut app, Side::Ask, 1, 100);
+        let resting = place(&mut app, Side::Ask, 1, 105);
+        assert_eq!(own_status(&app, maker), Some(OrderStatus::Open));
+
+        app.refresh();
+        assert_eq!(app.best_prices, (None, Some(100.into())));
+
+        app.selected = 1;
+        app.cancel_selected();
+        assert_eq!(app.venue.as_ref().map(Venue::pending), Some(1));
+        step(&mut app);
+        assert_eq!(own_status(&app, resting), Some(OrderStatus::Cancelled));
+    }
+
+    #[test]
+    fn makers_fill_from_executions_without_retention() {
+        let mut app = app().with_gateway(OrderBook::default()).unwrap();
+        let maker = place(&mut app, Side::Ask, 1, 100);
+        let taker = place(&mut app, Side::Bid, 1, 100);
+        assert_eq!(own_status(&app, maker), Some(OrderStatus::Filled));
+        assert_eq!(own_status(&app, taker), Some(OrderStatus::Filled));
+        assert_eq!(app.tape.len(), 1);
+
+        app.refresh();
+        assert_eq!(app.best_prices, (None, None));
+        assert_eq!(app.own_orders().len(), 2);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    io,
+    thread::{self, JoinHandle},
+};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError, unbounded};
+
+use crate::{
+    BookReader, OrderId, OrderRequest, OrderResult, Price, Quantity, TradeExecution, TradingPair,
+    gateway::{GatewayResult, OrderGateway},
+};
+
+/// A request from the order entry panel.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Submission {
+    Place(OrderRequest),
+    Cancel(OrderId),
+    Reduce(OrderId, Quantity),
+    Amend(OrderId, Price, Quantity),
+}
+
+/// What the venue made of one [`Submission`]. `None` means the order was
+/// no longer in the book (or could not be amended).
+pub(super) struct Report {
+    pub market: Option<TradingPair>,
+    pub submission: Submission,
+    pub outcome: GatewayResult<Option<(OrderResult, Vec<TradeExecution>)>>,
+}
+
+/// An [`OrderGateway`] running on its own thread, so that matching never
+/// holds up rendering.
+///
+/// Submissions queue on a channel and their [`Report`]s come back in the
+/// same order. The book is read through each market's [`BookReader`]
+/// instead of going through the queue.
+pub(super) struct Venue {
+    markets: Vec<TradingPair>,
+    /// One per market, or a single one for a venue without markets.
+    readers: Vec<BookReader>,
+    submissions: Option<Sender<(Option<TradingPair>, Submission)>>,
+    reports: Receiver<Report>,
+    pending: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Venue {
+    pub fn spawn(gateway: impl OrderGateway + Send + 'static) -> io::Result<Self> {
+        let markets = gateway.markets();
+        let readers = if markets.is_empty() {
+            vec![gateway.reader(None)]
+        } else {
+            markets.iter().map(|m| gateway.reader(Some(m))).collect()
+        };
+        let readers = readers
+            .into_iter()
+            .collect::<GatewayResult<Vec<_>>>()
+            .map_err(io::Error::other)?;
+        let (submissions, inbox) = unbounded();
+        let (outbox, reports) = unbounded();
+        let thread = thread::Builder::new()
+            .name("tui-venue".to_string())
+            .spawn(move || run_venue(gateway, inbox, outbox))?;
+        Ok(Self {
+            markets,
+            readers,
+            submissions: Some(submissions),
+            reports,
+            pending: 0,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn markets(&self) -> &[TradingPair] {
+        &self.markets
+    }
+
+    pub fn reader(&self, market_idx: usize) -> Option<&BookReader> {
+        self.readers.get(market_idx)
+    }
+
+    /// Queues `submission` without waiting for the venue.
+    pub fn submit(&mut self, market: Option<TradingPair>, submission: Submission) -> bool {
+        let sent = self
+            .submissions
+            .as_ref()
+            .is_some_and(|s| s.send((market, submission)).is_ok());
+        if sent {
+            self.pending += 1;
+        }
+        sent
+    }
+
+    /// The next report, if the venue has finished one.
+    pub fn try_report(&mut self) -> Result<Report, TryRecvError> {
+        let report = self.reports.try_recv()?;
+        self.pending -= 1;
+        Ok(report)
+    }
+
+    /// Waits for the next report; used by tests to step the venue.
+    #[cfg(test)]
+    pub fn wait_report(&mut self) -> Option<Report> {
+        let report = self.reports.recv().ok()?;
+        self.pending -= 1;
+        Some(report)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+}
+
+impl Drop for Venue {
+    fn drop(&mut self) {
+        self.submissions = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run_venue(
+    mut gateway: impl OrderGateway,
+    submissions: Receiver<(Option<TradingPair>, Submission)>,
+    reports: Sender<Report>,
+) {
+    for (market, submission) in submissions {
+        let pair = market.as_ref();
+        let outcome = match submission {
+            Submission::Place(order) => gateway.place(pair, order).map(Some),
+            Submission::Cancel(id) => gateway.cancel(pair, id).map(|r| r.map(|r| (r, Vec::new()))),
+            Submission::Reduce(id, qty) => gateway
+                .reduce(pair, id, qty)
+                .map(|r| r.map(|r| (r, Vec::new()))),
+            Submission::Amend(id, price, qty) => gateway.amend(pair, id, price, qty),
+        };
+        let report = Report {
+            market,
+            submission,
+            outcome,
+        };
+        if reports.send(report).is_err() {
+            break;
+        }
+    }
+}
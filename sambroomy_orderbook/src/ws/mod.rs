@@ -0,0 +1,475 @@
+//! WebSocket order entry and market data over a [`MatchingEngine`].
+//!
+//! See [`protocol`] for the message format. Requests from all connections
+//! are applied to one engine behind a mutex; market data fans out through a
+//! broadcast channel that each connection filters by its subscriptions.
+
+pub mod protocol;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, warn};
+use rust_decimal::Decimal;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+use crate::{
+    BookSnapshot, MatchingEngine, OrderId, OrderRequest, OrderResult, TradeExecution, TradingPair,
+    orderbook::DEFAULT_SNAPSHOT_DEPTH,
+};
+use protocol::{AckResult, Channel, ClientRequest, Op, OrderReport, ServerMessage, TradeReport};
+
+type ConnId = u64;
+
+const EVENT_CAPACITY: usize = 4096;
+const DEFAULT_BOOK_DEPTH: usize = 20;
+
+#[derive(Debug)]
+enum Event {
+    Market {
+        channel: Channel,
+        market: TradingPair,
+        message: ServerMessage,
+    },
+    Own {
+        conn: ConnId,
+        market: TradingPair,
+        message: ServerMessage,
+    },
+}
+
+struct Core {
+    engine: MatchingEngine,
+    /// Last snapshot each market's L2 diffs were computed against.
+    l2: HashMap<TradingPair, Arc<BookSnapshot>>,
+    /// Kept while the engine still answers for the order, resting or
+    /// retained after it left the book.
+    owners: HashMap<OrderId, ConnId>,
+}
+
+impl Core {
+    /// Drops `order_id`'s owner once the engine has forgotten the order.
+    fn forget_if_gone(&mut self, pair: &TradingPair, order_id: &OrderId) {
+        if self
+            .engine
+            .order_status(pair, *order_id)
+            .ok()
+            .flatten()
+            .is_none()
+        {
+            self.owners.remove(order_id);
+        }
+    }
+}
+
+struct Shared {
+    core: Mutex<Core>,
+    events: broadcast::Sender<Arc<Event>>,
+    next_conn: AtomicU64,
+}
+
+/// WebSocket server exposing a [`MatchingEngine`]. Cheap to clone.
+#[derive(Clone)]
+pub struct WsGateway {
+    shared: Arc<Shared>,
+}
+
+#[derive(Default)]
+struct Session {
+    subscriptions: HashSet<(Channel, Option<TradingPair>)>,
+}
+
+impl Session {
+    fn wants(&self, channel: Channel, market: &TradingPair) -> bool {
+        self.subscriptions.contains(&(channel, None))
+            || self
+                .subscriptions
+                .contains(&(channel, Some(market.clone())))
+    }
+}
+
+impl WsGateway {
+    pub fn new(engine: MatchingEngine) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            shared: Arc::new(Shared {
+                core: Mutex::new(Core {
+                    engine,
+                    l2: HashMap::new(),
+                    owners: HashMap::new(),
+                }),
+                events,
+                next_conn: AtomicU64::new(1),
+            }),
+        }
+    }
+
+    /// Runs `f` against the engine, e.g. to add markets while serving.
+    pub fn with_engine<T>(&self, f: impl FnOnce(&mut MatchingEngine) -> T) -> T {
+        f(&mut self.shared.core.lock().unwrap().engine)
+    }
+
+    /// Accepts connections until the listener fails.
+    pub async fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let gateway = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = gateway.handle_connection(stream).await {
+                    debug!("connection {} closed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(
+        self,
+        stream: TcpStream,
+    ) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+        let mut ws = accept_async(stream).await?;
+        let conn = self.shared.next_conn.fetch_add(1, Ordering::Relaxed);
+        let mut events = self.shared.events.subscribe();
+        let mut session = Session::default();
+
+        let result = loop {
+            let outgoing = tokio::select! {
+                incoming = ws.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => self.handle_text(conn, &mut session, &text),
+                    Some(Ok(Message::Binary(_))) => vec![ServerMessage::Reject {
+                        id: None,
+                        reason: "binary frames are not supported".to_string(),
+                    }],
+                    Some(Ok(Message::Close(_))) | None => break Ok(()),
+                    Some(Ok(_)) => Vec::new(),
+                    Some(Err(e)) => break Err(e),
+                },
+                event = events.recv() => match event {
+                    Ok(event) => filter_event(&event, conn, &session).into_iter().collect(),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("connection {} lagged, dropped {} events", conn, n);
+                        Vec::new()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break Ok(()),
+                },
+            };
+            if let Err(e) = send_all(&mut ws, outgoing).await {
+                break Err(e);
+            }
+        };
+
+        self.shared
+            .core
+            .lock()
+            .unwrap()
+            .owners
+            .retain(|_, owner| *owner != conn);
+        result
+    }
+
+    fn handle_text(&self, conn: ConnId, session: &mut Session, text: &str) -> Vec<ServerMessage> {
+        match serde_json::from_str::<ClientRequest>(text) {
+            Ok(request) => {
+                let id = request.id.clone();
+                match self.handle_request(conn, session, request) {
+                    Ok((result, extra)) => {
+                        let mut out = vec![ServerMessage::Ack { id, result }];
+                        out.extend(extra);
+                        out
+                    }
+                    Err(reason) => vec![ServerMessage::Reject { id, reason }],
+                }
+            }
+            Err(e) => vec![ServerMessage::Reject {
+                id: serde_json::from_str::<serde_json::Value>(text)
+                    .ok()
+                    .and_then(|v| v.get("id").cloned()),
+                reason: e.to_string(),
+            }],
+        }
+    }
+
+    /// Applies one request. Returns the ack payload plus any messages that
+    /// must follow it on this connection, such as an L2 snapshot.
+    fn handle_request(
+        &self,
+        conn: ConnId,
+        session: &mut Session,
+        request: ClientRequest,
+    ) -> Result<(AckResult, Vec<ServerMessage>), String> {
+        let mut core = self.shared.core.lock().unwrap();
+        match &request.op {
+            Op::Place {
+                market,
+                side,
+                order_type,
+                price,
+                qty,
+            } => {
+                let pair = market.parse()?;
+                if *qty <= Decimal::ZERO {
+                    return Err("qty must be positive".to_string());
+                }
+                let order =
+                    OrderRequest::new((*side).into(), *qty, order_type.to_order_type(*price)?);
+                let (result, executions) = core.engine.place_order(&pair, order)?;
+                if core.engine.order_status(&pair, result.get_id())?.is_some() {
+                    core.owners.insert(result.get_id(), conn);
+                }
+                self.publish(&mut core, &pair, &executions);
+                Ok((order_ack(&result, &executions), Vec::new()))
+            }
+            Op::Cancel { market, order_id } => {
+                let pair = market.parse()?;
+                check_owner(&core, conn, order_id)?;
+                let result = core
+                    .engine
+                    .cancel_order(&pair, *order_id)?
+                    .ok_or_else(|| format!("unknown order {}", order_id))?;
+                core.forget_if_gone(&pair, order_id);
+                self.publish(&mut core, &pair, &[]);
+                Ok((order_ack(&result, &[]), Vec::new()))
+            }
+            Op::Reduce {
+                market,
+                order_id,
+                qty,
+            } => {
+                let pair = market.parse()?;
+                if *qty <= Decimal::ZERO {
+                    return Err("qty must be positive".to_string());
+                }
+                check_owner(&core, conn, order_id)?;
+                let result = core
+                    .engine
+                    .reduce_order(&pair, *order_id, *qty)?
+                    .ok_or_else(|| format!("unknown order {}", order_id))?;
+                core.forget_if_gone(&pair, order_id);
+                self.publish(&mut core, &pair, &[]);
+                Ok((order_ack(&result, &[]), Vec::new()))
+            }
+            Op::Amend {
+                market,
+                order_id,
+                price,
+                qty,
+            } => {
+                let pair = market.parse()?;
+                if *price <= Decimal::ZERO {
+                    return Err("price must be positive".to_string());
+                }
+                if *qty <= Decimal::ZERO {
+                    return Err("qty must be positive".to_string());
+                }
+                check_owner(&core, conn, order_id)?;
+                let (result, executions) = core
+                    .engine
+                    .amend_order(&pair, *order_id, *price, *qty)?
+                    .ok_or_else(|| format!("order {} cannot be amended", order_id))?;
+                core.forget_if_gone(&pair, order_id);
+                self.publish(&mut core, &pair, &executions);
+                Ok((order_ack(&result, &executions), Vec::new()))
+            }
+            Op::Order { market, order_id } => {
+                let pair = market.parse()?;
+                check_owner(&core, conn, order_id)?;
+                let result = core.engine.order_status(&pair, *order_id)?;
+                let Some(result) = result else {
+                    core.owners.remove(order_id);
+                    return Err(format!("unknown order {}", order_id));
+                };
+                Ok((order_ack(&result, &[]), Vec::new()))
+            }
+            Op::Markets => {
+                let mut markets: Vec<_> = core
+                    .engine
+                    .get_markets()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                markets.sort();
+                Ok((AckResult::Markets { markets }, Vec::new()))
+            }
+            Op::Book { market, depth } => {
+                let pair: TradingPair = market.parse()?;
+                let depth = depth.unwrap_or(DEFAULT_BOOK_DEPTH);
+                let snapshot = if depth <= DEFAULT_SNAPSHOT_DEPTH {
+                    core.engine.reader().snapshot(&pair)?
+                } else {
+                    Arc::new(core.engine.deep_snapshot(&pair, depth)?)
+                };
+                Ok((
+                    AckResult::Book {
+                        market: pair.to_string(),
+                        version: snapshot.version,
+                        bids: snapshot.bids.iter().take(depth).copied().collect(),
+                        asks: snapshot.asks.iter().take(depth).copied().collect(),
+                    },
+                    Vec::new(),
+                ))
+            }
+            Op::Subscribe { channel, market } => {
+                let pair = market
+                    .as_deref()
+                    .map(str::parse::<TradingPair>)
+                    .transpose()?;
+                if let Some(pair) = &pair
+                    && !core.engine.market_exists(pair)
+                {
+                    return Err(format!("Market for {} does not exist", pair));
+                }
+                session.subscriptions.insert((*channel, pair.clone()));
+                // L2 subscribers start from the same snapshot later diffs are
+                // computed against; updates with a version at or below it
+                // can be ignored.
+                let snapshots = match channel {
+                    Channel::L2 => {
+                        let pairs = pair.map_or_else(|| core.engine.get_markets(), |p| vec![p]);
+                        pairs
+                            .iter()
+                            .map(|p| ServerMessage::l2_snapshot(p, &l2_base(&mut core, p)))
+                            .collect()
+                    }
+                    Channel::Trades | Channel::Orders => Vec::new(),
+                };
+                Ok((
+                    AckResult::Subscription {
+                        channel: *channel,
+                        market: market.clone(),
+                    },
+                    snapshots,
+                ))
+            }
+            Op::Unsubscribe { channel, market } => {
+                let pair = market
+                    .as_deref()
+                    .map(str::parse::<TradingPair>)
+                    .transpose()?;
+                if !session.subscriptions.remove(&(*channel, pair)) {
+                    return Err("not subscribed".to_string());
+                }
+                Ok((
+                    AckResult::Subscription {
+                        channel: *channel,
+                        market: market.clone(),
+                    },
+                    Vec::new(),
+                ))
+            }
+        }
+    }
+
+    /// Broadcasts trades, fills of other connections' resting orders and
+    /// the L2 diff caused by a mutation of `pair`.
+    fn publish(&self, core: &mut Core, pair: &TradingPair, executions: &[TradeExecution]) {
+        let events = &self.shared.events;
+        for execution in executions {
+            let _ = events.send(Arc::new(Event::Market {
+                channel: Channel::Trades,
+                market: pair.clone(),
+                message: ServerMessage::Trade {
+                    market: pair.to_string(),
+                    trade: TradeReport::from(execution),
+                },
+            }));
+
+            let maker = execution.maker_order_id;
+            let Some(&owner) = core.owners.get(&maker) else {
+                continue;
+            };
+            let resting = core.engine.get_order(pair, maker).ok().flatten();
+            let (status, remaining_qty) = match &resting {
+                Some(order) => (order.status.into(), order.remaining_qty),
+                None => {
+                    core.forget_if_gone(pair, &maker);
+                    (protocol::WireStatus::Filled, Default::default())
+                }
+            };
+            let _ = events.send(Arc::new(Event::Own {
+                conn: owner,
+                market: pair.clone(),
+                message: ServerMessage::OrderUpdate {
+                    market: pair.to_string(),
+                    order_id: maker,
+                    status,
+                    fill_price: execution.price,
+                    fill_qty: execution.qty,
+                    remaining_qty,
+                },
+            }));
+        }
+
+        let old = l2_base(core, pair);
+        if let Ok(new) = core.engine.reader().snapshot(pair) {
+            if let Some(message) = ServerMessage::l2_update(pair, &old, &new) {
+                let _ = events.send(Arc::new(Event::Market {
+                    channel: Channel::L2,
+                    market: pair.clone(),
+                    message,
+                }));
+            }
+            core.l2.insert(pair.clone(), new);
+        }
+    }
+}
+
+/// Orders belong to the connection that placed them. Other connections
+/// learn order ids from the trades channel, so anything else is treated as
+/// an unknown order rather than revealing that it exists.
+fn check_owner(core: &Core, conn: ConnId, order_id: &OrderId) -> Result<(), String> {
+    if core.owners.get(order_id) == Some(&conn) {
+        Ok(())
+    } else {
+        Err(format!("unknown order {}", order_id))
+    }
+}
+
+/// Snapshot the next L2 diff of `pair` will be computed against.
+fn l2_base(core: &mut Core, pair: &TradingPair) -> Arc<BookSnapshot> {
+    core.l2
+        .entry(pair.clone())
+        .or_insert_with(|| core.engine.reader().snapshot(pair).unwrap_or_default())
+        .clone()
+}
+
+fn order_ack(result: &OrderResult, executions: &[TradeExecution]) -> AckResult {
+    AckResult::Order {
+        order: OrderReport::from(result),
+        trades: executions.iter().map(TradeReport::from).collect(),
+    }
+}
+
+fn filter_event(event: &Event, conn: ConnId, session: &Session) -> Option<ServerMessage> {
+    match event {
+        Event::Market {
+            channel,
+            market,
+            message,
+        } => session.wants(*channel, market).then(|| message.clone()),
+        Event::Own {
+            conn: owner,
+            market,
+            message,
+        } => (*owner == conn && session.wants(Channel::Orders, market)).then(|| message.clone()),
+    }
+}
+
+async fn send_all(
+    ws: &mut tokio_tungstenite::WebSocketStream<TcpStream>,
+    messages: Vec<ServerMessage>,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    for message in messages {
+        let text = serde_json::to_string(&message).expect("server messages serialize");
+        ws.feed(Message::Text(text.into())).await?;
+    }
+    ws.flush().await
+}
@@ -0,0 +1,322 @@
+//! JSON messages exchanged over the WebSocket gateway.
+//!
+//! Every client message carries an `op` and an optional `id`; the server
+//! answers each one with an `ack` or `reject` echoing that `id`. Market data
+//! and own-order updates arrive unsolicited once subscribed.
+
+use std::time::UNIX_EPOCH;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    BookSnapshot, OrderId, OrderResult, OrderStatus, OrderType, Price, Quantity, Side,
+    TradeExecution, TradingPair, orderbook::Timestamp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireSide {
+    Buy,
+    Sell,
+}
+
+impl From<Side> for WireSide {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Bid => WireSide::Buy,
+            Side::Ask => WireSide::Sell,
+        }
+    }
+}
+
+impl From<WireSide> for Side {
+    fn from(side: WireSide) -> Self {
+        match side {
+            WireSide::Buy => Side::Bid,
+            WireSide::Sell => Side::Ask,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireOrderType {
+    Market,
+    Limit,
+    Ioc,
+    Fok,
+}
+
+impl WireOrderType {
+    pub fn to_order_type(self, price: Option<Price>) -> Result<OrderType, String> {
+        match (self, price) {
+            (WireOrderType::Market, _) => Ok(OrderType::Market),
+            (WireOrderType::Limit, Some(p)) => Ok(OrderType::Limit(p)),
+            (WireOrderType::Ioc, Some(p)) => Ok(OrderType::IOC(p)),
+            (WireOrderType::Fok, Some(p)) => Ok(OrderType::FOK(p)),
+            (_, None) => Err(format!("{:?} orders need a price", self).to_lowercase()),
+        }
+    }
+}
+
+impl From<OrderType> for WireOrderType {
+    fn from(order_type: OrderType) -> Self {
+        match order_type {
+            OrderType::Market => WireOrderType::Market,
+            OrderType::Limit(_) | OrderType::SystemLevel(_) => WireOrderType::Limit,
+            OrderType::IOC(_) => WireOrderType::Ioc,
+            OrderType::FOK(_) => WireOrderType::Fok,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+}
+
+impl From<OrderStatus> for WireStatus {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::Open => WireStatus::Open,
+            OrderStatus::PartiallyFilled => WireStatus::PartiallyFilled,
+            OrderStatus::Filled => WireStatus::Filled,
+            OrderStatus::Cancelled => WireStatus::Cancelled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Trades,
+    L2,
+    Orders,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientRequest {
+    /// Correlation id echoed in the matching `ack`/`reject`.
+    #[serde(default)]
+    pub id: Option<Value>,
+    #[serde(flatten)]
+    pub op: Op,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op {
+    Place {
+        market: String,
+        side: WireSide,
+        #[serde(rename = "type")]
+        order_type: WireOrderType,
+        #[serde(default)]
+        price: Option<Price>,
+        qty: Quantity,
+    },
+    Cancel {
+        market: String,
+        order_id: OrderId,
+    },
+    Reduce {
+        market: String,
+        order_id: OrderId,
+        qty: Quantity,
+    },
+    Amend {
+        market: String,
+        order_id: OrderId,
+        price: Price,
+        qty: Quantity,
+    },
+    /// Also answers for filled and cancelled orders while the engine
+    /// retains them.
+    Order {
+        market: String,
+        order_id: OrderId,
+    },
+    Markets,
+    /// `depth` may exceed the snapshot depth; deeper levels are read from
+    /// the book itself.
+    Book {
+        market: String,
+        #[serde(default)]
+        depth: Option<usize>,
+    },
+    /// Without a `market`, subscribes to every market.
+    Subscribe {
+        channel: Channel,
+        #[serde(default)]
+        market: Option<String>,
+    },
+    Unsubscribe {
+        channel: Channel,
+        #[serde(default)]
+        market: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderReport {
+    pub order_id: OrderId,
+    pub side: WireSide,
+    #[serde(rename = "type")]
+    pub order_type: WireOrderType,
+    pub price: Option<Price>,
+    pub status: WireStatus,
+    pub qty: Quantity,
+    pub filled_qty: Quantity,
+    pub remaining_qty: Quantity,
+    pub avg_price: Option<Price>,
+}
+
+impl From<&OrderResult> for OrderReport {
+    fn from(result: &OrderResult) -> Self {
+        let filled_qty = result.filled_qty();
+        Self {
+            order_id: result.get_id(),
+            side: result.side().into(),
+            order_type: result.order_type().into(),
+            price: result.order_type().price(),
+            status: result.status.into(),
+            qty: result.initial_qty(),
+            filled_qty,
+            remaining_qty: result.remaining_qty,
+            avg_price: (filled_qty > Decimal::ZERO).then(|| result.avr_fill_price()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeReport {
+    pub price: Price,
+    pub qty: Quantity,
+    /// Side of the aggressor.
+    pub side: WireSide,
+    pub taker_order_id: OrderId,
+    pub maker_order_id: OrderId,
+    pub timestamp_ns: u128,
+}
+
+impl From<&TradeExecution> for TradeReport {
+    fn from(trade: &TradeExecution) -> Self {
+        Self {
+            price: trade.price,
+            qty: trade.qty,
+            side: trade.take_side.into(),
+            taker_order_id: trade.taker_order_id,
+            maker_order_id: trade.maker_order_id,
+            timestamp_ns: epoch_nanos(trade.timestamp),
+        }
+    }
+}
+
+fn epoch_nanos(ts: Timestamp) -> u128 {
+    ts.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AckResult {
+    Order {
+        order: OrderReport,
+        trades: Vec<TradeReport>,
+    },
+    Markets {
+        markets: Vec<String>,
+    },
+    Book {
+        market: String,
+        version: u64,
+        bids: Vec<(Price, Quantity)>,
+        asks: Vec<(Price, Quantity)>,
+    },
+    Subscription {
+        channel: Channel,
+        market: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Ack {
+        id: Option<Value>,
+        result: AckResult,
+    },
+    Reject {
+        id: Option<Value>,
+        reason: String,
+    },
+    Trade {
+        market: String,
+        #[serde(flatten)]
+        trade: TradeReport,
+    },
+    /// Full top-N book, sent once on `l2` subscription.
+    L2Snapshot {
+        market: String,
+        version: u64,
+        bids: Vec<(Price, Quantity)>,
+        asks: Vec<(Price, Quantity)>,
+    },
+    /// Changed levels since the previous version; a zero quantity removes
+    /// the level.
+    L2Update {
+        market: String,
+        version: u64,
+        bids: Vec<(Price, Quantity)>,
+        asks: Vec<(Price, Quantity)>,
+    },
+    /// A resting order of this connection was filled by someone else.
+    OrderUpdate {
+        market: String,
+        order_id: OrderId,
+        status: WireStatus,
+        fill_price: Price,
+        fill_qty: Quantity,
+        remaining_qty: Quantity,
+    },
+}
+
+impl ServerMessage {
+    pub fn l2_snapshot(market: &TradingPair, snapshot: &BookSnapshot) -> Self {
+        ServerMessage::L2Snapshot {
+            market: market.to_string(),
+            version: snapshot.version,
+            bids: snapshot.bids.clone(),
+            asks: snapshot.asks.clone(),
+        }
+    }
+
+    /// Levels of `new` that differ from `old`, or `None` if nothing changed.
+    pub fn l2_update(market: &TradingPair, old: &BookSnapshot, new: &BookSnapshot) -> Option<Self> {
+        let bids = level_diff(&old.bids, &new.bids);
+        let asks = level_diff(&old.asks, &new.asks);
+        if bids.is_empty() && asks.is_empty() {
+            return None;
+        }
+        Some(ServerMessage::L2Update {
+            market: market.to_string(),
+            version: new.version,
+            bids,
+            asks,
+        })
+    }
+}
+
+fn level_diff(old: &[(Price, Quantity)], new: &[(Price, Quantity)]) -> Vec<(Price, Quantity)> {
+    let removed = old
+        .iter()
+        .filter(|(p, _)| !new.iter().any(|(np, _)| np == p))
+        .map(|(p, _)| (*p, Quantity::ZERO));
+    let changed = new.iter().filter(|level| !old.contains(level)).copied();
+    removed.chain(changed).collect()
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::{dec, limit, pair};
+use orderbooklib::{
+    AccountId, BookChange, ItchBody, ItchEncoder, MatchingEngine, OrderBook, OrderRequest,
+    OrderStatus, OrderType, Side,
+};
+use rust_decimal::Decimal;
+
+fn auction(reference: Option<&str>, orders: &[(Side, u32, &str)]) -> OrderBook {
+    let mut book = OrderBook::default();
+    book.start_auction(reference.map(dec));
+    for &(side, qty, price) in orders {
+        book.add_order(limit(side, qty, price));
+    }
+    book
+}
+
+#[test]
+fn uncross_trades_the_most_volume_at_one_price() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.start_auction(&pair()).unwrap();
+    let changes = engine.subscribe_book_changes(&pair()).unwrap();
+    for (side, qty, price) in [
+        (Side::Bid, 10, "102"),
+        (Side::Bid, 5, "101"),
+        (Side::Bid, 5, "100"),
+        (Side::Ask, 8, "99"),
+        (Side::Ask, 6, "101"),
+        (Side::Ask, 10, "103"),
+    ] {
+        let (_, fills) = engine
+            .place_order(&pair(), limit(side, qty, price))
+            .unwrap();
+        assert!(fills.is_empty());
+    }
+    let (result, _) = engine
+        .place_order(&pair(), OrderRequest::new(Side::Bid, 1, OrderType::Market))
+        .unwrap();
+    assert_eq!(result.status, OrderStatus::Cancelled);
+
+    let indicative = engine.indicative(&pair()).unwrap().unwrap();
+    assert_eq!(indicative.price, Some(dec("101")));
+    assert_eq!(indicative.matched_qty, dec("14"));
+    assert_eq!(
+        (indicative.imbalance, indicative.imbalance_side),
+        (dec("1"), Some(Side::Bid))
+    );
+    let snapshot = engine.reader().snapshot(&pair()).unwrap();
+    assert_eq!(snapshot.indicative, Some(indicative));
+    assert_eq!(snapshot.best_prices(), (Some(dec("102")), Some(dec("99"))));
+
+    let executions = engine.uncross(&pair()).unwrap();
+    assert_eq!(
+        executions.iter().map(|e| e.qty).collect::<Vec<_>>(),
+        vec![dec("8"), dec("2"), dec("4")]
+    );
+    assert!(executions.iter().all(|e| e.price == dec("101")));
+    // The asks arrived last, so they take.
+    assert!(executions.iter().all(|e| e.take_side == Side::Ask));
+    assert_eq!(engine.indicative(&pair()), Ok(None));
+    assert_eq!(
+        engine.get_best_bid_ask(&pair()),
+        Ok((Some(dec("101")), Some(dec("103"))))
+    );
+    assert_eq!(engine.get_volume(&pair()), Ok(dec("16")));
+    assert_eq!(engine.get_order_book_state(&pair()).unwrap().bids.len(), 2);
+
+    // Both resting orders of each match are reported executed.
+    let mut encoder = ItchEncoder::new();
+    encoder.add_market(pair()).unwrap();
+    let uncrossed: Vec<_> = changes
+        .try_iter()
+        .filter(|c| matches!(c, BookChange::Uncrossed(_)))
+        .collect();
+    assert_eq!(uncrossed.len(), 3);
+    let messages = encoder.book_change(&pair(), &uncrossed[0]).unwrap();
+    let executed = messages
+        .iter()
+        .filter(|m| matches!(m.body, ItchBody::OrderExecuted { .. }))
+        .count();
+    assert_eq!(executed, 2);
+
+    // Continuous matching resumes.
+    let (_, fills) = engine
+        .place_order(&pair(), limit(Side::Ask, 1, "100"))
+        .unwrap();
+    assert_eq!(fills[0].price, dec("101"));
+}
+
+#[test]
+fn ties_go_to_surplus_pressure_then_reference() {
+    // Surplus demand at both 100 and 101: the higher price wins.
+    let book = auction(
+        None,
+        &[
+            (Side::Bid, 10, "101"),
+            (Side::Ask, 4, "99"),
+            (Side::Ask, 4, "100"),
+        ],
+    );
+    let indicative = book.indicative().unwrap();
+    assert_eq!(indicative.price, Some(dec("101")));
+    assert_eq!(
+        (indicative.matched_qty, indicative.imbalance),
+        (dec("8"), dec("2"))
+    );
+    // Surplus supply: the lower one.
+    let book = auction(
+        None,
+        &[
+            (Side::Ask, 10, "99"),
+            (Side::Bid, 4, "101"),
+            (Side::Bid, 4, "100"),
+        ],
+    );
+    let indicative = book.indicative().unwrap();
+    assert_eq!(indicative.price, Some(dec("99")));
+    assert_eq!(indicative.imbalance_side, Some(Side::Ask));
+
+    // Balanced: the reference decides, within the candidates.
+    let orders = [(Side::Bid, 10, "101"), (Side::Ask, 10, "99")];
+    let price = |reference| auction(reference, &orders).indicative().unwrap().price;
+    assert_eq!(price(None), Some(dec("100")));
+    assert_eq!(price(Some("100.5")), Some(dec("100.5")));
+    assert_eq!(price(Some("90")), Some(dec("99")));
+    assert_eq!(price(Some("120")), Some(dec("101")));
+
+    // The smallest surplus beats pressure.
+    let book = auction(
+        None,
+        &[
+            (Side::Bid, 10, "101"),
+            (Side::Bid, 10, "100"),
+            (Side::Ask, 5, "99"),
+            (Side::Ask, 5, "100"),
+        ],
+    );
+    assert_eq!(book.indicative().unwrap().price, Some(dec("101")));
+
+    let mut book = auction(None, &[(Side::Bid, 1, "99"), (Side::Ask, 1, "100")]);
+    let indicative = book.indicative().unwrap();
+    assert_eq!(
+        (indicative.price, indicative.matched_qty),
+        (None, Decimal::ZERO)
+    );
+    assert!(book.uncross().is_empty());
+    assert!(!book.in_auction());
+    assert_eq!(book.get_depth(), (1, 1));
+}
+
+#[test]
+fn uncross_settles_accounts_at_the_auction_price() {
+    const BUYER: AccountId = 1;
+    const SELLER: AccountId = 2;
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.enable_ledger().unwrap();
+    let ledger = engine.ledger_mut().unwrap();
+    ledger.deposit(BUYER, "USDT", dec("1050")).unwrap();
+    ledger.deposit(SELLER, "BTC", dec("10")).unwrap();
+
+    engine.start_auction(&pair()).unwrap();
+    engine
+        .place_order_for(BUYER, &pair(), limit(Side::Bid, 10, "105"))
+        .unwrap();
+    engine
+        .place_order_for(SELLER, &pair(), limit(Side::Ask, 10, "95"))
+        .unwrap();
+    assert_eq!(
+        engine.ledger().unwrap().balance(BUYER, "USDT").reserved,
+        dec("1050")
+    );
+
+    let executions = engine.uncross(&pair()).unwrap();
+    assert_eq!(executions[0].price, dec("100"));
+    let ledger = engine.ledger().unwrap();
+    assert_eq!(ledger.balance(BUYER, "USDT").free, dec("50"));
+    assert_eq!(ledger.balance(BUYER, "USDT").reserved, Decimal::ZERO);
+    assert_eq!(ledger.balance(BUYER, "BTC").free, dec("10"));
+    assert_eq!(ledger.balance(SELLER, "USDT").free, dec("1000"));
+    assert_eq!(ledger.open_orders(BUYER) + ledger.open_orders(SELLER), 0);
+    assert!(
+        engine
+            .get_order_book_state(&pair())
+            .unwrap()
+            .bids
+            .is_empty()
+    );
+}
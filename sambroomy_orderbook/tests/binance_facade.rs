@@ -0,0 +1,272 @@
+mod common;
+
+use binance_spot_connector_rust::{
+    http::{Credentials, error::ClientError},
+    hyper::{BinanceHttpClient, Error},
+    market,
+    trade::{
+        self,
+        order::{Side, TimeInForce},
+    },
+};
+use common::{dec, pair};
+use orderbooklib::{BinanceFacade, MatchingEngine, MaxOrderQty, RiskChain, TradingState};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+const API_KEY: &str = "test-key";
+const SECRET: &str = "test-secret";
+
+async fn start_facade() -> String {
+    start_facade_with(|_| {}).await.0
+}
+
+async fn start_facade_with(configure: impl FnOnce(&mut MatchingEngine)) -> (String, BinanceFacade) {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    configure(&mut engine);
+    let facade = BinanceFacade::new(engine);
+    facade.add_api_key(API_KEY, SECRET);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let server = facade.clone();
+    tokio::spawn(async move { server.serve(listener).await });
+    (base, facade)
+}
+
+fn credentials(secret: &str) -> Credentials {
+    Credentials::from_hmac(API_KEY, secret)
+}
+
+/// Sends a connector request and parses the JSON body. A macro because the
+/// client's connector type lives in crates this test cannot name.
+macro_rules! send {
+    ($client:expr, $request:expr) => {
+        async {
+            let body = $client.send($request).await?.into_body_str().await?;
+            Ok::<Value, Error>(serde_json::from_str(&body).unwrap())
+        }
+        .await
+    };
+}
+
+fn limit(side: Side, price: &str, qty: &str) -> trade::new_order::NewOrder {
+    trade::new_order("BTCUSDT", side, "LIMIT")
+        .time_in_force(TimeInForce::Gtc)
+        .price(dec(price))
+        .quantity(dec(qty))
+}
+
+/// Binance error code and message of a rejected request.
+fn error(result: Result<Value, Error>) -> (i16, String) {
+    match result {
+        Err(Error::Client(ClientError::Structured(err))) => (err.data.code, err.data.message),
+        other => panic!("expected a Binance error, got {:?}", other),
+    }
+}
+
+fn error_code(result: Result<Value, Error>) -> i16 {
+    error(result).0
+}
+
+#[tokio::test]
+async fn connector_trades_against_the_local_engine() {
+    let base = start_facade().await;
+    let client = BinanceHttpClient::with_url(&base).credentials(credentials(SECRET));
+
+    let info = send!(client, market::exchange_info()).unwrap();
+    assert_eq!(info["symbols"][0]["symbol"], "BTCUSDT");
+    assert_eq!(info["symbols"][0]["quoteAsset"], "USDT");
+
+    let maker = send!(client, limit(Side::Sell, "101", "4")).unwrap();
+    assert_eq!(maker["status"], "NEW");
+    let maker_id = maker["orderId"].as_u64().unwrap();
+    let resting = send!(client, limit(Side::Buy, "99", "2")).unwrap();
+    let resting_id = resting["orderId"].as_u64().unwrap();
+
+    let taker = send!(client, limit(Side::Buy, "101", "1")).unwrap();
+    assert_eq!(taker["status"], "FILLED");
+    assert_eq!(taker["executedQty"], "1.00000000");
+    assert_eq!(
+        taker["fills"],
+        json!([{
+            "price": "101.00000000",
+            "qty": "1.00000000",
+            "commission": "0.00000000",
+            "commissionAsset": "USDT",
+            "tradeId": 1,
+        }])
+    );
+
+    let order = send!(client, trade::get_order("BTCUSDT").order_id(maker_id)).unwrap();
+    assert_eq!(order["status"], "PARTIALLY_FILLED");
+    assert_eq!(order["executedQty"], "1.00000000");
+    assert_eq!(order["cummulativeQuoteQty"], "101.00000000");
+
+    let depth = send!(client, market::depth("BTCUSDT").limit(5)).unwrap();
+    assert_eq!(depth["asks"], json!([["101.00000000", "3.00000000"]]));
+    assert_eq!(depth["bids"], json!([["99.00000000", "2.00000000"]]));
+
+    let ticker = send!(client, market::book_ticker().symbol("BTCUSDT")).unwrap();
+    assert_eq!(ticker["bidPrice"], "99.00000000");
+    assert_eq!(ticker["askQty"], "3.00000000");
+
+    let open = send!(client, trade::open_orders().symbol("BTCUSDT")).unwrap();
+    let ids: Vec<_> = open
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["orderId"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![maker_id, resting_id]);
+
+    let cancelled = send!(client, trade::cancel_order("BTCUSDT").order_id(resting_id)).unwrap();
+    assert_eq!(cancelled["status"], "CANCELED");
+
+    let again = send!(client, trade::cancel_order("BTCUSDT").order_id(resting_id));
+    assert_eq!(error_code(again), -2011);
+
+    let maker_only = trade::new_order("BTCUSDT", Side::Buy, "LIMIT_MAKER")
+        .price(dec("101"))
+        .quantity(dec("1"));
+    let crossed = send!(client, maker_only);
+    assert_eq!(error_code(crossed), -2010);
+}
+
+#[tokio::test]
+async fn rejects_bad_signatures_and_requests() {
+    let base = start_facade().await;
+
+    let forged = BinanceHttpClient::with_url(&base).credentials(credentials("wrong-secret"));
+    let err = send!(forged, limit(Side::Buy, "100", "1"));
+    assert_eq!(error_code(err), -1022);
+
+    let client = BinanceHttpClient::with_url(&base).credentials(credentials(SECRET));
+    let unknown = trade::new_order("ETHUSDT", Side::Buy, "MARKET").quantity(dec("1"));
+    let err = send!(client, unknown);
+    assert_eq!(error_code(err), -1121);
+
+    let zero = send!(client, limit(Side::Buy, "100", "0"));
+    assert_eq!(error_code(zero), -1013);
+
+    let missing = send!(client, trade::get_order("BTCUSDT").order_id(42));
+    assert_eq!(error_code(missing), -2013);
+
+    let reqwest = reqwest::Client::new();
+    let response = reqwest
+        .get(format!("{base}/api/v3/openOrders?timestamp=1&signature=00"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], -2014);
+}
+
+#[tokio::test]
+async fn engine_rejections_are_new_order_rejected() {
+    let (base, facade) = start_facade_with(|engine| {
+        let chain = RiskChain::new().with(MaxOrderQty(dec("5")));
+        engine.set_risk_chain(&pair(), None, chain).unwrap();
+    })
+    .await;
+    let client = BinanceHttpClient::with_url(&base).credentials(credentials(SECRET));
+
+    let err = send!(client, limit(Side::Buy, "100", "6"));
+    assert_eq!(
+        error(err),
+        (
+            -2010,
+            "max order quantity rejected the order: quantity 6 exceeds 5".to_string()
+        )
+    );
+
+    let resting = send!(client, limit(Side::Buy, "100", "1")).unwrap();
+    let resting_id = resting["orderId"].as_u64().unwrap();
+    facade
+        .with_engine(|engine| engine.set_market_state(&pair(), TradingState::Halted))
+        .unwrap();
+    let halted = (-2011, "Market for BTC_USDT is halted".to_string());
+    let err = send!(client, trade::cancel_order("BTCUSDT").order_id(resting_id));
+    assert_eq!(error(err), halted);
+    let err = send!(client, limit(Side::Buy, "100", "1"));
+    assert_eq!(error(err), (-2010, halted.1));
+}
+
+#[tokio::test]
+async fn api_keys_only_see_their_own_orders() {
+    let (base, facade) = start_facade_with(|_| {}).await;
+    facade.add_api_key("other-key", "other-secret");
+    let owner = BinanceHttpClient::with_url(&base).credentials(credentials(SECRET));
+    let other = BinanceHttpClient::with_url(&base)
+        .credentials(Credentials::from_hmac("other-key", "other-secret"));
+
+    let placed = send!(
+        owner,
+        limit(Side::Sell, "101", "1").new_client_order_id("mine")
+    )
+    .unwrap();
+    let order_id = placed["orderId"].as_u64().unwrap();
+
+    let open = send!(other, trade::open_orders()).unwrap();
+    assert_eq!(open, json!([]));
+    let err = send!(other, trade::get_order("BTCUSDT").order_id(order_id));
+    assert_eq!(error_code(err), -2013);
+    let err = send!(
+        other,
+        trade::get_order("BTCUSDT").orig_client_order_id("mine")
+    );
+    assert_eq!(error_code(err), -2013);
+    let err = send!(other, trade::cancel_order("BTCUSDT").order_id(order_id));
+    assert_eq!(error_code(err), -2011);
+    send!(
+        other,
+        limit(Side::Sell, "102", "1").new_client_order_id("mine")
+    )
+    .unwrap();
+
+    let order = send!(owner, trade::get_order("BTCUSDT").order_id(order_id)).unwrap();
+    assert_eq!(order["status"], "NEW");
+    let open = send!(owner, trade::open_orders()).unwrap();
+    assert_eq!(open.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn api_keys_trade_their_ledger_accounts() {
+    let (base, facade) = start_facade_with(|engine| {
+        engine.enable_ledger().unwrap();
+        let ledger = engine.ledger_mut().unwrap();
+        ledger.deposit(1, "USDT", dec("1000")).unwrap();
+        ledger.deposit(2, "BTC", dec("3")).unwrap();
+    })
+    .await;
+    facade.add_api_key("seller-key", "seller-secret");
+    let buyer = BinanceHttpClient::with_url(&base).credentials(credentials(SECRET));
+    let seller = BinanceHttpClient::with_url(&base)
+        .credentials(Credentials::from_hmac("seller-key", "seller-secret"));
+
+    let err = send!(buyer, limit(Side::Buy, "100", "1"));
+    assert_eq!(
+        error(err),
+        (
+            -2010,
+            "orders need an account while the ledger is enabled".to_string()
+        )
+    );
+
+    facade.set_account(API_KEY, 1);
+    facade.set_account("seller-key", 2);
+    send!(seller, limit(Side::Sell, "100", "3")).unwrap();
+    let taker = send!(buyer, limit(Side::Buy, "100", "2")).unwrap();
+    assert_eq!(taker["status"], "FILLED");
+    let err = send!(buyer, limit(Side::Buy, "100", "9"));
+    assert_eq!(error_code(err), -2010);
+
+    facade.with_engine(|engine| {
+        let ledger = engine.ledger().unwrap();
+        assert_eq!(ledger.balance(1, "BTC").free, dec("2"));
+        assert_eq!(ledger.balance(1, "USDT").free, dec("800"));
+        assert_eq!(ledger.balance(2, "USDT").free, dec("200"));
+        assert_eq!(ledger.balance(2, "BTC").reserved, dec("1"));
+    });
+}
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use orderbooklib::{OrderBook, OrderId, OrderRequest, OrderType, Price, Side, TradeOrder};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Add(Side, u32, u8, u32),
+    System(Side, u32, u32),
+    Cancel(usize, u32),
+    Delete(usize),
+    Clear,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let side = prop_oneof![Just(Side::Bid), Just(Side::Ask)];
+    prop_oneof![
+        8 => (side.clone(), 1..20u32, 0..5u8, 95..106u32).prop_map(|(s, q, k, p)| Op::Add(s, q, k, p)),
+        2 => (side, 1..20u32, 95..106u32).prop_map(|(s, q, p)| Op::System(s, q, p)),
+        2 => (any::<usize>(), 1..10u32).prop_map(|(i, q)| Op::Cancel(i, q)),
+        2 => any::<usize>().prop_map(Op::Delete),
+        1 => Just(Op::Clear),
+    ]
+}
+
+fn order_type(kind: u8, price: u32) -> OrderType {
+    match kind {
+        0 => OrderType::Market,
+        1 => OrderType::limit(price),
+        2 => OrderType::ioc(price),
+        3 => OrderType::fok(price),
+        _ => OrderType::system_level(price),
+    }
+}
+
+type Level = VecDeque<(OrderId, Decimal)>;
+
+/// A deliberately naive book: price levels in `BTreeMap`s, matched best
+/// price first and oldest order first.
+#[derive(Debug, Default)]
+struct Model {
+    bids: BTreeMap<Price, Level>,
+    asks: BTreeMap<Price, Level>,
+}
+
+impl Model {
+    fn side(&mut self, side: Side) -> &mut BTreeMap<Price, Level> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+
+    /// Matches an incoming order and rests what is left, returning the
+    /// fills as (maker, price, qty).
+    fn add(&mut self, request: &OrderRequest) -> Vec<(OrderId, Price, Decimal)> {
+        let limit = request.price();
+        let crosses = |price: Price| match (request.side, limit) {
+            (_, None) => true,
+            (Side::Bid, Some(limit)) => price <= limit,
+            (Side::Ask, Some(limit)) => price >= limit,
+        };
+        let opposite = self.side(request.side.opposite());
+        if let OrderType::FOK(_) = request.order_type {
+            let available: Decimal = opposite
+                .iter()
+                .filter(|(p, _)| crosses(**p))
+                .flat_map(|(_, level)| level.iter().map(|(_, q)| *q))
+                .sum();
+            if available < request.qty {
+                return Vec::new();
+            }
+        }
+
+        let mut remaining = request.qty;
+        let mut fills = Vec::new();
+        while remaining > Decimal::ZERO {
+            let best = match request.side {
+                Side::Bid => opposite.first_entry(),
+                Side::Ask => opposite.last_entry(),
+            };
+            let Some(mut level) = best.filter(|l| crosses(*l.key())) else {
+                break;
+            };
+            let price = *level.key();
+            let (maker, qty) = level.get_mut().front_mut().unwrap();
+            let fill = remaining.min(*qty);
+            *qty -= fill;
+            remaining -= fill;
+            fills.push((*maker, price, fill));
+            if qty.is_zero() {
+                level.get_mut().pop_front();
+            }
+            if level.get().is_empty() {
+                level.remove();
+            }
+        }
+
+        if remaining > Decimal::ZERO {
+            match request.order_type {
+                OrderType::Limit(price) => self.rest(request.side, price, request.id(), remaining),
+                OrderType::SystemLevel(price) => {
+                    self.merge(request.side, price, request.id(), remaining)
+                }
+                OrderType::Market | OrderType::IOC(_) | OrderType::FOK(_) => {}
+            }
+        }
+        fills
+    }
+
+    fn rest(&mut self, side: Side, price: Price, id: OrderId, qty: Decimal) {
+        self.side(side)
+            .entry(price)
+            .or_default()
+            .push_back((id, qty));
+    }
+
+    /// System orders share one id per price and top up in place.
+    fn merge(&mut self, side: Side, price: Price, id: OrderId, qty: Decimal) {
+        let level = self.side(side).entry(price).or_default();
+        match level.iter_mut().find(|(o, _)| *o == id) {
+            Some((_, resting)) => *resting += qty,
+            None => level.push_back((id, qty)),
+        }
+    }
+
+    /// Takes `qty` off an order, removing it once nothing is left. Returns
+    /// what remains, or `None` for an unknown order.
+    fn reduce(&mut self, id: OrderId, qty: Decimal) -> Option<Decimal> {
+        for side in [Side::Bid, Side::Ask] {
+            let levels = self.side(side);
+            let Some((&price, level)) = levels
+                .iter_mut()
+                .find(|(_, level)| level.iter().any(|(o, _)| *o == id))
+            else {
+                continue;
+            };
+            let i = level.iter().position(|(o, _)| *o == id).unwrap();
+            let left = level[i].1 - qty.min(level[i].1);
+            level[i].1 = left;
+            if left.is_zero() {
+                level.remove(i);
+                if level.is_empty() {
+                    levels.remove(&price);
+                }
+            }
+            return Some(left);
+        }
+        None
+    }
+
+    fn volume(&self) -> Decimal {
+        self.bids
+            .values()
+            .chain(self.asks.values())
+            .flatten()
+            .map(|(_, q)| *q)
+            .sum()
+    }
+}
+
+/// The book's levels for `side`, best first, with each order's id and
+/// remaining quantity in queue order.
+fn levels(book: &OrderBook, side: Side) -> Vec<(Price, Vec<(OrderId, Decimal)>)> {
+    let half = match side {
+        Side::Bid => &book.bids,
+        Side::Ask => &book.asks,
+    };
+    half.iter_prices()
+        .map(|price| {
+            let orders = book.get_orders_at_price(side, price).unwrap_or_default();
+            (
+                price,
+                orders.iter().map(|o| (o.id, o.remaining_qty)).collect(),
+            )
+        })
+        .collect()
+}
+
+fn model_levels(model: &Model, side: Side) -> Vec<(Price, Vec<(OrderId, Decimal)>)> {
+    let levels = match side {
+        Side::Bid => &model.bids,
+        Side::Ask => &model.asks,
+    };
+    let collected = levels
+        .iter()
+        .map(|(price, level)| (*price, level.iter().copied().collect()));
+    match side {
+        Side::Bid => collected.rev().collect(),
+        Side::Ask => collected.collect(),
+    }
+}
+
+fn check(book: &OrderBook, model: &Model) -> Result<(), TestCaseError> {
+    book.check_invariants().map_err(TestCaseError::fail)?;
+    prop_assert_eq!(levels(book, Side::Bid), model_levels(model, Side::Bid));
+    prop_assert_eq!(levels(book, Side::Ask), model_levels(model, Side::Ask));
+    prop_assert_eq!(book.get_total_volume(), model.volume());
+    Ok(())
+}
+
+fn run(ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let mut book = OrderBook::default();
+    let mut model = Model::default();
+    let mut ids: Vec<OrderId> = Vec::new();
+
+    for op in ops {
+        let pick = |i: usize| (!ids.is_empty()).then(|| ids[i % ids.len()]);
+        match op {
+            Op::Add(side, qty, kind, price) => {
+                let request = OrderRequest::new(side, qty, order_type(kind, price));
+                let before = book.get_total_volume();
+                let expected = model.add(&request);
+                let (result, executions) = book.add_order(request);
+
+                let fills: Vec<_> = executions
+                    .iter()
+                    .map(|e| (e.maker_order_id, e.price, e.qty))
+                    .collect();
+                prop_assert_eq!(fills, expected);
+                for execution in &executions {
+                    prop_assert_eq!(execution.taker_order_id, request.id());
+                    prop_assert_eq!(execution.take_side, side);
+                }
+                let filled: Decimal = executions.iter().map(|e| e.qty).sum();
+                prop_assert_eq!(result.filled_qty(), filled);
+                prop_assert_eq!(result.initial_qty(), filled + result.remaining_qty);
+                let rested = match request.order_type {
+                    OrderType::Limit(_) | OrderType::SystemLevel(_) => result.remaining_qty,
+                    _ => Decimal::ZERO,
+                };
+                prop_assert_eq!(book.get_total_volume(), before - filled + rested);
+                ids.push(request.id());
+            }
+            Op::System(side, qty, price) => {
+                // Inserted without matching, so only at prices that leave
+                // the book uncrossed.
+                let price = Decimal::from(price);
+                let crosses = match side {
+                    Side::Bid => book.best_ask().is_some_and(|ask| price >= ask),
+                    Side::Ask => book.best_bid().is_some_and(|bid| price <= bid),
+                };
+                if crosses {
+                    continue;
+                }
+                let request = OrderRequest::new(side, qty, OrderType::SystemLevel(price));
+                book.add_system_order(side, price, TradeOrder::from(request));
+                model.merge(side, price, request.id(), request.qty);
+                ids.push(request.id());
+            }
+            Op::Cancel(i, qty) => {
+                let Some(id) = pick(i) else { continue };
+                let expected = model.reduce(id, qty.into());
+                let result = book.cancel_order(id, qty);
+                prop_assert_eq!(result.map(|r| r.remaining_qty), expected);
+            }
+            Op::Delete(i) => {
+                let Some(id) = pick(i) else { continue };
+                let expected = model.reduce(id, Decimal::MAX);
+                let result = book.delete_order(id);
+                prop_assert_eq!(result.is_some(), expected.is_some());
+            }
+            Op::Clear => {
+                book.clear();
+                model = Model::default();
+            }
+        }
+        check(&book, &model)?;
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    /// Against the naive model every step gives the same fills in the same
+    /// order and leaves the same queues, and the book stays consistent.
+    #[test]
+    fn book_matches_model(ops in proptest::collection::vec(op(), 1..120)) {
+        run(ops)?;
+    }
+}
+
+#[test]
+fn system_orders_keep_their_place_when_topped_up() {
+    let mut book = OrderBook::default();
+    let first = OrderRequest::new(Side::Bid, 5, OrderType::system_level(100));
+    book.add_order(first);
+    let limit = OrderRequest::new(Side::Bid, 3, OrderType::limit(100));
+    book.add_order(limit);
+    book.add_order(OrderRequest::new(
+        Side::Bid,
+        4,
+        OrderType::system_level(100),
+    ));
+    book.check_invariants().unwrap();
+
+    let (_, executions) = book.add_order(OrderRequest::new(Side::Ask, 10, OrderType::Market));
+    let fills: Vec<_> = executions
+        .iter()
+        .map(|e| (e.maker_order_id, e.qty))
+        .collect();
+    assert_eq!(
+        fills,
+        vec![
+            (first.id(), Decimal::from(9)),
+            (limit.id(), Decimal::from(1))
+        ]
+    );
+    book.check_invariants().unwrap();
+}
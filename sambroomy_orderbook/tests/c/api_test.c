@@ -0,0 +1,125 @@
+/* Drives the C ABI the way an embedding gateway would; run by tests/c_api.rs. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "orderbook.h"
+
+#define CHECK(cond)                                                          \
+  do {                                                                       \
+    if (!(cond)) {                                                           \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+      return 1;                                                              \
+    }                                                                        \
+  } while (0)
+
+#define MARKET "BTC_USDT"
+
+typedef struct Fills {
+  int count;
+  int64_t qty;
+  ObOrderId last_maker;
+} Fills;
+
+static void on_execution(const char *market, const ObExecution *execution, void *user_data) {
+  Fills *fills = (Fills *)user_data;
+  if (strcmp(market, MARKET) != 0 || execution->qty.scale != 0) {
+    fills->count = -1000;
+    return;
+  }
+  fills->count += 1;
+  fills->qty += execution->qty.mantissa;
+  fills->last_maker = execution->maker_order_id;
+}
+
+static ObDecimal dec(int64_t mantissa, uint32_t scale) {
+  ObDecimal d = {mantissa, scale};
+  return d;
+}
+
+int main(void) {
+  ObEngine *engine = ob_engine_new();
+  CHECK(engine != NULL);
+  CHECK(ob_add_market(engine, MARKET) == OB_STATUS_OK);
+  CHECK(strcmp(ob_last_error(engine), "") == 0);
+  CHECK(ob_add_market(engine, "BTC/USDT") == OB_STATUS_MARKET_EXISTS);
+  CHECK(strstr(ob_last_error(engine), "already exists") != NULL);
+  CHECK(ob_add_market(engine, "BTCUSDT") == OB_STATUS_INVALID_ARGUMENT);
+  CHECK(ob_add_market(engine, NULL) == OB_STATUS_NULL_ARGUMENT);
+  CHECK(ob_add_market(NULL, MARKET) == OB_STATUS_NULL_ARGUMENT);
+
+  Fills fills = {0};
+  CHECK(ob_set_execution_callback(engine, on_execution, &fills) == OB_STATUS_OK);
+
+  /* Two asks at 100.5 and 101, one bid at 99.25. */
+  ObOrderResult first_ask, second_ask, bid;
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_ASK, OB_ORDER_TYPE_LIMIT, dec(1005, 1), dec(3, 0),
+                       &first_ask) == OB_STATUS_OK);
+  CHECK(first_ask.status == OB_ORDER_STATUS_OPEN);
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_ASK, OB_ORDER_TYPE_LIMIT, dec(101, 0), dec(4, 0),
+                       &second_ask) == OB_STATUS_OK);
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_BID, OB_ORDER_TYPE_LIMIT, dec(9925, 2), dec(2, 0),
+                       &bid) == OB_STATUS_OK);
+  CHECK(fills.count == 0);
+
+  ObBbo bbo;
+  CHECK(ob_get_bbo(engine, MARKET, &bbo) == OB_STATUS_OK);
+  CHECK(bbo.has_bid && bbo.has_ask);
+  CHECK(bbo.bid.price.mantissa == 9925 && bbo.bid.price.scale == 2);
+  CHECK(bbo.ask.price.mantissa == 1005 && bbo.ask.price.scale == 1);
+  CHECK(bbo.ask.qty.mantissa == 3);
+
+  ObLevel levels[8];
+  size_t count = 0;
+  CHECK(ob_get_depth(engine, MARKET, OB_SIDE_ASK, levels, 8, &count) == OB_STATUS_OK);
+  CHECK(count == 2);
+  CHECK(levels[1].price.mantissa == 101 && levels[1].qty.mantissa == 4);
+  CHECK(ob_get_depth(engine, MARKET, OB_SIDE_ASK, levels, 1, &count) == OB_STATUS_OK);
+  CHECK(count == 1);
+  CHECK(ob_get_depth(engine, MARKET, OB_SIDE_ASK, NULL, 4, &count) == OB_STATUS_NULL_ARGUMENT);
+
+  /* A market buy of 5 sweeps the first ask and part of the second. */
+  ObOrderResult taken;
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_BID, OB_ORDER_TYPE_MARKET, dec(0, 0), dec(5, 0),
+                       &taken) == OB_STATUS_OK);
+  CHECK(taken.status == OB_ORDER_STATUS_FILLED);
+  CHECK(taken.filled_qty.mantissa == 5 && taken.remaining_qty.mantissa == 0);
+  CHECK(fills.count == 2 && fills.qty == 5);
+  CHECK(memcmp(&fills.last_maker, &second_ask.id, sizeof(ObOrderId)) == 0);
+
+  ObOrderResult cancelled;
+  CHECK(ob_cancel_order(engine, MARKET, second_ask.id, &cancelled) == OB_STATUS_OK);
+  CHECK(cancelled.status == OB_ORDER_STATUS_CANCELLED);
+  CHECK(cancelled.remaining_qty.mantissa == 2);
+  CHECK(ob_cancel_order(engine, MARKET, second_ask.id, NULL) == OB_STATUS_NOT_FOUND);
+  CHECK(ob_get_bbo(engine, MARKET, &bbo) == OB_STATUS_OK);
+  CHECK(!bbo.has_ask && bbo.ask.price.mantissa == 0);
+
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_BID, OB_ORDER_TYPE_LIMIT, dec(-1, 0), dec(1, 0),
+                       NULL) == OB_STATUS_INVALID_ARGUMENT);
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_BID, OB_ORDER_TYPE_LIMIT, dec(1, 29), dec(1, 0),
+                       NULL) == OB_STATUS_INVALID_ARGUMENT);
+  CHECK(ob_place_order(engine, MARKET, 2, OB_ORDER_TYPE_LIMIT, dec(1, 0), dec(1, 0), NULL) ==
+        OB_STATUS_INVALID_ARGUMENT);
+  CHECK(strstr(ob_last_error(engine), "invalid side 2") != NULL);
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_BID, 4, dec(1, 0), dec(1, 0), NULL) ==
+        OB_STATUS_INVALID_ARGUMENT);
+  CHECK(strstr(ob_last_error(engine), "invalid order type 4") != NULL);
+  CHECK(ob_get_depth(engine, MARKET, 0xffffffffu, levels, 8, &count) ==
+        OB_STATUS_INVALID_ARGUMENT);
+  CHECK(ob_place_order(engine, "ETH_USDT", OB_SIDE_BID, OB_ORDER_TYPE_LIMIT, dec(1, 0),
+                       dec(1, 0), NULL) == OB_STATUS_UNKNOWN_MARKET);
+  CHECK(strstr(ob_last_error(engine), "ETH_USDT") != NULL);
+
+  CHECK(ob_set_execution_callback(engine, NULL, NULL) == OB_STATUS_OK);
+  CHECK(ob_place_order(engine, MARKET, OB_SIDE_ASK, OB_ORDER_TYPE_IOC, dec(99, 0), dec(1, 0),
+                       &taken) == OB_STATUS_OK);
+  CHECK(taken.status == OB_ORDER_STATUS_FILLED && fills.count == 2);
+
+  CHECK(ob_remove_market(engine, MARKET) == OB_STATUS_OK);
+  CHECK(ob_get_bbo(engine, MARKET, &bbo) == OB_STATUS_UNKNOWN_MARKET);
+  ob_engine_free(engine);
+  ob_engine_free(NULL);
+  puts("ok");
+  return 0;
+}
@@ -0,0 +1,42 @@
+//! Compiles `tests/c/api_test.c` against the cdylib and the checked-in
+//! header, then runs it.
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_program_drives_the_engine() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // target/<profile>/deps/c_api-<hash> -> target/<profile>
+    let profile_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .to_path_buf();
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let binary = profile_dir.join("c_api_test");
+
+    let compiled = Command::new(&compiler)
+        .arg(manifest.join("tests/c/api_test.c"))
+        .args(["-std=c11", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(manifest.join("include"))
+        .arg("-L")
+        .arg(&profile_dir)
+        .arg(format!("-Wl,-rpath,{}", profile_dir.display()))
+        .args(["-lorderbooklib", "-o"])
+        .arg(&binary)
+        .status();
+    let Ok(compiled) = compiled else {
+        eprintln!("skipping: no C compiler found as '{compiler}'");
+        return;
+    };
+    assert!(compiled.success(), "compiling the C test failed");
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
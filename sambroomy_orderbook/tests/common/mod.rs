@@ -0,0 +1,17 @@
+//! Helpers shared by the integration tests; each test crate uses a subset.
+#![allow(dead_code)]
+
+use orderbooklib::{OrderRequest, OrderType, Quantity, Side, TradingPair};
+use rust_decimal::Decimal;
+
+pub fn pair() -> TradingPair {
+    TradingPair::new("BTC".to_string(), "USDT".to_string())
+}
+
+pub fn dec(s: &str) -> Decimal {
+    s.parse().unwrap()
+}
+
+pub fn limit(side: Side, qty: impl Into<Quantity>, price: &str) -> OrderRequest {
+    OrderRequest::new(side, qty, OrderType::Limit(dec(price)))
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{dec, limit, pair};
+use orderbooklib::{
+    AccountId, FEE_ACCOUNT, Fee, FeeSchedule, FeeTier, Liquidity, MatchingEngine, Side, TradingPair,
+};
+use rust_decimal::Decimal;
+
+const MAKER: AccountId = 1;
+const TAKER: AccountId = 2;
+
+fn fee(amount: &str, asset: &str, liquidity: Liquidity) -> Option<Fee> {
+    Some(Fee {
+        amount: dec(amount),
+        asset: asset.to_string(),
+        liquidity,
+    })
+}
+
+fn tier(min_volume: &str, maker_rate: &str, taker_rate: &str) -> FeeTier {
+    FeeTier {
+        min_volume: dec(min_volume),
+        maker_rate: dec(maker_rate),
+        taker_rate: dec(taker_rate),
+    }
+}
+
+fn funded_engine() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.enable_ledger().unwrap();
+    let ledger = engine.ledger_mut().unwrap();
+    ledger.deposit(MAKER, "BTC", dec("100")).unwrap();
+    ledger.deposit(TAKER, "USDT", dec("100000")).unwrap();
+    engine
+}
+
+#[test]
+fn schedules_are_validated() {
+    assert!(FeeSchedule::tiered(Duration::ZERO, vec![]).is_err());
+    assert!(FeeSchedule::tiered(Duration::ZERO, vec![tier("1", "0", "0")]).is_err());
+    let unordered = vec![tier("0", "0", "0.002"), tier("0", "0", "0.001")];
+    assert!(FeeSchedule::tiered(Duration::ZERO, unordered).is_err());
+    assert!(FeeSchedule::flat(dec("0"), dec("-0.001")).is_err());
+    assert!(FeeSchedule::flat(dec("-1"), dec("0.001")).is_err());
+
+    let schedule = FeeSchedule::tiered(
+        Duration::from_secs(60),
+        vec![
+            tier("0", "0.001", "0.002"),
+            tier("1000", "-0.0001", "0.001"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(schedule.tier(dec("999.99")).taker_rate, dec("0.002"));
+    assert_eq!(schedule.tier(dec("1000")).maker_rate, dec("-0.0001"));
+}
+
+#[test]
+fn fees_are_recorded_without_accounts() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    let (_, fills) = engine
+        .place_order(&pair(), limit(Side::Bid, 1, "100"))
+        .unwrap();
+    assert!(fills.is_empty());
+    let (_, fills) = engine
+        .place_order(&pair(), limit(Side::Ask, 1, "100"))
+        .unwrap();
+    assert_eq!((&fills[0].maker_fee, &fills[0].taker_fee), (&None, &None));
+
+    let schedule = FeeSchedule::flat(dec("-0.0002"), dec("0.001")).unwrap();
+    assert!(
+        engine
+            .set_fee_schedule(&TradingPair::new("X".into(), "Y".into()), schedule.clone())
+            .is_err()
+    );
+    engine.set_fee_schedule(&pair(), schedule).unwrap();
+    engine
+        .place_order(&pair(), limit(Side::Bid, 2, "100"))
+        .unwrap();
+    let (_, fills) = engine
+        .place_order(&pair(), limit(Side::Ask, 2, "100"))
+        .unwrap();
+    // The maker bought base and earns a rebate on it; the taker sold for
+    // quote and pays on the proceeds.
+    assert_eq!(fills[0].maker_fee, fee("-0.0004", "BTC", Liquidity::Maker));
+    assert_eq!(fills[0].taker_fee, fee("0.2", "USDT", Liquidity::Taker));
+}
+
+#[test]
+fn ledger_charges_fees_to_the_fee_account() {
+    let mut engine = funded_engine();
+    let schedule = FeeSchedule::flat(dec("-0.0001"), dec("0.001")).unwrap();
+    engine.set_fee_schedule(&pair(), schedule).unwrap();
+
+    engine
+        .place_order_for(MAKER, &pair(), limit(Side::Ask, 10, "100"))
+        .unwrap();
+    let (_, fills) = engine
+        .place_order_for(TAKER, &pair(), limit(Side::Bid, 10, "100"))
+        .unwrap();
+    assert_eq!(fills[0].taker_fee, fee("0.01", "BTC", Liquidity::Taker));
+    assert_eq!(fills[0].maker_fee, fee("-0.1", "USDT", Liquidity::Maker));
+
+    let ledger = engine.ledger().unwrap();
+    assert_eq!(ledger.balance(TAKER, "BTC").free, dec("9.99"));
+    assert_eq!(ledger.balance(MAKER, "USDT").free, dec("1000.1"));
+    assert_eq!(ledger.balance(FEE_ACCOUNT, "BTC").free, dec("0.01"));
+    assert_eq!(ledger.balance(FEE_ACCOUNT, "USDT").free, dec("-0.1"));
+    assert_eq!(ledger.total("BTC"), dec("100"));
+    assert_eq!(ledger.total("USDT"), dec("100000"));
+}
+
+#[test]
+fn volume_tiers_roll_over_a_window() {
+    let mut engine = funded_engine();
+    let window = Duration::from_millis(300);
+    let schedule = FeeSchedule::tiered(
+        window,
+        vec![tier("0", "0.001", "0.002"), tier("1000", "0", "0.001")],
+    )
+    .unwrap();
+    engine.set_fee_schedule(&pair(), schedule).unwrap();
+
+    let trade = |engine: &mut MatchingEngine| {
+        engine
+            .place_order_for(MAKER, &pair(), limit(Side::Ask, 6, "100"))
+            .unwrap();
+        let (_, fills) = engine
+            .place_order_for(TAKER, &pair(), limit(Side::Bid, 6, "100"))
+            .unwrap();
+        let taker_fee = fills[0].taker_fee.as_ref().unwrap().amount;
+        taker_fee / dec("6")
+    };
+
+    // 600 traded, then 1200: the third fill reaches the second tier.
+    assert_eq!(trade(&mut engine), dec("0.002"));
+    assert_eq!(trade(&mut engine), dec("0.002"));
+    assert_eq!(trade(&mut engine), dec("0.001"));
+    std::thread::sleep(window);
+    assert_eq!(trade(&mut engine), dec("0.002"));
+
+    let free = FeeSchedule::flat(Decimal::ZERO, Decimal::ZERO).unwrap();
+    engine.set_account_fee_schedule(TAKER, free);
+    assert_eq!(trade(&mut engine), Decimal::ZERO);
+}
@@ -0,0 +1,358 @@
+mod common;
+
+use std::time::Duration;
+
+use common::pair;
+use orderbooklib::{
+    FixGateway, MatchingEngine, MaxOrderQty, RiskChain, TradingState,
+    fix_message::{FixMessage, msg_type, tag},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+const ACCEPTOR: &str = "ENGINE";
+
+async fn start_gateway() -> String {
+    start_gateway_with(|_| {}).await.0
+}
+
+async fn start_gateway_with(configure: impl FnOnce(&mut MatchingEngine)) -> (String, FixGateway) {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    configure(&mut engine);
+    let gateway = FixGateway::new(engine, ACCEPTOR);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let server = gateway.clone();
+    tokio::spawn(async move { server.serve(listener).await });
+    (addr, gateway)
+}
+
+/// Minimal FIX initiator: stamps headers and decodes whatever comes back.
+struct Initiator {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    comp_id: String,
+    next_seq: u64,
+}
+
+impl Initiator {
+    async fn connect(addr: &str, comp_id: &str) -> Self {
+        Self {
+            stream: TcpStream::connect(addr).await.unwrap(),
+            buf: Vec::new(),
+            comp_id: comp_id.to_string(),
+            next_seq: 1,
+        }
+    }
+
+    async fn send(&mut self, msg: FixMessage) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.send_as(seq, msg).await;
+    }
+
+    async fn send_as(&mut self, seq: u64, msg: FixMessage) {
+        let mut out = FixMessage::new(msg.msg_type())
+            .with(tag::SENDER_COMP_ID, &self.comp_id)
+            .with(tag::TARGET_COMP_ID, ACCEPTOR)
+            .with(tag::MSG_SEQ_NUM, seq)
+            .with(tag::SENDING_TIME, "20250101-00:00:00.000");
+        for (t, v) in msg.fields().iter().skip(1) {
+            out.push(*t, v);
+        }
+        self.stream.write_all(&out.encode()).await.unwrap();
+    }
+
+    /// Next message, or `None` once the acceptor closed the connection.
+    async fn recv_any(&mut self) -> Option<FixMessage> {
+        loop {
+            if let Some((msg, used)) = FixMessage::decode(&self.buf).unwrap() {
+                self.buf.drain(..used);
+                return Some(msg);
+            }
+            let read = timeout(Duration::from_secs(5), self.stream.read_buf(&mut self.buf))
+                .await
+                .expect("timed out waiting for the acceptor")
+                .unwrap();
+            if read == 0 {
+                return None;
+            }
+        }
+    }
+
+    /// Next message that is not a plain Heartbeat.
+    async fn recv(&mut self) -> FixMessage {
+        loop {
+            let msg = self.recv_any().await.expect("connection closed");
+            if msg.msg_type() != msg_type::HEARTBEAT || msg.get(tag::TEST_REQ_ID).is_some() {
+                return msg;
+            }
+        }
+    }
+
+    async fn logon(&mut self, reset: bool, heartbeat: u64) -> FixMessage {
+        let mut logon = FixMessage::new(msg_type::LOGON)
+            .with(tag::ENCRYPT_METHOD, 0)
+            .with(tag::HEART_BT_INT, heartbeat);
+        if reset {
+            logon.push(tag::RESET_SEQ_NUM_FLAG, "Y");
+        }
+        self.send(logon).await;
+        let reply = self.recv().await;
+        assert_eq!(reply.msg_type(), msg_type::LOGON);
+        reply
+    }
+}
+
+fn limit(cl_ord_id: &str, side: &str, price: &str, qty: &str) -> FixMessage {
+    FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+        .with(tag::CL_ORD_ID, cl_ord_id)
+        .with(tag::SYMBOL, "BTC/USDT")
+        .with(tag::SIDE, side)
+        .with(tag::ORDER_QTY, qty)
+        .with(tag::ORD_TYPE, 2)
+        .with(tag::PRICE, price)
+}
+
+#[tokio::test]
+async fn orders_map_to_execution_reports() {
+    let addr = start_gateway().await;
+    let mut maker = Initiator::connect(&addr, "MAKER").await;
+    let mut taker = Initiator::connect(&addr, "TAKER").await;
+    maker.logon(true, 30).await;
+    taker.logon(true, 30).await;
+
+    maker.send(limit("m1", "2", "100", "5")).await;
+    let new = maker.recv().await;
+    assert_eq!(new.msg_type(), msg_type::EXECUTION_REPORT);
+    assert_eq!(new.get(tag::EXEC_TYPE), Some("0"));
+    assert_eq!(new.get(tag::ORD_STATUS), Some("0"));
+    assert_eq!(new.get(tag::LEAVES_QTY), Some("5"));
+    let order_id = new.get(tag::ORDER_ID).unwrap().to_string();
+
+    taker
+        .send(limit("t1", "1", "101", "2").with(tag::TIME_IN_FORCE, 3))
+        .await;
+    assert_eq!(taker.recv().await.get(tag::EXEC_TYPE), Some("0"));
+    let fill = taker.recv().await;
+    assert_eq!(fill.get(tag::EXEC_TYPE), Some("F"));
+    assert_eq!(fill.get(tag::ORD_STATUS), Some("2"));
+    assert_eq!(fill.get(tag::LAST_PX), Some("100"));
+    assert_eq!(fill.get(tag::LAST_QTY), Some("2"));
+
+    let maker_fill = maker.recv().await;
+    assert_eq!(maker_fill.get(tag::CL_ORD_ID), Some("m1"));
+    assert_eq!(maker_fill.get(tag::EXEC_TYPE), Some("F"));
+    assert_eq!(maker_fill.get(tag::ORD_STATUS), Some("1"));
+    assert_eq!(maker_fill.get(tag::CUM_QTY), Some("2"));
+    assert_eq!(maker_fill.get(tag::LEAVES_QTY), Some("3"));
+
+    let replace = FixMessage::new(msg_type::ORDER_CANCEL_REPLACE_REQUEST)
+        .with(tag::ORIG_CL_ORD_ID, "m1")
+        .with(tag::CL_ORD_ID, "m2")
+        .with(tag::SYMBOL, "BTC/USDT")
+        .with(tag::SIDE, 2)
+        .with(tag::ORDER_QTY, 6)
+        .with(tag::ORD_TYPE, 2)
+        .with(tag::PRICE, 102);
+    maker.send(replace).await;
+    let replaced = maker.recv().await;
+    assert_eq!(replaced.get(tag::EXEC_TYPE), Some("5"));
+    assert_eq!(replaced.get(tag::ORIG_CL_ORD_ID), Some("m1"));
+    assert_eq!(replaced.get(tag::ORDER_ID), Some(order_id.as_str()));
+    assert_eq!(replaced.get(tag::PRICE), Some("102"));
+    assert_eq!(replaced.get(tag::LEAVES_QTY), Some("4"));
+
+    let cancel = |orig: &str, cl: &str| {
+        FixMessage::new(msg_type::ORDER_CANCEL_REQUEST)
+            .with(tag::ORIG_CL_ORD_ID, orig)
+            .with(tag::CL_ORD_ID, cl)
+            .with(tag::SYMBOL, "BTC/USDT")
+            .with(tag::SIDE, 2)
+    };
+    maker.send(cancel("m2", "m3")).await;
+    let cancelled = maker.recv().await;
+    assert_eq!(cancelled.get(tag::EXEC_TYPE), Some("4"));
+    assert_eq!(cancelled.get(tag::ORD_STATUS), Some("4"));
+    assert_eq!(cancelled.get(tag::LEAVES_QTY), Some("0"));
+
+    maker.send(cancel("m3", "m4")).await;
+    let too_late = maker.recv().await;
+    assert_eq!(too_late.msg_type(), msg_type::ORDER_CANCEL_REJECT);
+    assert_eq!(too_late.get(tag::CXL_REJ_RESPONSE_TO), Some("1"));
+    assert_eq!(too_late.get(tag::CXL_REJ_REASON), Some("0"));
+
+    maker.send(cancel("nope", "m5")).await;
+    let unknown = maker.recv().await;
+    assert_eq!(unknown.get(tag::CXL_REJ_REASON), Some("1"));
+    assert_eq!(unknown.get(tag::ORDER_ID), Some("NONE"));
+
+    let mut bad_symbol = limit("m6", "1", "100", "1");
+    bad_symbol.set(tag::SYMBOL, "ETH/USDT");
+    maker.send(bad_symbol).await;
+    let rejected = maker.recv().await;
+    assert_eq!(rejected.get(tag::EXEC_TYPE), Some("8"));
+    assert_eq!(rejected.get(tag::ORD_REJ_REASON), Some("1"));
+
+    maker.send(limit("m1", "1", "100", "1")).await;
+    assert_eq!(maker.recv().await.get(tag::ORD_REJ_REASON), Some("6"));
+
+    let no_qty = FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+        .with(tag::CL_ORD_ID, "m7")
+        .with(tag::SYMBOL, "BTC/USDT")
+        .with(tag::SIDE, 1)
+        .with(tag::ORD_TYPE, 1);
+    maker.send(no_qty).await;
+    let reject = maker.recv().await;
+    assert_eq!(reject.msg_type(), msg_type::REJECT);
+    assert_eq!(reject.get(tag::REF_TAG_ID), Some("38"));
+
+    maker.send(FixMessage::new("V")).await;
+    let unsupported = maker.recv().await;
+    assert_eq!(unsupported.msg_type(), msg_type::BUSINESS_MESSAGE_REJECT);
+    assert_eq!(unsupported.get(tag::REF_MSG_TYPE), Some("V"));
+}
+
+#[tokio::test]
+async fn session_layer_recovers_gaps_and_survives_reconnects() {
+    let addr = start_gateway().await;
+    let mut client = Initiator::connect(&addr, "CLIENT").await;
+    let logon = client.logon(true, 1).await;
+    assert_eq!(logon.seq_num(), Some(1));
+    assert_eq!(logon.get(tag::HEART_BT_INT), Some("1"));
+
+    client
+        .send(FixMessage::new(msg_type::TEST_REQUEST).with(tag::TEST_REQ_ID, "ping"))
+        .await;
+    let heartbeat = client.recv().await;
+    assert_eq!(heartbeat.msg_type(), msg_type::HEARTBEAT);
+    assert_eq!(heartbeat.get(tag::TEST_REQ_ID), Some("ping"));
+
+    // Skip MsgSeqNum 3: the acceptor asks for it instead of processing 4.
+    client.next_seq = 4;
+    client.send(limit("lost", "1", "90", "1")).await;
+    let resend = client.recv().await;
+    assert_eq!(resend.msg_type(), msg_type::RESEND_REQUEST);
+    assert_eq!(resend.get(tag::BEGIN_SEQ_NO), Some("3"));
+    let gap_fill = FixMessage::new(msg_type::SEQUENCE_RESET)
+        .with(tag::POSS_DUP_FLAG, "Y")
+        .with(tag::GAP_FILL_FLAG, "Y")
+        .with(tag::NEW_SEQ_NO, 5);
+    client.send_as(3, gap_fill).await;
+
+    client.send(limit("a", "1", "90", "1")).await;
+    let new = client.recv().await;
+    assert_eq!(new.get(tag::CL_ORD_ID), Some("a"));
+    let report_seq = new.seq_num().unwrap();
+
+    // Stay silent past the heartbeat interval and answer the TestRequest.
+    let test_request = client.recv().await;
+    assert_eq!(test_request.msg_type(), msg_type::TEST_REQUEST);
+    let test_req_id = test_request.get(tag::TEST_REQ_ID).unwrap().to_string();
+    client
+        .send(FixMessage::new(msg_type::HEARTBEAT).with(tag::TEST_REQ_ID, test_req_id))
+        .await;
+
+    client
+        .send(
+            FixMessage::new(msg_type::RESEND_REQUEST)
+                .with(tag::BEGIN_SEQ_NO, 1)
+                .with(tag::END_SEQ_NO, 0),
+        )
+        .await;
+    let admin_gap = client.recv().await;
+    assert_eq!(admin_gap.msg_type(), msg_type::SEQUENCE_RESET);
+    assert_eq!(admin_gap.seq_num(), Some(1));
+    assert_eq!(
+        admin_gap.get(tag::NEW_SEQ_NO),
+        Some(report_seq.to_string().as_str())
+    );
+    let resent = client.recv().await;
+    assert_eq!(resent.seq_num(), Some(report_seq));
+    assert_eq!(resent.get(tag::POSS_DUP_FLAG), Some("Y"));
+    assert_eq!(resent.get(tag::CL_ORD_ID), Some("a"));
+    assert!(resent.get(tag::ORIG_SENDING_TIME).is_some());
+
+    client.send(FixMessage::new(msg_type::LOGOUT)).await;
+    loop {
+        let msg = client.recv().await;
+        if msg.msg_type() == msg_type::LOGOUT {
+            break;
+        }
+    }
+    assert!(client.recv_any().await.is_none());
+
+    // Sequence numbers persist: a stale Logon is refused...
+    let next_seq = client.next_seq;
+    let mut stale = Initiator::connect(&addr, "CLIENT").await;
+    stale
+        .send(
+            FixMessage::new(msg_type::LOGON)
+                .with(tag::ENCRYPT_METHOD, 0)
+                .with(tag::HEART_BT_INT, 30),
+        )
+        .await;
+    let refused = stale.recv().await;
+    assert_eq!(refused.msg_type(), msg_type::LOGOUT);
+    assert!(refused.get(tag::TEXT).unwrap().contains("too low"));
+    assert!(stale.recv_any().await.is_none());
+
+    // ...and the right one continues where the session left off.
+    let mut resumed = Initiator::connect(&addr, "CLIENT").await;
+    resumed.next_seq = next_seq;
+    let logon = resumed.logon(false, 30).await;
+    assert!(logon.seq_num().unwrap() > report_seq);
+}
+
+#[tokio::test]
+async fn engine_rejections_carry_reasons() {
+    let (addr, gateway) = start_gateway_with(|engine| {
+        let chain = RiskChain::new().with(MaxOrderQty("5".parse().unwrap()));
+        engine.set_risk_chain(&pair(), None, chain).unwrap();
+    })
+    .await;
+    let mut client = Initiator::connect(&addr, "CLIENT").await;
+    client.logon(true, 30).await;
+
+    client.send(limit("c1", "1", "100", "6")).await;
+    let rejected = client.recv().await;
+    assert_eq!(rejected.get(tag::EXEC_TYPE), Some("8"));
+    assert_eq!(rejected.get(tag::ORD_REJ_REASON), Some("3"));
+    assert_eq!(
+        rejected.get(tag::TEXT),
+        Some("max order quantity rejected the order: quantity 6 exceeds 5")
+    );
+
+    client.send(limit("c2", "1", "100", "1")).await;
+    assert_eq!(client.recv().await.get(tag::EXEC_TYPE), Some("0"));
+    gateway
+        .with_engine(|engine| engine.set_market_state(&pair(), TradingState::Halted))
+        .unwrap();
+
+    let cancel = FixMessage::new(msg_type::ORDER_CANCEL_REQUEST)
+        .with(tag::ORIG_CL_ORD_ID, "c2")
+        .with(tag::CL_ORD_ID, "c3")
+        .with(tag::SYMBOL, "BTC/USDT")
+        .with(tag::SIDE, 1);
+    client.send(cancel).await;
+    let refused = client.recv().await;
+    assert_eq!(refused.msg_type(), msg_type::ORDER_CANCEL_REJECT);
+    assert_eq!(refused.get(tag::CXL_REJ_REASON), Some("99"));
+    assert_eq!(
+        refused.get(tag::TEXT),
+        Some("Market for BTC_USDT is halted")
+    );
+
+    client.send(limit("c4", "1", "100", "1")).await;
+    let rejected = client.recv().await;
+    assert_eq!(rejected.get(tag::EXEC_TYPE), Some("8"));
+    assert_eq!(rejected.get(tag::ORD_REJ_REASON), Some("2"));
+    assert_eq!(
+        rejected.get(tag::TEXT),
+        Some("Market for BTC_USDT is halted")
+    );
+}
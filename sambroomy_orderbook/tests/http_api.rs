@@ -0,0 +1,260 @@
+mod common;
+
+use common::pair;
+use orderbooklib::{
+    HttpApi, MatchingEngine, MaxOrderQty, OrderRequest, OrderType, RiskChain, Side, TradingState,
+};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+async fn start_api() -> String {
+    start_api_with(|_| {}).await
+}
+
+async fn start_api_with(configure: impl FnOnce(&mut MatchingEngine)) -> String {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    configure(&mut engine);
+    let api = HttpApi::new(engine);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { api.serve(listener).await });
+    base
+}
+
+async fn get(client: &Client, url: String) -> (StatusCode, Value) {
+    let response = client.get(url).send().await.unwrap();
+    let status = response.status();
+    (status, response.json().await.unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn orders_and_market_data_round_trip() {
+    let base = start_api().await;
+    let client = Client::new();
+
+    let (status, markets) = get(&client, format!("{base}/markets")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(markets, json!(["BTC_USDT"]));
+
+    for (side, price) in [("sell", "101"), ("sell", "102"), ("buy", "99")] {
+        let response = client
+            .post(format!("{base}/markets/BTC_USDT/orders"))
+            .json(&json!({"side": side, "type": "limit", "price": price, "qty": "4"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let response = client
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "buy", "type": "market", "qty": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let placed: Value = response.json().await.unwrap();
+    assert_eq!(placed["order"]["status"], "filled");
+    assert_eq!(placed["trades"][0]["price"], "101");
+
+    let (_, book) = get(&client, format!("{base}/markets/BTC_USDT/book?depth=1")).await;
+    assert_eq!(book["asks"], json!([["101", "3"]]));
+    assert_eq!(book["bids"], json!([["99", "4"]]));
+
+    let (_, bbo) = get(&client, format!("{base}/markets/BTC_USDT/bbo")).await;
+    assert_eq!(bbo["bid"]["price"], "99");
+    assert_eq!(bbo["ask"]["qty"], "3");
+
+    let (_, spread) = get(&client, format!("{base}/markets/BTC_USDT/spread")).await;
+    assert_eq!(spread["spread"], "2");
+
+    let (_, volume) = get(&client, format!("{base}/markets/BTC_USDT/volume")).await;
+    assert_eq!(volume["volume"], "11");
+
+    let response = client
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "buy", "type": "limit", "price": "98", "qty": "2"}))
+        .send()
+        .await
+        .unwrap();
+    let placed: Value = response.json().await.unwrap();
+    let id = placed["order"]["order_id"].as_str().unwrap().to_string();
+
+    let (status, order) = get(&client, format!("{base}/markets/BTC_USDT/orders/{id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(order["status"], "open");
+
+    let response = client
+        .delete(format!("{base}/markets/BTC_USDT/orders/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cancelled: Value = response.json().await.unwrap();
+    assert_eq!(cancelled["status"], "cancelled");
+
+    let (status, _) = get(&client, format!("{base}/markets/BTC_USDT/orders/{id}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn errors_map_to_http_status_codes() {
+    let base = start_api().await;
+    let client = Client::new();
+
+    let (status, body) = get(&client, format!("{base}/markets/ETH_USDT/book")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].as_str().unwrap().contains("does not exist"));
+
+    let (status, _) = get(&client, format!("{base}/markets/BTCUSDT/bbo")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = get(
+        &client,
+        format!("{base}/markets/BTC_USDT/orders/not-a-uuid"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let unknown = "0191d6a4-0000-7000-8000-000000000000";
+    let response = client
+        .delete(format!("{base}/markets/BTC_USDT/orders/{unknown}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    for body in [
+        json!({"side": "buy", "type": "limit", "qty": "1"}),
+        json!({"side": "buy", "type": "limit", "price": "100", "qty": "0"}),
+    ] {
+        let response = client
+            .post(format!("{base}/markets/BTC_USDT/orders"))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let response = client
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "sideways", "qty": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn risk_rejections_are_unprocessable() {
+    let base = start_api_with(|engine| {
+        let chain = RiskChain::new().with(MaxOrderQty("5".parse().unwrap()));
+        engine.set_risk_chain(&pair(), None, chain).unwrap();
+    })
+    .await;
+    let response = Client::new()
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "buy", "type": "limit", "price": "100", "qty": "6"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(
+        body["error"],
+        "max order quantity rejected the order: quantity 6 exceeds 5"
+    );
+}
+
+#[tokio::test]
+async fn orders_are_refused_once_the_ledger_is_enabled() {
+    let base = start_api_with(|engine| engine.enable_ledger().unwrap()).await;
+    let response = Client::new()
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "buy", "type": "limit", "price": "100", "qty": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(
+        body["error"],
+        "orders need an account while the ledger is enabled"
+    );
+}
+
+#[tokio::test]
+async fn trading_state_rejections_are_conflicts() {
+    let resting = OrderRequest::new(Side::Bid, 1, OrderType::limit(99u32));
+    let base = start_api_with(|engine| {
+        engine.place_order(&pair(), resting).unwrap();
+        engine
+            .set_market_state(&pair(), TradingState::Halted)
+            .unwrap();
+    })
+    .await;
+    let client = Client::new();
+
+    let response = client
+        .delete(format!("{base}/markets/BTC_USDT/orders/{}", resting.id()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "Market for BTC_USDT is halted");
+
+    let response = client
+        .post(format!("{base}/markets/BTC_USDT/orders"))
+        .json(&json!({"side": "buy", "type": "limit", "price": "100", "qty": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn deep_books_and_terminal_orders_are_served() {
+    let mut resting = None;
+    let base = start_api_with(|engine| {
+        engine.set_order_retention(Some(std::time::Duration::from_secs(60)));
+        for price in 1..=30u32 {
+            engine
+                .place_order(
+                    &pair(),
+                    OrderRequest::new(Side::Ask, 1, OrderType::limit(100 + price)),
+                )
+                .unwrap();
+        }
+        let (placed, _) = engine
+            .place_order(
+                &pair(),
+                OrderRequest::new(Side::Bid, 2, OrderType::limit(90)),
+            )
+            .unwrap();
+        resting = Some(placed.get_id());
+    })
+    .await;
+    let client = Client::new();
+
+    let (status, book) = get(&client, format!("{base}/markets/BTC_USDT/book?depth=100")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book["asks"].as_array().unwrap().len(), 30);
+    assert_eq!(book["asks"][29], json!(["130", "1"]));
+    let (_, shallow) = get(&client, format!("{base}/markets/BTC_USDT/book")).await;
+    assert_eq!(shallow["asks"].as_array().unwrap().len(), 20);
+    assert_eq!(shallow["version"], book["version"]);
+
+    let id = resting.unwrap();
+    client
+        .delete(format!("{base}/markets/BTC_USDT/orders/{id}"))
+        .send()
+        .await
+        .unwrap();
+    let (status, order) = get(&client, format!("{base}/markets/BTC_USDT/orders/{id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(order["status"], "cancelled");
+}
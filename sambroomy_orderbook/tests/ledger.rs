@@ -0,0 +1,226 @@
+mod common;
+
+use common::{dec, pair};
+use orderbooklib::{
+    AccountId, Balance, FEE_ACCOUNT, FeeSchedule, MatchingEngine, OrderId, OrderRequest, OrderType,
+    Side,
+};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+const ALICE: AccountId = 1;
+const BOB: AccountId = 2;
+
+fn balance(free: &str, reserved: &str) -> Balance {
+    Balance {
+        free: dec(free),
+        reserved: dec(reserved),
+    }
+}
+
+fn engine() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.enable_ledger().unwrap();
+    let ledger = engine.ledger_mut().unwrap();
+    ledger.deposit(ALICE, "BTC", dec("10")).unwrap();
+    ledger.deposit(BOB, "USDT", dec("1000")).unwrap();
+    engine
+}
+
+#[test]
+fn orders_reserve_settle_and_release() {
+    let mut engine = engine();
+    let limit = |price: &str| OrderType::Limit(dec(price));
+
+    let (ask, _) = engine
+        .place_order_for(
+            ALICE,
+            &pair(),
+            OrderRequest::new(Side::Ask, 4, limit("100")),
+        )
+        .unwrap();
+    let ledger = engine.ledger().unwrap();
+    assert_eq!(ledger.balance(ALICE, "BTC"), balance("6", "4"));
+
+    // Bob reserves at his limit but pays the resting price; the improvement
+    // and the unfilled part of his IOC are released.
+    let ioc = OrderRequest::new(Side::Bid, 5, OrderType::IOC(dec("110")));
+    let (_, fills) = engine.place_order_for(BOB, &pair(), ioc).unwrap();
+    assert_eq!(fills.len(), 1);
+    let ledger = engine.ledger().unwrap();
+    assert_eq!(ledger.balance(BOB, "USDT"), balance("600", "0"));
+    assert_eq!(ledger.balance(BOB, "BTC"), balance("4", "0"));
+    assert_eq!(ledger.balance(ALICE, "USDT"), balance("400", "0"));
+    assert_eq!(ledger.balance(ALICE, "BTC"), balance("6", "0"));
+    assert_eq!(ledger.reservation(ask.get_id()), None);
+
+    let too_big = OrderRequest::new(Side::Bid, 7, limit("90"));
+    let err = engine.place_order_for(BOB, &pair(), too_big).unwrap_err();
+    assert!(err.to_string().contains("insufficient USDT"), "{}", err);
+    assert_eq!(engine.get_depth(&pair()), Ok((0, 0)));
+
+    let (bid, _) = engine
+        .place_order_for(BOB, &pair(), OrderRequest::new(Side::Bid, 5, limit("90")))
+        .unwrap();
+    let id = bid.get_id();
+    assert_eq!(
+        engine.ledger().unwrap().balance(BOB, "USDT"),
+        balance("150", "450")
+    );
+    engine.reduce_order(&pair(), id, dec("2")).unwrap();
+    assert_eq!(
+        engine.ledger().unwrap().balance(BOB, "USDT"),
+        balance("330", "270")
+    );
+    assert!(
+        engine
+            .amend_order(&pair(), id, dec("100"), dec("7"))
+            .is_err()
+    );
+    engine
+        .amend_order(&pair(), id, dec("95"), dec("6"))
+        .unwrap();
+    assert_eq!(
+        engine.ledger().unwrap().balance(BOB, "USDT"),
+        balance("30", "570")
+    );
+    engine.cancel_order(&pair(), id).unwrap();
+    assert_eq!(
+        engine.ledger().unwrap().balance(BOB, "USDT"),
+        balance("600", "0")
+    );
+
+    let ledger = engine.ledger_mut().unwrap();
+    assert!(ledger.withdraw(BOB, "USDT", dec("600.01")).is_err());
+    assert_eq!(
+        ledger.withdraw(BOB, "USDT", dec("600")),
+        Ok(balance("0", "0"))
+    );
+    assert!(ledger.deposit(BOB, "USDT", Decimal::ZERO).is_err());
+}
+
+#[test]
+fn ledger_needs_accounts_and_empty_books() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    let order = OrderRequest::new(Side::Ask, 1, OrderType::Limit(dec("100")));
+    assert!(engine.place_order_for(ALICE, &pair(), order).is_err());
+    engine.place_order(&pair(), order).unwrap();
+    assert!(engine.enable_ledger().is_err());
+    engine.cancel_order(&pair(), order.id()).unwrap();
+    engine.enable_ledger().unwrap();
+    assert!(engine.place_order(&pair(), order).is_err());
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Deposit(AccountId, bool, u32),
+    Withdraw(AccountId, bool, u32),
+    Place(AccountId, Side, u32, Option<u32>, bool),
+    Cancel(usize),
+    Reduce(usize, u32),
+    Amend(usize, u32, u32),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let account = 1..=3u64;
+    let side = prop_oneof![Just(Side::Bid), Just(Side::Ask)];
+    prop_oneof![
+        1 => (account.clone(), any::<bool>(), 1..500u32).prop_map(|(a, b, n)| Op::Deposit(a, b, n)),
+        1 => (account.clone(), any::<bool>(), 1..200u32).prop_map(|(a, b, n)| Op::Withdraw(a, b, n)),
+        4 => (account, side, 1..20u32, proptest::option::weighted(0.9, 95..106u32), any::<bool>())
+            .prop_map(|(a, s, q, p, ioc)| Op::Place(a, s, q, p, ioc)),
+        1 => any::<usize>().prop_map(Op::Cancel),
+        1 => (any::<usize>(), 1..10u32).prop_map(|(i, q)| Op::Reduce(i, q)),
+        1 => (any::<usize>(), 95..106u32, 1..20u32).prop_map(|(i, p, q)| Op::Amend(i, p, q)),
+    ]
+}
+
+fn asset(base: bool) -> &'static str {
+    if base { "BTC" } else { "USDT" }
+}
+
+/// Free and reserved balances never go negative outside the fee account,
+/// reserved balances are exactly what resting orders hold, and trading never
+/// creates or destroys an asset.
+fn check(engine: &MatchingEngine, placed: &[OrderId], deposited: &[Decimal; 2]) {
+    let ledger = engine.ledger().unwrap();
+    for (i, asset) in ["BTC", "USDT"].into_iter().enumerate() {
+        assert_eq!(ledger.total(asset), deposited[i], "{} not conserved", asset);
+        for account in ledger.accounts() {
+            let balance = ledger.balance(account, asset);
+            if account != FEE_ACCOUNT {
+                assert!(balance.free >= Decimal::ZERO && balance.reserved >= Decimal::ZERO);
+            }
+            let held: Decimal = placed
+                .iter()
+                .filter_map(|id| ledger.reservation(*id))
+                .filter(|r| r.account == account && r.asset == asset)
+                .map(|r| r.amount)
+                .sum();
+            assert_eq!(balance.reserved, held);
+        }
+    }
+    for id in placed {
+        let resting = engine.get_order(&pair(), *id).unwrap();
+        assert_eq!(resting.is_some(), ledger.reservation(*id).is_some());
+    }
+}
+
+proptest! {
+    #[test]
+    fn balances_are_conserved(ops in proptest::collection::vec(op(), 1..80)) {
+        let mut engine = MatchingEngine::new();
+        engine.add_market(pair()).unwrap();
+        engine.enable_ledger().unwrap();
+        let rebate = FeeSchedule::flat(Decimal::new(-1, 4), Decimal::new(2, 3)).unwrap();
+        engine.set_fee_schedule(&pair(), rebate).unwrap();
+        let mut placed = Vec::new();
+        let mut deposited = [Decimal::ZERO; 2];
+
+        for op in ops {
+            let pick = |i: usize| (!placed.is_empty()).then(|| placed[i % placed.len()]);
+            match op {
+                Op::Deposit(account, base, n) => {
+                    let ledger = engine.ledger_mut().unwrap();
+                    ledger.deposit(account, asset(base), n.into()).unwrap();
+                    deposited[usize::from(!base)] += Decimal::from(n);
+                }
+                Op::Withdraw(account, base, n) => {
+                    let ledger = engine.ledger_mut().unwrap();
+                    if ledger.withdraw(account, asset(base), n.into()).is_ok() {
+                        deposited[usize::from(!base)] -= Decimal::from(n);
+                    }
+                }
+                Op::Place(account, side, qty, price, ioc) => {
+                    let order_type = match (price, ioc) {
+                        (None, _) => OrderType::Market,
+                        (Some(p), true) => OrderType::IOC(p.into()),
+                        (Some(p), false) => OrderType::Limit(p.into()),
+                    };
+                    let order = OrderRequest::new(side, qty, order_type);
+                    if engine.place_order_for(account, &pair(), order).is_ok() {
+                        placed.push(order.id());
+                    }
+                }
+                Op::Cancel(i) => {
+                    if let Some(id) = pick(i) {
+                        engine.cancel_order(&pair(), id).unwrap();
+                    }
+                }
+                Op::Reduce(i, qty) => {
+                    if let Some(id) = pick(i) {
+                        engine.reduce_order(&pair(), id, qty.into()).unwrap();
+                    }
+                }
+                Op::Amend(i, price, qty) => {
+                    if let Some(id) = pick(i) {
+                        let _ = engine.amend_order(&pair(), id, price.into(), qty.into());
+                    }
+                }
+            }
+            check(&engine, &placed, &deposited);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{dec, limit, pair};
+use orderbooklib::{
+    AccountId, CircuitBreaker, MatchingEngine, OrderError, OrderRequest, OrderType, Side,
+    TradingPair, TradingState,
+};
+use rust_decimal::Decimal;
+
+fn rejected(state: TradingState, message: impl Into<String>) -> OrderError {
+    OrderError::MarketState {
+        state,
+        message: message.into(),
+    }
+}
+
+fn market() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine
+}
+
+#[test]
+fn states_gate_order_entry() {
+    let mut engine = market();
+    assert_eq!(engine.market_state(&pair()), Ok(TradingState::Open));
+    let bid = limit(Side::Bid, 1, "99");
+    engine.place_order(&pair(), bid).unwrap();
+    engine
+        .place_order(&pair(), limit(Side::Ask, 1, "101"))
+        .unwrap();
+
+    engine
+        .set_market_state(&pair(), TradingState::PostOnly)
+        .unwrap();
+    engine
+        .place_order(&pair(), limit(Side::Bid, 1, "100"))
+        .unwrap();
+    let err = engine.place_order(&pair(), limit(Side::Bid, 1, "101"));
+    assert_eq!(
+        err.unwrap_err(),
+        rejected(
+            TradingState::PostOnly,
+            "Market for BTC_USDT is post-only and the order would take liquidity"
+        )
+    );
+    let err = engine.place_order(&pair(), OrderRequest::new(Side::Ask, 1, OrderType::Market));
+    assert!(err.is_err());
+    assert!(
+        engine
+            .amend_order(&pair(), bid.id(), dec("101"), dec("1"))
+            .is_err()
+    );
+
+    engine
+        .set_market_state(&pair(), TradingState::CancelOnly)
+        .unwrap();
+    let err = engine.place_order(&pair(), limit(Side::Bid, 1, "90"));
+    assert_eq!(
+        err.unwrap_err(),
+        rejected(
+            TradingState::CancelOnly,
+            "Market for BTC_USDT is cancel-only"
+        )
+    );
+    assert!(
+        engine
+            .amend_order(&pair(), bid.id(), dec("98"), dec("1"))
+            .is_err()
+    );
+    engine.reduce_order(&pair(), bid.id(), dec("0.5")).unwrap();
+
+    engine
+        .set_market_state(&pair(), TradingState::Halted)
+        .unwrap();
+    let err = engine.cancel_order(&pair(), bid.id());
+    assert_eq!(
+        err.unwrap_err(),
+        rejected(TradingState::Halted, "Market for BTC_USDT is halted")
+    );
+    assert_eq!(engine.get_depth(&pair()), Ok((1, 2)));
+
+    engine
+        .set_market_state(&pair(), TradingState::Open)
+        .unwrap();
+    assert!(engine.cancel_order(&pair(), bid.id()).unwrap().is_some());
+    let (_, fills) = engine
+        .place_order(&pair(), limit(Side::Ask, 1, "100"))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+}
+
+#[test]
+fn closing_cancels_resting_orders_and_releases_funds() {
+    const ACCOUNT: AccountId = 1;
+    let mut engine = market();
+    engine.enable_ledger().unwrap();
+    engine
+        .ledger_mut()
+        .unwrap()
+        .deposit(ACCOUNT, "USDT", dec("1000"))
+        .unwrap();
+    engine
+        .place_order_for(ACCOUNT, &pair(), limit(Side::Bid, 5, "100"))
+        .unwrap();
+    assert_eq!(
+        engine.ledger().unwrap().balance(ACCOUNT, "USDT").free,
+        dec("500")
+    );
+
+    engine
+        .set_market_state(&pair(), TradingState::Closed)
+        .unwrap();
+    assert_eq!(engine.get_depth(&pair()), Ok((0, 0)));
+    assert_eq!(
+        engine.ledger().unwrap().balance(ACCOUNT, "USDT").free,
+        dec("1000")
+    );
+    assert_eq!(engine.ledger().unwrap().open_orders(ACCOUNT), 0);
+    let err = engine.place_order_for(ACCOUNT, &pair(), limit(Side::Bid, 1, "100"));
+    assert_eq!(
+        err.unwrap_err(),
+        rejected(TradingState::Closed, "Market for BTC_USDT is closed")
+    );
+    assert!(
+        engine
+            .set_market_state(&pair(), TradingState::Halted)
+            .is_err()
+    );
+    engine
+        .set_market_state(&pair(), TradingState::Open)
+        .unwrap();
+    engine
+        .place_order_for(ACCOUNT, &pair(), limit(Side::Bid, 1, "100"))
+        .unwrap();
+}
+
+#[test]
+fn circuit_breaker_halts_on_fast_moves() {
+    let mut engine = market();
+    assert!(CircuitBreaker::new(Decimal::ZERO, Duration::from_secs(60)).is_err());
+    let breaker = CircuitBreaker::new(dec("0.1"), Duration::from_secs(60)).unwrap();
+    engine.set_circuit_breaker(&pair(), Some(breaker)).unwrap();
+
+    let trade = |engine: &mut MatchingEngine, price: &str| {
+        engine.place_order(&pair(), limit(Side::Ask, 1, price))?;
+        engine.place_order(&pair(), limit(Side::Bid, 1, price))
+    };
+    trade(&mut engine, "100").unwrap();
+    trade(&mut engine, "109").unwrap();
+    trade(&mut engine, "110").unwrap();
+    assert_eq!(engine.market_state(&pair()), Ok(TradingState::Open));
+    // 111 is within 10% of 109 and 110 but not of 100: the bid that would
+    // print there is refused and the ask it would have hit keeps resting.
+    let err = trade(&mut engine, "111").unwrap_err();
+    assert_eq!(engine.market_state(&pair()), Ok(TradingState::Halted));
+    let reason = engine.halt_reason(&pair()).unwrap().unwrap().to_string();
+    assert!(reason.contains("111 moved more than 10% from 100"));
+    assert_eq!(
+        err,
+        rejected(
+            TradingState::Halted,
+            format!("Market for BTC_USDT is halted: {}", reason)
+        )
+    );
+    assert_eq!(engine.reader().snapshot(&pair()).unwrap().asks.len(), 1);
+    let err = trade(&mut engine, "111").unwrap_err();
+    assert!(matches!(
+        err,
+        OrderError::MarketState {
+            state: TradingState::Halted,
+            ..
+        }
+    ));
+
+    // Resuming starts measuring from scratch; the bid at 121 takes the ask
+    // left at 111.
+    engine
+        .set_market_state(&pair(), TradingState::Open)
+        .unwrap();
+    assert_eq!(engine.halt_reason(&pair()), Ok(None));
+    trade(&mut engine, "121").unwrap();
+    assert_eq!(engine.market_state(&pair()), Ok(TradingState::Open));
+    assert!(trade(&mut engine, "90").is_err());
+    assert_eq!(engine.market_state(&pair()), Ok(TradingState::Halted));
+    assert_eq!(engine.reader().snapshot(&pair()).unwrap().bids.len(), 0);
+
+    let other = TradingPair::new("X".into(), "Y".into());
+    assert!(engine.set_circuit_breaker(&other, None).is_err());
+    assert!(engine.set_market_state(&other, TradingState::Open).is_err());
+}
@@ -0,0 +1,265 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{dec, limit, pair};
+use orderbooklib::{
+    AccountId, MatchingEngine, OrderBook, OrderFilter, OrderId, OrderRequest, OrderStatus,
+    OrderType, Side, TradeOrder,
+};
+use rust_decimal::Decimal;
+
+const ALICE: AccountId = 1;
+const BOB: AccountId = 2;
+
+fn ids(found: Vec<&TradeOrder>) -> Vec<OrderId> {
+    found.iter().map(|o| o.id).collect()
+}
+
+fn status(book: &OrderBook, id: OrderId) -> Option<(OrderStatus, Decimal)> {
+    book.order_status(id).map(|r| (r.status, r.remaining_qty))
+}
+
+fn listed(engine: &MatchingEngine, filter: OrderFilter) -> Vec<OrderId> {
+    let found = engine.open_orders(&pair(), &filter).unwrap();
+    found.iter().map(|r| r.get_id()).collect()
+}
+
+#[test]
+fn open_orders_filter_by_side_and_price_in_priority_order() {
+    let mut book = OrderBook::default();
+    let orders = [
+        limit(Side::Bid, 1, "99"),
+        limit(Side::Bid, 2, "98"),
+        limit(Side::Bid, 3, "99"),
+        limit(Side::Ask, 4, "101"),
+        limit(Side::Ask, 5, "103"),
+    ];
+    for order in orders {
+        book.add_order(order);
+    }
+    assert_eq!(
+        ids(book.open_orders(None, ..)),
+        [0, 2, 1, 3, 4].map(|i| orders[i].id())
+    );
+    assert_eq!(
+        ids(book.open_orders(Some(Side::Bid), ..dec("99"))),
+        [orders[1].id()]
+    );
+    assert_eq!(
+        ids(book.open_orders(None, dec("99")..=dec("101"))),
+        [0, 2, 3].map(|i| orders[i].id())
+    );
+    assert!(book.open_orders(Some(Side::Ask), ..dec("101")).is_empty());
+}
+
+#[test]
+fn terminal_orders_are_kept_for_the_retention_period() {
+    let mut book = OrderBook::default();
+    let forgotten = limit(Side::Ask, 1, "100");
+    book.add_order(forgotten);
+    book.delete_order(forgotten.id());
+    assert!(book.order_status(forgotten.id()).is_none());
+
+    book.set_order_retention(Some(Duration::from_secs(60)));
+    let maker = limit(Side::Ask, 2, "100");
+    let cancelled = limit(Side::Ask, 3, "101");
+    let partial = limit(Side::Ask, 4, "102");
+    for order in [maker, cancelled, partial] {
+        book.add_order(order);
+    }
+    book.cancel_order(cancelled.id(), 3);
+    let taker = OrderRequest::new(Side::Bid, 3, OrderType::Market);
+    book.add_order(taker);
+    let ioc = OrderRequest::new(Side::Bid, 9, OrderType::IOC(dec("100")));
+    book.add_order(ioc);
+
+    assert_eq!(
+        status(&book, maker.id()),
+        Some((OrderStatus::Filled, dec("0")))
+    );
+    assert_eq!(
+        status(&book, cancelled.id()),
+        Some((OrderStatus::Cancelled, dec("0")))
+    );
+    assert_eq!(
+        status(&book, partial.id()),
+        Some((OrderStatus::PartiallyFilled, dec("3")))
+    );
+    assert_eq!(
+        status(&book, taker.id()),
+        Some((OrderStatus::Filled, dec("0")))
+    );
+    assert_eq!(
+        status(&book, ioc.id()),
+        Some((OrderStatus::Cancelled, dec("9")))
+    );
+
+    book.clear();
+    assert_eq!(
+        status(&book, partial.id()),
+        Some((OrderStatus::Cancelled, dec("3")))
+    );
+
+    book.set_order_retention(Some(Duration::ZERO));
+    let expired = limit(Side::Bid, 1, "100");
+    book.add_order(expired);
+    book.delete_order(expired.id());
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(book.order_status(expired.id()).is_none());
+}
+
+#[test]
+fn engine_lists_open_orders_by_owner_and_keeps_fill_history() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.enable_ledger().unwrap();
+    engine.set_order_retention(Some(Duration::from_secs(60)));
+    let ledger = engine.ledger_mut().unwrap();
+    ledger.deposit(ALICE, "BTC", dec("10")).unwrap();
+    ledger.deposit(BOB, "USDT", dec("1000")).unwrap();
+
+    let asks = [limit(Side::Ask, 2, "100"), limit(Side::Ask, 3, "101")];
+    for ask in asks {
+        engine.place_order_for(ALICE, &pair(), ask).unwrap();
+    }
+    let bid = limit(Side::Bid, 1, "90");
+    engine.place_order_for(BOB, &pair(), bid).unwrap();
+
+    let alice = OrderFilter {
+        owner: Some(ALICE),
+        ..Default::default()
+    };
+    assert_eq!(listed(&engine, alice.clone()), asks.map(|o| o.id()));
+    assert_eq!(
+        listed(
+            &engine,
+            OrderFilter {
+                side: Some(Side::Bid),
+                ..Default::default()
+            }
+        ),
+        [bid.id()]
+    );
+    assert_eq!(
+        listed(
+            &engine,
+            OrderFilter {
+                min_price: Some(dec("100.5")),
+                ..alice.clone()
+            }
+        ),
+        [asks[1].id()]
+    );
+
+    let sweep = OrderRequest::new(Side::Bid, 4, OrderType::Limit(dec("101")));
+    engine.place_order_for(BOB, &pair(), sweep).unwrap();
+    assert_eq!(listed(&engine, alice), [asks[1].id()]);
+
+    let fills = engine.fill_history(&pair(), sweep.id()).unwrap().unwrap();
+    let summary: Vec<_> = fills.iter().map(|f| (f.order_id, f.qty, f.price)).collect();
+    assert_eq!(
+        summary,
+        [
+            (asks[0].id(), dec("2"), dec("100")),
+            (asks[1].id(), dec("2"), dec("101"))
+        ]
+    );
+    assert!(fills[0].timestamp <= fills[1].timestamp);
+
+    let filled = engine.order_status(&pair(), asks[0].id()).unwrap().unwrap();
+    assert_eq!(filled.status, OrderStatus::Filled);
+    assert_eq!(filled.fills()[0].order_id, sweep.id());
+    assert!(engine.get_order(&pair(), asks[0].id()).unwrap().is_none());
+}
+
+#[test]
+fn fill_history_survives_amendments() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.set_order_retention(Some(Duration::from_secs(60)));
+
+    let maker = limit(Side::Ask, 5, "100");
+    engine.place_order(&pair(), maker).unwrap();
+    let first = OrderRequest::new(Side::Bid, 2, OrderType::Market);
+    engine.place_order(&pair(), first).unwrap();
+    engine
+        .amend_order(&pair(), maker.id(), dec("101"), dec("4"))
+        .unwrap()
+        .unwrap();
+    let second = limit(Side::Bid, 4, "101");
+    engine.place_order(&pair(), second).unwrap();
+
+    let fills = engine.fill_history(&pair(), maker.id()).unwrap().unwrap();
+    let summary: Vec<_> = fills.iter().map(|f| (f.order_id, f.qty, f.price)).collect();
+    assert_eq!(
+        summary,
+        [
+            (first.id(), dec("2"), dec("100")),
+            (second.id(), dec("4"), dec("101"))
+        ]
+    );
+    let filled = engine.order_status(&pair(), maker.id()).unwrap().unwrap();
+    assert_eq!(filled.status, OrderStatus::Filled);
+    assert_eq!(filled.initial_qty(), dec("6"));
+}
+
+#[test]
+fn engine_refuses_non_positive_reductions_and_amendments() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    let (placed, _) = engine
+        .place_order(&pair(), limit(Side::Ask, 5, "100"))
+        .unwrap();
+    let id = placed.get_id();
+
+    let err = engine.reduce_order(&pair(), id, dec("-5")).unwrap_err();
+    assert_eq!(err.to_string(), "qty must be positive");
+    let err = engine
+        .amend_order(&pair(), id, dec("0"), dec("5"))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "price must be positive");
+    let err = engine
+        .amend_order(&pair(), id, dec("90"), dec("0"))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "qty must be positive");
+
+    let order = engine.get_order(&pair(), id).unwrap().unwrap();
+    assert_eq!(
+        (order.status, order.remaining_qty),
+        (OrderStatus::Open, dec("5"))
+    );
+}
+
+#[test]
+fn engine_refuses_an_id_that_is_still_resting() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    let order = limit(Side::Ask, 5, "100");
+    engine.place_order(&pair(), order).unwrap();
+
+    let err = engine.place_order(&pair(), order).unwrap_err();
+    assert_eq!(err.to_string(), "duplicate order id");
+    assert_eq!(engine.get_depth(&pair()), Ok((1, 0)));
+
+    engine.cancel_order(&pair(), order.id()).unwrap();
+    engine.place_order(&pair(), order).unwrap();
+}
+
+#[test]
+fn owner_filters_need_the_ledger() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    let resting = limit(Side::Ask, 1, "100");
+    engine.place_order(&pair(), resting).unwrap();
+
+    let alice = OrderFilter {
+        owner: Some(ALICE),
+        ..Default::default()
+    };
+    assert_eq!(
+        engine.open_orders(&pair(), &alice).unwrap_err(),
+        "filtering by owner needs the ledger"
+    );
+    assert_eq!(listed(&engine, OrderFilter::default()), [resting.id()]);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{dec, pair};
+use orderbooklib::{
+    AccountId, FeeSchedule, MarkSource, MatchingEngine, OrderRequest, OrderType, PnlMethod, Side,
+};
+use rust_decimal::Decimal;
+
+const TRADER: AccountId = 1;
+const DEALER: AccountId = 2;
+
+fn place(engine: &mut MatchingEngine, account: AccountId, side: Side, qty: u32, price: &str) {
+    let order = OrderRequest::new(side, qty, OrderType::Limit(dec(price)));
+    engine.place_order_for(account, &pair(), order).unwrap();
+}
+
+#[test]
+fn positions_follow_fills_and_export_to_polars() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine.enable_ledger().unwrap();
+    engine.enable_positions();
+    let fees = FeeSchedule::flat(Decimal::ZERO, dec("0.001")).unwrap();
+    engine.set_fee_schedule(&pair(), fees).unwrap();
+    for account in [TRADER, DEALER] {
+        let ledger = engine.ledger_mut().unwrap();
+        ledger.deposit(account, "BTC", dec("100")).unwrap();
+        ledger.deposit(account, "USDT", dec("100000")).unwrap();
+    }
+
+    // The trader lifts two offers, then sells one back into a bid.
+    place(&mut engine, DEALER, Side::Ask, 2, "100");
+    place(&mut engine, DEALER, Side::Ask, 2, "110");
+    place(&mut engine, TRADER, Side::Bid, 4, "110");
+    place(&mut engine, DEALER, Side::Bid, 1, "120");
+    place(&mut engine, TRADER, Side::Ask, 1, "120");
+
+    let positions = engine.positions().unwrap();
+    let trader = positions.position(TRADER, &pair()).unwrap();
+    assert_eq!(trader.qty, dec("3"));
+    assert_eq!(trader.realized(PnlMethod::AverageCost), dec("15"));
+    assert_eq!(trader.realized(PnlMethod::Fifo), dec("20"));
+    // 0.004 BTC at 100 and 110 on the buys, 0.12 USDT on the sell.
+    assert_eq!(trader.fees, dec("0.54"));
+    let dealer = positions.position(DEALER, &pair()).unwrap();
+    assert_eq!(dealer.qty, dec("-3"));
+    assert_eq!(dealer.fees, Decimal::ZERO);
+    assert_eq!(positions.last_price(&pair()), Some(dec("120")));
+
+    assert_eq!(
+        engine.unrealized_pnl(TRADER, &pair(), PnlMethod::Fifo, MarkSource::Mid),
+        Ok(None)
+    );
+    place(&mut engine, DEALER, Side::Bid, 1, "114");
+    place(&mut engine, DEALER, Side::Ask, 1, "116");
+    assert_eq!(
+        engine.unrealized_pnl(TRADER, &pair(), PnlMethod::Fifo, MarkSource::Mid),
+        Ok(Some(dec("25")))
+    );
+    assert_eq!(
+        engine.unrealized_pnl(
+            TRADER,
+            &pair(),
+            PnlMethod::AverageCost,
+            MarkSource::LastTrade
+        ),
+        Ok(Some(dec("45")))
+    );
+
+    let frame = engine.positions_frame(MarkSource::Mid).unwrap();
+    assert_eq!(frame.height(), 2);
+    let column = |name: &str| frame.column(name).unwrap().f64().unwrap().get(0);
+    assert_eq!(
+        frame.column("account").unwrap().u64().unwrap().get(0),
+        Some(TRADER)
+    );
+    assert_eq!(column("qty"), Some(3.0));
+    assert_eq!(column("realized_fifo"), Some(20.0));
+    assert_eq!(column("mark"), Some(115.0));
+    assert_eq!(column("unrealized_fifo"), Some(25.0));
+    assert_eq!(column("unrealized_avg"), Some(30.0));
+}
+
+#[test]
+fn positions_need_to_be_enabled() {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    assert!(engine.positions().is_none());
+    assert!(engine.positions_frame(MarkSource::Mid).is_err());
+    assert!(
+        engine
+            .unrealized_pnl(TRADER, &pair(), PnlMethod::Fifo, MarkSource::Mid)
+            .is_err()
+    );
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use common::{dec, limit, pair};
+use orderbooklib::{
+    AccountId, DailyLossLimit, MarkSource, MatchingEngine, MaxNotional, MaxOpenOrders, MaxOrderQty,
+    MaxPosition, OrderError, OrderRequest, OrderType, PriceCollar, RiskChain, RiskCheck,
+    RiskContext, RiskRejection, Side, TradingPair,
+};
+
+const TRADER: AccountId = 1;
+const DEALER: AccountId = 2;
+
+fn market() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine.add_market(pair()).unwrap();
+    engine
+}
+
+fn accounts() -> MatchingEngine {
+    let mut engine = market();
+    engine.enable_ledger().unwrap();
+    engine.enable_positions();
+    for account in [TRADER, DEALER] {
+        let ledger = engine.ledger_mut().unwrap();
+        ledger.deposit(account, "BTC", dec("1000")).unwrap();
+        ledger.deposit(account, "USDT", dec("1000000")).unwrap();
+    }
+    engine
+}
+
+#[test]
+fn fat_fingers_are_stopped_before_the_book() {
+    let mut engine = market();
+    for price in ["100", "101", "102"] {
+        engine
+            .place_order(&pair(), limit(Side::Ask, dec("5"), price))
+            .unwrap();
+    }
+    let chain = RiskChain::new()
+        .with(MaxOrderQty(dec("100")))
+        .with(MaxNotional(dec("5000")));
+    engine.set_risk_chain(&pair(), None, chain).unwrap();
+
+    let huge = OrderRequest::new(Side::Bid, dec("1e9"), OrderType::Market);
+    let err = engine.place_order(&pair(), huge).unwrap_err();
+    assert_eq!(
+        err,
+        OrderError::Risk(RiskRejection::new(
+            "max order quantity",
+            "quantity 1000000000 exceeds 100"
+        ))
+    );
+    let err = engine.place_order(&pair(), OrderRequest::new(Side::Bid, 99, OrderType::Market));
+    assert!(err.unwrap_err().to_string().starts_with("max notional"));
+    assert_eq!(engine.get_depth(&pair()), Ok((3, 0)));
+
+    engine
+        .place_order(&pair(), limit(Side::Bid, dec("5"), "100"))
+        .unwrap();
+    assert_eq!(engine.get_depth(&pair()), Ok((2, 0)));
+}
+
+#[test]
+fn collars_follow_the_reference_price() {
+    let mut engine = market();
+    let collar = PriceCollar {
+        band: dec("0.05"),
+        reference: MarkSource::Mid,
+    };
+    engine
+        .set_risk_chain(&pair(), None, RiskChain::new().with(collar))
+        .unwrap();
+
+    // No mid yet, so anything goes.
+    engine
+        .place_order(&pair(), limit(Side::Bid, dec("1"), "90"))
+        .unwrap();
+    engine
+        .place_order(&pair(), limit(Side::Ask, dec("1"), "110"))
+        .unwrap();
+    assert!(
+        engine
+            .place_order(&pair(), limit(Side::Bid, dec("1"), "94.99"))
+            .is_err()
+    );
+    assert!(
+        engine
+            .place_order(&pair(), limit(Side::Ask, dec("1"), "105.01"))
+            .is_err()
+    );
+    engine
+        .place_order(&pair(), limit(Side::Bid, dec("1"), "95"))
+        .unwrap();
+
+    let collar = PriceCollar {
+        band: dec("0.01"),
+        reference: MarkSource::LastTrade,
+    };
+    engine
+        .set_risk_chain(&pair(), None, RiskChain::new().with(collar))
+        .unwrap();
+    engine
+        .place_order(&pair(), limit(Side::Ask, dec("1"), "95"))
+        .unwrap();
+    assert!(
+        engine
+            .place_order(&pair(), limit(Side::Ask, dec("1"), "96"))
+            .is_err()
+    );
+    let err = engine.place_order(&pair(), OrderRequest::new(Side::Ask, 1, OrderType::Market));
+    assert!(err.unwrap_err().to_string().contains("price 90 is outside"));
+}
+
+#[test]
+fn account_limits_apply_on_top_of_the_market_chain() {
+    let mut engine = accounts();
+    engine
+        .set_risk_chain(&pair(), None, RiskChain::new().with(MaxOrderQty(dec("50"))))
+        .unwrap();
+    let chain = RiskChain::new()
+        .with(MaxOpenOrders(2))
+        .with(MaxPosition(dec("10")));
+    engine.set_risk_chain(&pair(), Some(TRADER), chain).unwrap();
+
+    let first = limit(Side::Bid, dec("4"), "99");
+    engine.place_order_for(TRADER, &pair(), first).unwrap();
+    engine
+        .place_order_for(TRADER, &pair(), limit(Side::Bid, dec("4"), "98"))
+        .unwrap();
+    let err = engine.place_order_for(TRADER, &pair(), limit(Side::Bid, dec("1"), "97"));
+    assert!(err.unwrap_err().to_string().starts_with("max open orders"));
+    // Amending is not a new order, but still passes the other checks.
+    engine
+        .amend_order(&pair(), first.id(), dec("99"), dec("3"))
+        .unwrap();
+    let err = engine.amend_order(&pair(), first.id(), dec("99"), dec("60"));
+    assert!(
+        err.unwrap_err()
+            .to_string()
+            .starts_with("max order quantity")
+    );
+
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Ask, dec("7"), "98"))
+        .unwrap();
+    assert_eq!(engine.ledger().unwrap().open_orders(TRADER), 0);
+    let err = engine.place_order_for(TRADER, &pair(), limit(Side::Bid, dec("4"), "90"));
+    assert!(err.unwrap_err().to_string().starts_with("max position"));
+    // Reducing the position is always allowed.
+    engine
+        .place_order_for(TRADER, &pair(), limit(Side::Ask, dec("20"), "200"))
+        .unwrap_err();
+    engine
+        .place_order_for(TRADER, &pair(), limit(Side::Ask, dec("7"), "200"))
+        .unwrap();
+    // Other accounts only see the market chain.
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Bid, dec("40"), "90"))
+        .unwrap();
+}
+
+#[test]
+fn daily_loss_limit_stops_a_losing_account() {
+    let mut engine = accounts();
+    let chain = RiskChain::new().with(DailyLossLimit::new(dec("50")));
+    engine.set_risk_chain(&pair(), Some(TRADER), chain).unwrap();
+
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Ask, dec("10"), "100"))
+        .unwrap();
+    engine
+        .place_order_for(TRADER, &pair(), limit(Side::Bid, dec("10"), "100"))
+        .unwrap();
+    // The market drops 4: the trader is down 40 and may still trade.
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Bid, dec("1"), "96"))
+        .unwrap();
+    engine
+        .place_order_for(TRADER, &pair(), limit(Side::Ask, dec("1"), "96"))
+        .unwrap();
+    // Down another 5 on the remaining 9.
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Bid, dec("1"), "91"))
+        .unwrap();
+    engine
+        .place_order_for(DEALER, &pair(), limit(Side::Ask, dec("1"), "91"))
+        .unwrap();
+    let err = engine.place_order_for(TRADER, &pair(), limit(Side::Ask, dec("1"), "91"));
+    assert_eq!(
+        err.unwrap_err(),
+        OrderError::Risk(RiskRejection::new(
+            "daily loss limit",
+            "lost 85 today, limit 50"
+        ))
+    );
+}
+
+struct OnlyBids;
+
+impl RiskCheck for OnlyBids {
+    fn name(&self) -> &'static str {
+        "only bids"
+    }
+
+    fn check(&mut self, ctx: &RiskContext, order: &OrderRequest) -> Result<(), RiskRejection> {
+        assert_eq!(ctx.pair, &pair());
+        match order.side {
+            Side::Bid => Ok(()),
+            Side::Ask => Err(RiskRejection::new(self.name(), "asks are disabled")),
+        }
+    }
+}
+
+#[test]
+fn custom_checks_plug_into_the_chain() {
+    let mut engine = market();
+    let chain = RiskChain::new().with(OnlyBids).with(MaxOrderQty(dec("5")));
+    assert_eq!(
+        chain.names().collect::<Vec<_>>(),
+        vec!["only bids", "max order quantity"]
+    );
+    engine.set_risk_chain(&pair(), None, chain).unwrap();
+    let err = engine.place_order(&pair(), limit(Side::Ask, dec("10"), "100"));
+    assert_eq!(
+        err.unwrap_err().to_string(),
+        "only bids rejected the order: asks are disabled"
+    );
+    assert!(
+        engine
+            .set_risk_chain(
+                &TradingPair::new("X".into(), "Y".into()),
+                None,
+                RiskChain::new()
+            )
+            .is_err()
+    );
+}
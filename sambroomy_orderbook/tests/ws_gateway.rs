@@ -0,0 +1,324 @@
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use orderbooklib::{MatchingEngine, TradingPair, WsGateway};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, time::timeout};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+
+type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn start_gateway() -> String {
+    start_gateway_with(|_| {}).await
+}
+
+async fn start_gateway_with(configure: impl FnOnce(&mut MatchingEngine)) -> String {
+    let mut engine = MatchingEngine::new();
+    engine
+        .add_market(TradingPair::new("BTC".to_string(), "USDT".to_string()))
+        .unwrap();
+    configure(&mut engine);
+    let gateway = WsGateway::new(engine);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { gateway.serve(listener).await });
+    url
+}
+
+async fn connect(url: &str) -> Client {
+    connect_async(url).await.unwrap().0
+}
+
+async fn send(client: &mut Client, request: Value) {
+    client
+        .send(Message::Text(request.to_string().into()))
+        .await
+        .unwrap();
+}
+
+async fn recv(client: &mut Client) -> Value {
+    loop {
+        let message = timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("timed out waiting for the gateway")
+            .unwrap()
+            .unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+async fn request(client: &mut Client, request: Value) -> Value {
+    send(client, request).await;
+    recv(client).await
+}
+
+#[tokio::test]
+async fn acks_and_rejects_carry_correlation_ids() {
+    let url = start_gateway().await;
+    let mut client = connect(&url).await;
+
+    let markets = request(&mut client, json!({"id": 1, "op": "markets"})).await;
+    assert_eq!(markets["type"], "ack");
+    assert_eq!(markets["id"], 1);
+    assert_eq!(markets["result"]["markets"], json!(["BTC_USDT"]));
+
+    let placed = request(
+        &mut client,
+        json!({"id": "a", "op": "place", "market": "BTC_USDT", "side": "buy", "type": "limit", "price": "100", "qty": "2"}),
+    )
+    .await;
+    assert_eq!(placed["type"], "ack");
+    assert_eq!(placed["id"], "a");
+    assert_eq!(placed["result"]["order"]["status"], "open");
+    let order_id = placed["result"]["order"]["order_id"].clone();
+
+    let book = request(
+        &mut client,
+        json!({"id": "b", "op": "book", "market": "BTC_USDT", "depth": 5}),
+    )
+    .await;
+    assert_eq!(book["result"]["bids"], json!([["100", "2"]]));
+
+    let amended = request(
+        &mut client,
+        json!({"id": "c", "op": "amend", "market": "BTC_USDT", "order_id": order_id, "price": "101", "qty": "3"}),
+    )
+    .await;
+    assert_eq!(amended["result"]["order"]["price"], "101");
+    assert_eq!(amended["result"]["order"]["remaining_qty"], "3");
+
+    let cancelled = request(
+        &mut client,
+        json!({"id": "d", "op": "cancel", "market": "BTC_USDT", "order_id": order_id}),
+    )
+    .await;
+    assert_eq!(cancelled["result"]["order"]["status"], "cancelled");
+
+    let again = request(
+        &mut client,
+        json!({"id": "e", "op": "cancel", "market": "BTC_USDT", "order_id": order_id}),
+    )
+    .await;
+    assert_eq!(again["type"], "reject");
+    assert_eq!(again["id"], "e");
+
+    let unknown = request(
+        &mut client,
+        json!({"id": 7, "op": "place", "market": "ETH_USDT", "side": "buy", "type": "market", "qty": "1"}),
+    )
+    .await;
+    assert_eq!(unknown["type"], "reject");
+    assert_eq!(unknown["id"], 7);
+
+    let no_price = request(
+        &mut client,
+        json!({"id": 8, "op": "place", "market": "BTC_USDT", "side": "buy", "type": "limit", "qty": "1"}),
+    )
+    .await;
+    assert_eq!(no_price["type"], "reject");
+
+    let malformed = request(&mut client, json!({"id": 9, "op": "teleport"})).await;
+    assert_eq!(malformed["type"], "reject");
+    assert_eq!(malformed["id"], 9);
+}
+
+#[tokio::test]
+async fn subscribers_receive_trades_l2_diffs_and_own_fills() {
+    let url = start_gateway().await;
+    let mut maker = connect(&url).await;
+    let mut taker = connect(&url).await;
+
+    for channel in ["trades", "orders"] {
+        let ack = request(
+            &mut maker,
+            json!({"op": "subscribe", "channel": channel, "market": "BTC_USDT"}),
+        )
+        .await;
+        assert_eq!(ack["type"], "ack");
+    }
+    let ack = request(&mut maker, json!({"op": "subscribe", "channel": "l2"})).await;
+    assert_eq!(ack["result"]["channel"], "l2");
+    let snapshot = recv(&mut maker).await;
+    assert_eq!(snapshot["type"], "l2_snapshot");
+    assert_eq!(snapshot["bids"], json!([]));
+
+    let placed = request(
+        &mut maker,
+        json!({"id": 1, "op": "place", "market": "BTC_USDT", "side": "sell", "type": "limit", "price": "100", "qty": "5"}),
+    )
+    .await;
+    let maker_id = placed["result"]["order"]["order_id"].clone();
+    let update = recv(&mut maker).await;
+    assert_eq!(update["type"], "l2_update");
+    assert_eq!(update["asks"], json!([["100", "5"]]));
+
+    let taken = request(
+        &mut taker,
+        json!({"id": 2, "op": "place", "market": "BTC_USDT", "side": "buy", "type": "ioc", "price": "100", "qty": "2"}),
+    )
+    .await;
+    assert_eq!(taken["result"]["order"]["status"], "filled");
+    assert_eq!(taken["result"]["trades"][0]["maker_order_id"], maker_id);
+
+    let trade = recv(&mut maker).await;
+    assert_eq!(trade["type"], "trade");
+    assert_eq!(trade["side"], "buy");
+    assert_eq!(trade["qty"], "2");
+
+    let fill = recv(&mut maker).await;
+    assert_eq!(fill["type"], "order_update");
+    assert_eq!(fill["order_id"], maker_id);
+    assert_eq!(fill["status"], "partially_filled");
+    assert_eq!(fill["remaining_qty"], "3");
+
+    let update = recv(&mut maker).await;
+    assert_eq!(update["type"], "l2_update");
+    assert_eq!(update["asks"], json!([["100", "3"]]));
+
+    let ack = request(
+        &mut maker,
+        json!({"op": "unsubscribe", "channel": "l2", "market": "BTC_USDT"}),
+    )
+    .await;
+    assert_eq!(ack["type"], "reject");
+}
+
+#[tokio::test]
+async fn connections_cannot_touch_each_others_orders() {
+    let url = start_gateway().await;
+    let mut owner = connect(&url).await;
+    let mut other = connect(&url).await;
+
+    let placed = request(
+        &mut owner,
+        json!({"id": 1, "op": "place", "market": "BTC_USDT", "side": "sell", "type": "limit", "price": "100", "qty": "5"}),
+    )
+    .await;
+    let order_id = placed["result"]["order"]["order_id"].clone();
+
+    for op in [
+        json!({"id": 2, "op": "cancel", "market": "BTC_USDT", "order_id": order_id}),
+        json!({"id": 3, "op": "reduce", "market": "BTC_USDT", "order_id": order_id, "qty": "1"}),
+        json!({"id": 4, "op": "amend", "market": "BTC_USDT", "order_id": order_id, "price": "90", "qty": "5"}),
+        json!({"id": 5, "op": "order", "market": "BTC_USDT", "order_id": order_id}),
+    ] {
+        let rejected = request(&mut other, op.clone()).await;
+        assert_eq!(rejected["type"], "reject", "{}", op);
+        assert_eq!(rejected["id"], op["id"]);
+    }
+
+    let book = request(&mut owner, json!({"op": "book", "market": "BTC_USDT"})).await;
+    assert_eq!(book["result"]["asks"], json!([["100", "5"]]));
+
+    let cancelled = request(
+        &mut owner,
+        json!({"op": "cancel", "market": "BTC_USDT", "order_id": order_id}),
+    )
+    .await;
+    assert_eq!(cancelled["result"]["order"]["status"], "cancelled");
+}
+
+#[tokio::test]
+async fn orders_need_a_positive_qty() {
+    let url = start_gateway().await;
+    let mut client = connect(&url).await;
+
+    for qty in ["0", "-1"] {
+        let rejected = request(
+            &mut client,
+            json!({"id": 1, "op": "place", "market": "BTC_USDT", "side": "buy", "type": "limit", "price": "100", "qty": qty}),
+        )
+        .await;
+        assert_eq!(rejected["type"], "reject");
+        assert_eq!(rejected["reason"], "qty must be positive");
+    }
+}
+
+#[tokio::test]
+async fn reduce_and_amend_need_positive_values() {
+    let url = start_gateway().await;
+    let mut client = connect(&url).await;
+    let placed = request(
+        &mut client,
+        json!({"id": 1, "op": "place", "market": "BTC_USDT", "side": "sell", "type": "limit", "price": "100", "qty": "5"}),
+    )
+    .await;
+    let order_id = placed["result"]["order"]["order_id"].clone();
+
+    for (op, reason) in [
+        (
+            json!({"id": 2, "op": "reduce", "market": "BTC_USDT", "order_id": order_id, "qty": "-5"}),
+            "qty must be positive",
+        ),
+        (
+            json!({"id": 3, "op": "amend", "market": "BTC_USDT", "order_id": order_id, "price": "0", "qty": "5"}),
+            "price must be positive",
+        ),
+        (
+            json!({"id": 4, "op": "amend", "market": "BTC_USDT", "order_id": order_id, "price": "90", "qty": "0"}),
+            "qty must be positive",
+        ),
+    ] {
+        let rejected = request(&mut client, op.clone()).await;
+        assert_eq!(rejected["type"], "reject", "{}", op);
+        assert_eq!(rejected["reason"], reason, "{}", op);
+    }
+
+    let book = request(&mut client, json!({"op": "book", "market": "BTC_USDT"})).await;
+    assert_eq!(book["result"]["asks"], json!([["100", "5"]]));
+}
+
+#[tokio::test]
+async fn terminal_orders_and_deep_books_are_answered() {
+    let url =
+        start_gateway_with(|engine| engine.set_order_retention(Some(Duration::from_secs(60))))
+            .await;
+    let mut client = connect(&url).await;
+
+    for price in 1..=30 {
+        let placed = request(
+            &mut client,
+            json!({"op": "place", "market": "BTC_USDT", "side": "sell", "type": "limit", "price": (100 + price).to_string(), "qty": "1"}),
+        )
+        .await;
+        assert_eq!(placed["type"], "ack");
+    }
+    let book = request(
+        &mut client,
+        json!({"op": "book", "market": "BTC_USDT", "depth": 100}),
+    )
+    .await;
+    assert_eq!(book["result"]["asks"].as_array().unwrap().len(), 30);
+
+    let taker = request(
+        &mut client,
+        json!({"op": "place", "market": "BTC_USDT", "side": "buy", "type": "market", "qty": "1"}),
+    )
+    .await;
+    assert_eq!(taker["result"]["order"]["status"], "filled");
+    let taker_id = taker["result"]["order"]["order_id"].clone();
+
+    let resting = request(
+        &mut client,
+        json!({"op": "place", "market": "BTC_USDT", "side": "buy", "type": "limit", "price": "90", "qty": "2"}),
+    )
+    .await;
+    let resting_id = resting["result"]["order"]["order_id"].clone();
+    request(
+        &mut client,
+        json!({"op": "cancel", "market": "BTC_USDT", "order_id": resting_id}),
+    )
+    .await;
+
+    for (order_id, status) in [(taker_id, "filled"), (resting_id, "cancelled")] {
+        let order = request(
+            &mut client,
+            json!({"op": "order", "market": "BTC_USDT", "order_id": order_id}),
+        )
+        .await;
+        assert_eq!(order["type"], "ack", "{}", order);
+        assert_eq!(order["result"]["order"]["status"], status);
+    }
+}